@@ -1,4 +1,12 @@
 mod services;
 
-pub use services::{encode_files, get_encoding_profiles};
-pub use services::{EncodingProgressPayload, Profile};
+pub use services::{add_downmix_tracks, analyze_source, detect_forced_subtitles, flag_forced_subtitles, generate_previews, select_downmix_tracks};
+pub use services::{
+    clean_partial_outputs, encode_file, encode_files, get_encoding_profiles, ocr_subtitles, partial_output_path, probe_file, remux_files, verify_file, write_metadata,
+};
+pub use services::{AnalysisOptions, AudioTrack, Crop, DownmixOptions, FieldMode, PreviewOptions, Previews, SourceAnalysis};
+pub use services::{ContainerMetadata, MediaChapter, MediaInfo, MediaStream, QualityMetric, VerificationOptions, VerificationReport};
+pub use services::{Encoder, EncoderProgress, FfmpegEncoder, HandBrakeEncoder};
+pub use services::{EncodingEstimate, EncodingHistory, EncodingRecord, SourceProperties};
+pub use services::{EncodingJob, EncodingProgressPayload, EncodingQueue, ProcessPriority, Profile};
+pub use services::{SubtitleOcrOptions, SubtitleOutput};
@@ -0,0 +1,267 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::process::{Command, Stdio};
+use tracing::info;
+
+use crate::probe_file;
+
+/// The share of combed frames above which a source is deinterlaced.
+const INTERLACED_RATIO: f32 = 0.5;
+
+/// The share of combed frames and repeated fields above which a source is considered telecined.
+const TELECINE_RATIO: f32 = 0.1;
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldMode {
+    #[default]
+    Progressive,
+    Interlaced,
+    Telecined,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Crop {
+    pub top: u32,
+    pub bottom: u32,
+    pub left: u32,
+    pub right: u32,
+}
+
+/// The crop and deinterlace decisions for a source, applied as overrides on top of the encoding profile.
+///
+/// A decision is `None` if it was disabled in the `AnalysisOptions` or could not be detected, in which case
+/// the setting of the encoding profile is kept.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SourceAnalysis {
+    pub width: u32,
+    pub height: u32,
+    pub crop: Option<Crop>,
+    pub field_mode: Option<FieldMode>,
+    pub interlaced_ratio: f32,
+    pub repeated_ratio: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AnalysisOptions {
+    pub enabled: bool,
+    pub crop: bool,
+    pub deinterlace: bool,
+    pub samples: u32,
+    pub sample_duration: u32,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        Self { enabled: false, crop: true, deinterlace: true, samples: 5, sample_duration: 10 }
+    }
+}
+
+impl SourceAnalysis {
+    /// Returns the HandBrakeCLI arguments overriding the crop and deinterlace settings of the preset.
+    ///
+    /// Only the decisions that were made are overridden, the preset settings are kept for the others.
+    pub fn handbrake_args(&self) -> Vec<String> {
+        let mut args = vec![];
+
+        if let Some(crop) = self.crop {
+            args.extend(["--crop".to_string(), format!("{}:{}:{}:{}", crop.top, crop.bottom, crop.left, crop.right)]);
+        }
+
+        match self.field_mode {
+            None => {}
+            Some(FieldMode::Progressive) => args.extend(["--no-deinterlace", "--no-detelecine", "--no-comb-detect"].map(String::from)),
+            Some(FieldMode::Interlaced) => args.extend(["--deinterlace", "--no-detelecine"].map(String::from)),
+            Some(FieldMode::Telecined) => args.extend(["--detelecine", "--no-deinterlace"].map(String::from)),
+        }
+
+        args
+    }
+
+    /// Returns the ffmpeg video filters applying the crop and deinterlace decisions.
+    pub fn ffmpeg_filters(&self) -> Vec<String> {
+        let mut filters = vec![];
+
+        match self.field_mode {
+            None | Some(FieldMode::Progressive) => {}
+            Some(FieldMode::Interlaced) => filters.push("bwdif".to_string()),
+            Some(FieldMode::Telecined) => filters.extend(["fieldmatch".to_string(), "decimate".to_string()]),
+        }
+
+        if let Some(crop) = self.crop.filter(|crop| *crop != Crop::default()) {
+            let width = self.width.saturating_sub(crop.left + crop.right);
+            let height = self.height.saturating_sub(crop.top + crop.bottom);
+            filters.push(format!("crop={}:{}:{}:{}", width, height, crop.left, crop.top));
+        }
+
+        filters
+    }
+}
+
+/// Analyzes a source to decide which crop and deinterlace settings should be used to encode it.
+///
+/// The function runs ffmpeg with the `cropdetect` and `idet` filters on `samples` evenly distributed
+/// segments of the source. The smallest crop detected across all samples is used, so dark scenes don't
+/// cut off the picture. A source is considered telecined if both combed frames and repeated fields are
+/// detected, and interlaced if most of the frames are combed. Decisions that are disabled in the options, or
+/// for which ffmpeg reported nothing, are left as `None`.
+///
+/// # Arguments
+///
+/// * `ffmpeg_command` - A string slice that holds the analysis command to be executed (path of ffmpeg).
+/// * `ffprobe_command` - A string slice that holds the probing command used to read the duration and resolution (path of ffprobe).
+/// * `file` - A string slice that holds the path of the source file.
+/// * `options` - The `AnalysisOptions` describing which decisions should be made and how many samples should be analyzed.
+///
+/// # Returns
+///
+/// This function returns a `Result` containing the `SourceAnalysis` of the source.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The source file cannot be probed or contains no video stream.
+/// * The analysis process cannot be started or exits unsuccessfully.
+///
+/// # Example
+///
+/// ```no_run
+/// # use handbrake_core::{analyze_source, AnalysisOptions};
+/// # fn main() -> anyhow::Result<()> {
+/// let analysis = analyze_source("ffmpeg", "ffprobe", "/output/title_t00.mkv", &AnalysisOptions::default())?;
+/// println!("crop: {:?}, field mode: {:?}", analysis.crop, analysis.field_mode);
+/// # Ok(())
+/// # }
+/// ```
+pub fn analyze_source(ffmpeg_command: &str, ffprobe_command: &str, file: &str, options: &AnalysisOptions) -> Result<SourceAnalysis> {
+    let source_info = probe_file(ffprobe_command, file).context("failed to probe source file")?;
+    let video = source_info
+        .streams
+        .iter()
+        .find(|stream| stream.codec_type == "video")
+        .context("source file contains no video stream")?;
+
+    let width = video.width.unwrap_or(0);
+    let height = video.height.unwrap_or(0);
+
+    let samples = options.samples.max(1);
+    let sample_duration = options.sample_duration.to_string();
+
+    let mut crop: Option<Crop> = None;
+    let (mut combed, mut progressive, mut repeated, mut fields) = (0, 0, 0, 0);
+
+    for sample in 0..samples {
+        let start = (source_info.duration * (sample + 1) as f32 / (samples + 1) as f32).to_string();
+
+        let process = Command::new(ffmpeg_command)
+            .args(["-hide_banner", "-nostats", "-ss", &start, "-t", &sample_duration, "-i", file])
+            .args(["-map", "0:v:0", "-vf", "cropdetect=limit=24:round=2:reset=0,idet", "-f", "null", "-"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .context("failed to spawn analysis process")?;
+
+        if !process.status.success() {
+            anyhow::bail!("analysis process exited with {}", process.status);
+        }
+
+        let stderr = String::from_utf8_lossy(&process.stderr);
+
+        if let Some(detected) = stderr.lines().rev().find_map(|line| parse_crop(line, width, height)) {
+            crop = Some(match crop {
+                Some(crop) => Crop {
+                    top: crop.top.min(detected.top),
+                    bottom: crop.bottom.min(detected.bottom),
+                    left: crop.left.min(detected.left),
+                    right: crop.right.min(detected.right),
+                },
+                None => detected,
+            });
+        }
+
+        if let Some(line) = stderr.lines().rfind(|line| line.contains("Multi frame detection:")) {
+            combed += parse_count(line, "TFF:") + parse_count(line, "BFF:");
+            progressive += parse_count(line, "Progressive:");
+        }
+
+        if let Some(line) = stderr.lines().rfind(|line| line.contains("Repeated Fields:")) {
+            repeated += parse_count(line, "Top:") + parse_count(line, "Bottom:");
+            fields += parse_count(line, "Neither:") + parse_count(line, "Top:") + parse_count(line, "Bottom:");
+        }
+    }
+
+    let interlaced_ratio = if combed + progressive > 0 { combed as f32 / (combed + progressive) as f32 } else { 0.0 };
+    let repeated_ratio = if fields > 0 { repeated as f32 / fields as f32 } else { 0.0 };
+
+    let field_mode = match (interlaced_ratio, repeated_ratio) {
+        _ if !options.deinterlace || combed + progressive == 0 => None,
+        (interlaced, repeated) if interlaced > TELECINE_RATIO && repeated > TELECINE_RATIO => Some(FieldMode::Telecined),
+        (interlaced, _) if interlaced > INTERLACED_RATIO => Some(FieldMode::Interlaced),
+        _ => Some(FieldMode::Progressive),
+    };
+
+    let crop = crop.filter(|_| options.crop);
+
+    info!(?crop, ?field_mode, interlaced_ratio, repeated_ratio, "analyzed source: {}", file);
+
+    Ok(SourceAnalysis { width, height, crop, field_mode, interlaced_ratio, repeated_ratio })
+}
+
+/// Parses a `crop=w:h:x:y` value reported by `cropdetect` into the cropped pixels per side.
+fn parse_crop(line: &str, width: u32, height: u32) -> Option<Crop> {
+    let values = line
+        .split("crop=")
+        .nth(1)?
+        .split_whitespace()
+        .next()?
+        .split(':')
+        .map(|value| value.parse::<u32>().ok())
+        .collect::<Option<Vec<u32>>>()?;
+
+    let [crop_width, crop_height, x, y] = values[..] else { return None };
+
+    Some(Crop { top: y, bottom: height.saturating_sub(crop_height + y), left: x, right: width.saturating_sub(crop_width + x) })
+}
+
+/// Parses the number following a label of the `idet` summary, e.g. `TFF:` in `Multi frame detection: TFF: 12 BFF: 0`.
+fn parse_count(line: &str, label: &str) -> u64 {
+    line.split(label)
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analysis(crop: Option<Crop>, field_mode: Option<FieldMode>) -> SourceAnalysis {
+        SourceAnalysis { width: 720, height: 480, crop, field_mode, ..Default::default() }
+    }
+
+    #[test]
+    fn undecided_settings_are_not_overridden() {
+        let analysis = analysis(None, None);
+
+        assert!(analysis.handbrake_args().is_empty());
+        assert!(analysis.ffmpeg_filters().is_empty());
+    }
+
+    #[test]
+    fn detected_settings_are_overridden() {
+        let analysis = analysis(Some(Crop { top: 60, bottom: 60, left: 0, right: 0 }), Some(FieldMode::Telecined));
+
+        assert_eq!(analysis.handbrake_args(), ["--crop", "60:60:0:0", "--detelecine", "--no-deinterlace"]);
+        assert_eq!(analysis.ffmpeg_filters(), ["fieldmatch", "decimate", "crop=720:360:0:60"]);
+    }
+
+    #[test]
+    fn crop_is_overridden_without_field_mode() {
+        let analysis = analysis(Some(Crop::default()), None);
+
+        assert_eq!(analysis.handbrake_args(), ["--crop", "0:0:0:0"]);
+        assert!(analysis.ffmpeg_filters().is_empty());
+    }
+}
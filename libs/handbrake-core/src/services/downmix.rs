@@ -0,0 +1,236 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tracing::{error, info};
+
+use crate::probe_file;
+use crate::services::encoding::write_atomically;
+
+const DOWNMIX_CODEC: &str = "aac";
+
+/// The EBU R128 loudness normalization, resampled back to 48 kHz since `loudnorm` upsamples its output.
+const LOUDNORM_FILTER: &str = "aformat=channel_layouts=stereo,loudnorm=I=-23:TP=-1:LRA=20,aresample=48000";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DownmixOptions {
+    pub enabled: bool,
+    pub langs: Vec<String>,
+    pub min_channels: u8,
+    pub bitrate: u32,
+    pub normalize: bool,
+}
+
+impl Default for DownmixOptions {
+    fn default() -> Self {
+        Self { enabled: false, langs: vec![], min_channels: 3, bitrate: 192, normalize: false }
+    }
+}
+
+/// An audio track of a source as reported by the disc (e.g. the audio streams of a MakeMKV title).
+#[derive(Debug, Clone)]
+pub struct AudioTrack {
+    pub lang: String,
+    pub channels: u8,
+    pub codec: String,
+}
+
+/// Selects the audio tracks a stereo downmix should be created for.
+///
+/// For every language (or only the languages of `options.langs`, if any) the track with the most channels
+/// is selected, if it has at least `options.min_channels` channels and the language doesn't have a stereo
+/// or mono track already. If multiple tracks have the same number of channels, lossless codecs are preferred.
+///
+/// # Arguments
+///
+/// * `tracks` - The audio tracks of the source, in the order of the source file.
+/// * `options` - The `DownmixOptions` containing the languages and the minimal number of channels.
+///
+/// # Returns
+///
+/// The positions of the selected tracks among the audio tracks of the source.
+pub fn select_downmix_tracks(tracks: &[AudioTrack], options: &DownmixOptions) -> Vec<usize> {
+    let mut langs: Vec<&str> = vec![];
+    for track in tracks {
+        if !langs.contains(&track.lang.as_str()) {
+            langs.push(&track.lang);
+        }
+    }
+
+    let mut selected = langs
+        .into_iter()
+        .filter(|lang| options.langs.is_empty() || options.langs.iter().any(|l| l == lang))
+        .filter(|lang| !tracks.iter().any(|track| track.lang == *lang && track.channels > 0 && track.channels <= 2))
+        .filter_map(|lang| {
+            tracks
+                .iter()
+                .enumerate()
+                .filter(|(_, track)| track.lang == lang && track.channels >= options.min_channels.max(3))
+                .max_by_key(|(position, track)| (track.channels, codec_rank(&track.codec), std::cmp::Reverse(*position)))
+                .map(|(position, _)| position)
+        })
+        .collect::<Vec<usize>>();
+
+    selected.sort_unstable();
+    selected
+}
+
+/// Adds stereo AAC downmix tracks of surround audio tracks to an output file.
+///
+/// The selected audio tracks of `source` are downmixed to stereo AAC with ffmpeg, optionally normalized to
+/// -23 LUFS according to EBU R128, and muxed into `output` with mkvmerge, next to the original tracks. The
+/// downmix tracks are named `Stereo` and are not flagged as default. Languages the output already contains a
+/// stereo AAC track for (e.g. because it was added in a previous run) are skipped.
+///
+/// # Arguments
+///
+/// * `ffmpeg_command` - A string slice that holds the downmixing command to be executed (path of ffmpeg).
+/// * `ffprobe_command` - A string slice that holds the probing command to be executed (path of ffprobe).
+/// * `mkvmerge_command` - A string slice that holds the muxing command to be executed (path of mkvmerge).
+/// * `source` - A string slice that holds the path of the file containing the original audio tracks (the ripped title).
+/// * `output` - A string slice that holds the path of the output file the downmix tracks are added to.
+/// * `tracks` - The positions of the audio tracks of `source` to downmix, e.g. selected by `select_downmix_tracks`.
+/// * `options` - The `DownmixOptions` containing the bitrate and whether the tracks should be normalized.
+///
+/// # Returns
+///
+/// This function returns a `Result` containing the number of downmix tracks that were added.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The source or output file cannot be probed.
+/// * The temporary working directory cannot be created.
+/// * The downmix tracks cannot be muxed into the output file.
+///
+/// # Example
+///
+/// ```no_run
+/// # use handbrake_core::{add_downmix_tracks, DownmixOptions};
+/// # fn main() -> anyhow::Result<()> {
+/// let options = DownmixOptions { enabled: true, normalize: true, ..Default::default() };
+/// let added = add_downmix_tracks("ffmpeg", "ffprobe", "mkvmerge", "/output/title_t00.mkv", "/output/encoding/title_t00.mkv", &[0], &options)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn add_downmix_tracks(
+    ffmpeg_command: &str, ffprobe_command: &str, mkvmerge_command: &str, source: &str, output: &str, tracks: &[usize], options: &DownmixOptions,
+) -> Result<usize> {
+    let source_info = probe_file(ffprobe_command, source).context("failed to probe source file")?;
+    let output_info = probe_file(ffprobe_command, output).context("failed to probe output file")?;
+
+    let stereo_langs = output_info
+        .streams
+        .iter()
+        .filter(|stream| stream.codec_type == "audio" && stream.codec_name == DOWNMIX_CODEC && stream.channels == Some(2))
+        .map(|stream| stream.language.as_str())
+        .collect::<Vec<&str>>();
+
+    let streams = source_info
+        .streams
+        .iter()
+        .filter(|stream| stream.codec_type == "audio")
+        .enumerate()
+        .filter(|(position, stream)| tracks.contains(position) && !stereo_langs.contains(&stream.language.as_str()))
+        .collect::<Vec<_>>();
+
+    if streams.is_empty() {
+        return Ok(0);
+    }
+
+    let work_dir = PathBuf::from(format!("{}.downmix", output));
+    fs::create_dir_all(&work_dir).context("failed to create downmix working directory")?;
+
+    let mut downmixes = vec![];
+
+    for (position, stream) in streams {
+        info!("downmixing {} audio track {} ({}) of file: {}", stream.codec_name, stream.index, stream.language, source);
+
+        let downmix_file = work_dir.join(format!("{}.mka", position));
+
+        match downmix_track(ffmpeg_command, source, position, &downmix_file, options) {
+            Ok(()) => downmixes.push((stream.language.as_str(), downmix_file)),
+            Err(e) => error!("failed to downmix audio track {} of file {}: {:?}", stream.index, source, e),
+        }
+    }
+
+    let result = mux_downmixes(mkvmerge_command, output, &downmixes).map(|_| downmixes.len());
+
+    fs::remove_dir_all(&work_dir).ok();
+
+    result
+}
+
+/// Downmixes a single audio track of the source to a stereo AAC file.
+fn downmix_track(command: &str, source: &str, position: usize, downmix_file: &Path, options: &DownmixOptions) -> Result<()> {
+    let mut process = Command::new(command);
+    process
+        .args(["-hide_banner", "-nostats", "-y", "-i", source, "-map", &format!("0:a:{}", position), "-vn", "-sn"])
+        .args(["-c:a", DOWNMIX_CODEC, "-b:a", &format!("{}k", options.bitrate)]);
+
+    if options.normalize {
+        process.args(["-af", LOUDNORM_FILTER]);
+    } else {
+        process.args(["-ac", "2"]);
+    }
+
+    let status = process
+        .arg(downmix_file)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .context("failed to spawn downmixing process")?;
+
+    if !status.success() {
+        anyhow::bail!("downmixing process exited with {}", status);
+    }
+
+    Ok(())
+}
+
+/// Muxes the downmix files as additional audio tracks into the output file.
+fn mux_downmixes(command: &str, output: &str, downmixes: &[(&str, PathBuf)]) -> Result<()> {
+    if downmixes.is_empty() {
+        return Ok(());
+    }
+
+    write_atomically(Path::new(output), |partial_file| {
+        let mut process = Command::new(command);
+        process.args(["--output", partial_file, output]);
+
+        for (lang, downmix_file) in downmixes {
+            process
+                .args(["--language", &format!("0:{}", lang)])
+                .args(["--default-track-flag", "0:no"])
+                .args(["--track-name", "0:Stereo"])
+                .arg(downmix_file);
+        }
+
+        let status = process
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .context("failed to spawn muxing process")?;
+
+        // mkvmerge exits with 1 if the file was written successfully but warnings occurred
+        if !matches!(status.code(), Some(0) | Some(1)) {
+            anyhow::bail!("muxing process exited with {}", status);
+        }
+
+        Ok(())
+    })
+}
+
+/// Ranks audio codecs by quality, so lossless tracks are preferred as downmix source.
+fn codec_rank(codec: &str) -> u8 {
+    let codec = codec.to_lowercase();
+
+    match codec.as_str() {
+        c if c.contains("truehd") || c.contains("dts-hd ma") || c.contains("lpcm") || c.contains("flac") => 3,
+        c if c.contains("dts") => 2,
+        c if c.contains("e-ac3") || c.contains("eac3") => 1,
+        _ => 0,
+    }
+}
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use std::fmt::Debug;
+use std::sync::atomic::AtomicBool;
+
+use crate::{encode_file, probe_file, EncodingJob, MediaInfo, ProcessPriority};
+
+/// The progress of a running encode, as reported by an `Encoder`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EncoderProgress {
+    /// The encoded fraction of the input, between `0` and `1`.
+    pub progress: f32,
+    /// The estimated remaining encoding time in seconds.
+    pub eta: f32,
+}
+
+/// A backend that is able to encode and probe media files.
+///
+/// Encoders only read the input, profile and source analysis of a job. They report the progress of an
+/// encode through the `progress` callback and must stop the encoding process as soon as `cancel` is set,
+/// so how progress reaches the UI and how jobs are cancelled is up to the caller (e.g. the `EncodingQueue`).
+/// Encoders are shared between the workers of the `EncodingQueue`, so they have to be `Send` and `Sync`.
+pub trait Encoder: Debug + Send + Sync {
+    /// Encodes the input file of the job into the `encoding` directory inside the job's output directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The `EncodingJob` containing the input file, profile, output directory and source analysis.
+    /// * `progress` - Called with the progress of the encode whenever the encoding process reports it.
+    /// * `cancel` - A flag that aborts the encode once it is set.
+    ///
+    /// # Returns
+    ///
+    /// The path of the encoded file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the encoding process fails or the encode is cancelled.
+    fn encode(&self, job: &EncodingJob, progress: &dyn Fn(EncoderProgress), cancel: &AtomicBool) -> Result<String>;
+
+    /// Reads the container and stream information of a media file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be probed.
+    fn probe(&self, file: &str) -> Result<MediaInfo>;
+}
+
+/// An `Encoder` using HandBrakeCLI with the HandBrake preset of the job's profile.
+#[derive(Debug, Clone)]
+pub struct HandBrakeEncoder {
+    pub command: String,
+    pub ffprobe_command: String,
+    pub priority: ProcessPriority,
+}
+
+impl Encoder for HandBrakeEncoder {
+    fn encode(&self, job: &EncodingJob, progress: &dyn Fn(EncoderProgress), cancel: &AtomicBool) -> Result<String> {
+        encode_file(&self.command, &self.priority, job, progress, cancel)
+    }
+
+    fn probe(&self, file: &str) -> Result<MediaInfo> {
+        probe_file(&self.ffprobe_command, file)
+    }
+}
@@ -1,13 +1,16 @@
 use anyhow::{Context, Result};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{mpsc::Sender, Arc};
 use tracing::info;
 
-use crate::Profile;
+use crate::{EncoderProgress, EncodingJob, Profile, SourceProperties};
+
+/// The marker of output files that are still being written.
+const PARTIAL_MARKER: &str = "partial";
 
 #[derive(Debug, Serialize)]
 pub struct EncodingProgressPayload {
@@ -16,6 +19,12 @@ pub struct EncodingProgressPayload {
     pub step: usize,
 }
 
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct ProcessPriority {
+    pub niceness: Option<i32>,
+    pub cpu_affinity: Option<String>,
+}
+
 /// Encodes a list of files using the specified command and profile, and sends progress updates through a channel.
 ///
 /// This function takes a command, an encoding profile, a list of input files, an output directory,
@@ -48,16 +57,13 @@ pub struct EncodingProgressPayload {
 ///
 /// # Example
 ///
-/// ```
-/// use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+/// ```no_run
+/// use std::sync::{Arc, atomic::AtomicBool};
 /// use std::sync::mpsc::channel;
-/// use your_crate::{encode_files, Profile};
+/// use handbrake_core::{encode_files, get_encoding_profiles};
 ///
-/// let command = "ffmpeg";
-/// let profile = Profile {
-///     file_name: "profile.json".to_string(),
-///     preset_name: "preset".to_string(),
-/// };
+/// let command = "HandBrakeCLI";
+/// let profile = get_encoding_profiles("/path/to/profiles").expect("Failed to get encoding profiles").remove(0);
 /// let files = vec!["input1.mp4", "input2.mp4"];
 /// let output_dir = "/path/to/output";
 /// let cancel_flag = Arc::new(AtomicBool::new(false));
@@ -73,26 +79,76 @@ pub struct EncodingProgressPayload {
 /// ```
 ///
 /// Note: In a real-world application, proper error handling should be added to handle any potential issues gracefully.
-
 pub fn encode_files(
-    command: &str, profile: &Profile, files: &[&str], output_dir: &str, cancel_flag: Arc<AtomicBool>, sender: Sender<(&str, Option<EncodingProgressPayload>)>,
+    command: &str, profile: &Profile, files: &[&str], output_dir: &str, cancel_flag: Arc<AtomicBool>, sender: Sender<(&'static str, Option<EncodingProgressPayload>)>,
 ) -> Result<()> {
     for (i, &file) in files.iter().enumerate() {
-        let file_name = Path::new(file).file_name().context("failed to get file name")?.to_str().unwrap();
+        let job = EncodingJob {
+            profile: profile.clone(),
+            input: file.to_string(),
+            output_dir: output_dir.to_string(),
+            step: i,
+            source: SourceProperties::default(),
+            analysis: None,
+            cancel_flag: cancel_flag.clone(),
+            sender: sender.clone(),
+        };
 
-        let encoding_output_dir = Path::new(output_dir).join("encoding/");
+        let progress = |progress: EncoderProgress| {
+            sender
+                .send(("progress", Some(EncodingProgressPayload { progress: progress.progress, eta: progress.eta, step: i })))
+                .ok();
+        };
 
-        if !encoding_output_dir.exists() {
-            std::fs::create_dir_all(&encoding_output_dir).context("failed to create encoding output directory")?;
-        }
+        encode_file(command, &ProcessPriority::default(), &job, &progress, &cancel_flag)?;
+    }
+
+    sender.send(("done", None)).unwrap();
+
+    Ok(())
+}
+
+/// Encodes a single file described by an `EncodingJob` with HandBrakeCLI and reports its progress.
+///
+/// The encoded file is written to the `encoding` directory inside the job's output directory, keeping
+/// the file name of the input. While encoding, the output is written to a partial file which is renamed
+/// once the encode succeeded and removed if it failed or was cancelled. The HandBrake process is started
+/// with the given process priority, so the niceness and CPU affinity of the encoder can be limited on
+/// machines that also serve other tasks.
+///
+/// # Arguments
+///
+/// * `command` - A string slice that holds the encoding command to be executed (path of HandBrakeCLI).
+/// * `priority` - The niceness and CPU affinity the encoding process should be started with.
+/// * `job` - The `EncodingJob` containing the input file, profile, output directory and source analysis.
+/// * `progress` - Called with the progress and ETA whenever HandBrake reports them.
+/// * `cancel_flag` - A flag that kills the encoding process once it is set.
+///
+/// # Returns
+///
+/// This function returns a `Result` containing the path of the encoded file if successful.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The file name of the input file cannot be determined.
+/// * The output directory cannot be created.
+/// * The encoding process cannot be started or exits unsuccessfully.
+/// * The output of the encoding process cannot be read or parsed.
+/// * The encoded file cannot be moved into place.
+/// * The cancel flag is set while encoding.
+pub fn encode_file(command: &str, priority: &ProcessPriority, job: &EncodingJob, progress: &dyn Fn(EncoderProgress), cancel_flag: &AtomicBool) -> Result<String> {
+    let file = job.input.as_str();
+    let output_path = job.output_path()?;
 
-        let output_path = encoding_output_dir.join(file_name);
-        let output_file = output_path.to_str().unwrap();
+    std::fs::create_dir_all(output_path.parent().context("failed to get output directory")?).context("failed to create encoding output directory")?;
 
-        info!("encoding file:{} with profile: {} into {}", file, profile.file_name, output_file);
+    info!("encoding file:{} with profile: {} into {}", file, job.profile.file_name, output_path.display());
 
-        let mut process = Command::new(command)
-            .args(&["--json", "--input", file, "--output", output_file, "--preset-import-file", &profile.file_name, "-Z", &profile.preset_name])
+    write_atomically(&output_path, |partial_file| {
+        let mut process = build_command(command, priority)
+            .args(["--json", "--input", file, "--output", partial_file, "--preset-import-file", &job.profile.file_name, "-Z", &job.profile.preset_name])
+            .args(job.analysis.as_ref().map(|analysis| analysis.handbrake_args()).unwrap_or_default())
             .stdout(Stdio::piped())
             .stderr(Stdio::null())
             .spawn()
@@ -106,6 +162,7 @@ pub fn encode_files(
         for line in stdout.lines() {
             if cancel_flag.load(Ordering::Relaxed) {
                 process.kill()?;
+                process.wait().ok();
                 anyhow::bail!("operation aborted");
             }
 
@@ -134,13 +191,149 @@ pub fn encode_files(
                 _ => {}
             }
 
-            sender
-                .send(("progress", Some(EncodingProgressPayload { progress: current_progress, eta: current_eta, step: i })))
-                .unwrap();
+            progress(EncoderProgress { progress: current_progress, eta: current_eta });
         }
+
+        let status = process.wait().context("failed to wait for encoding process")?;
+
+        if !status.success() {
+            anyhow::bail!("encoding process exited with {}", status);
+        }
+
+        Ok(())
+    })?;
+
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+/// Returns the temporary path an output file is written to until it is complete.
+///
+/// The marker is inserted before the extension (e.g. `title_t00.partial.mkv`), so encoders that
+/// detect the container format from the file extension keep working.
+pub fn partial_output_path(output_path: &Path) -> PathBuf {
+    let stem = output_path.file_stem().unwrap_or_default().to_string_lossy();
+
+    match output_path.extension() {
+        Some(extension) => output_path.with_file_name(format!("{}.{}.{}", stem, PARTIAL_MARKER, extension.to_string_lossy())),
+        None => output_path.with_file_name(format!("{}.{}", stem, PARTIAL_MARKER)),
     }
+}
 
-    sender.send(("done", None)).unwrap();
+/// Removes partial output files left behind by interrupted encodes or remuxes.
+///
+/// Partial files are searched in the `encoding` directory of the job and in the `encoding` directories
+/// of its versions (`versions/<version>/encoding`), which jobs with multiple versions write to.
+///
+/// # Arguments
+///
+/// * `output_dir` - A string slice specifying the output directory of the job.
+///
+/// # Returns
+///
+/// This function returns a `Result` containing the number of removed files.
+///
+/// # Errors
+///
+/// This function will return an error if an `encoding` or the `versions` directory exists but cannot be read.
+pub fn clean_partial_outputs(output_dir: &str) -> Result<usize> {
+    let mut encoding_output_dirs = vec![Path::new(output_dir).join("encoding/")];
+    let versions_dir = Path::new(output_dir).join("versions/");
 
-    Ok(())
+    if versions_dir.exists() {
+        for entry in std::fs::read_dir(&versions_dir).context("failed to read versions directory")? {
+            encoding_output_dirs.push(entry?.path().join("encoding/"));
+        }
+    }
+
+    let mut removed = 0;
+
+    for encoding_output_dir in encoding_output_dirs.iter().filter(|dir| dir.is_dir()) {
+        for entry in std::fs::read_dir(encoding_output_dir).context("failed to read encoding output directory")? {
+            let path = entry?.path();
+            let is_partial = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().contains(&format!(".{}", PARTIAL_MARKER)));
+
+            if is_partial && std::fs::remove_file(&path).is_ok() {
+                info!("removed partial output file: {}", path.display());
+                removed += 1;
+            }
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Runs `write` with the partial path of `output_path` and moves the written file into place once it succeeded.
+///
+/// The rename is atomic as both paths are in the same directory, so a file with the final name is always
+/// complete. If `write` fails, the partial file is removed.
+pub(crate) fn write_atomically<F>(output_path: &Path, write: F) -> Result<()>
+where
+    F: FnOnce(&str) -> Result<()>,
+{
+    let partial_path = partial_output_path(output_path);
+
+    if let Err(e) = write(&partial_path.to_string_lossy()) {
+        std::fs::remove_file(&partial_path).ok();
+        return Err(e);
+    }
+
+    std::fs::rename(&partial_path, output_path).context("failed to move output file into place")
+}
+
+/// Builds the command used to spawn an encoding process with the given process priority.
+///
+/// If a niceness is configured, the command is wrapped with `nice -n <niceness>`. If a CPU
+/// affinity is configured, the command is additionally wrapped with `taskset -c <cpu_list>`,
+/// where the CPU list uses the format of `taskset` (e.g. `0-3,6`).
+pub(crate) fn build_command(command: &str, priority: &ProcessPriority) -> Command {
+    let mut wrapper: Vec<String> = vec![];
+
+    if let Some(niceness) = priority.niceness {
+        wrapper.extend(["nice".to_string(), "-n".to_string(), niceness.to_string()]);
+    }
+
+    if let Some(cpu_affinity) = &priority.cpu_affinity {
+        wrapper.extend(["taskset".to_string(), "-c".to_string(), cpu_affinity.to_string()]);
+    }
+
+    match wrapper.split_first() {
+        Some((program, args)) => {
+            let mut process = Command::new(program);
+            process.args(args).arg(command);
+            process
+        }
+        None => Command::new(command),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cleans_partial_outputs_of_job_and_versions() {
+        let job_dir = std::env::temp_dir().join(format!("ripper_partial_outputs_{}", std::process::id()));
+        let files = [
+            "encoding/title_t00.partial.mkv",
+            "encoding/title_t01.mkv",
+            "versions/1080p/encoding/title_t00.partial.mkv",
+            "versions/2160p/encoding/title_t00.partial.mkv",
+            "versions/2160p/encoding/title_t01.mkv",
+        ];
+
+        for file in files {
+            let path = job_dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+
+        assert_eq!(clean_partial_outputs(&job_dir.to_string_lossy()).unwrap(), 3);
+        assert!(job_dir.join("encoding/title_t01.mkv").exists());
+        assert!(job_dir.join("versions/2160p/encoding/title_t01.mkv").exists());
+        assert!(!job_dir.join("versions/1080p/encoding/title_t00.partial.mkv").exists());
+
+        std::fs::remove_dir_all(job_dir).ok();
+    }
 }
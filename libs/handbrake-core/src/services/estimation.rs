@@ -0,0 +1,227 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::SourceAnalysis;
+
+/// The number of most recent encodes of a profile that are used to calibrate an estimate.
+const CALIBRATION_WINDOW: usize = 20;
+
+/// Output size in relation to the source size, used as long as no encodes have been recorded.
+const DEFAULT_SIZE_RATIO: f32 = 0.35;
+
+/// Encoding seconds per second of source material and megapixel, used as long as no encodes have been recorded.
+const DEFAULT_SPEED: f32 = 0.5;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SourceProperties {
+    pub duration: f32,
+    pub width: u32,
+    pub height: u32,
+    pub bit_rate: u64,
+}
+
+impl SourceProperties {
+    /// Creates the source properties from a MakeMKV style video size (e.g. `1920x1080`) and the size of the title.
+    pub fn from_title(duration: u32, video_size: &str, disk_size_bytes: i64) -> Self {
+        let (width, height) = video_size.split_once('x').unwrap_or_default();
+        let bit_rate = if duration > 0 { (disk_size_bytes.max(0) as u64 * 8) / duration as u64 } else { 0 };
+
+        Self { duration: duration as f32, width: width.trim().parse().unwrap_or(0), height: height.trim().parse().unwrap_or(0), bit_rate }
+    }
+
+    fn megapixel_seconds(&self) -> f32 {
+        self.duration * (self.width * self.height) as f32 / 1_000_000.0
+    }
+
+    fn size(&self) -> f32 {
+        self.bit_rate as f32 / 8.0 * self.duration
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncodingRecord {
+    pub profile_id: String,
+    pub source: SourceProperties,
+    #[serde(default)]
+    pub analysis: Option<SourceAnalysis>,
+    pub output_size: u64,
+    pub encode_time: f32,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct EncodingEstimate {
+    pub output_size: u64,
+    pub encode_time: f32,
+    pub calibration_samples: usize,
+}
+
+/// The history of completed encodes on this machine, stored as a JSON file.
+///
+/// The history is used to calibrate size and time estimates for new encodes. It can be cloned and
+/// shared between threads, writes to the underlying file are synchronized.
+#[derive(Debug, Clone)]
+pub struct EncodingHistory {
+    path: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+impl EncodingHistory {
+    /// Creates a new `EncodingHistory` stored at the given path. The file is created with the first record.
+    pub fn new(path: &str) -> Self {
+        Self { path: PathBuf::from(path), lock: Arc::new(Mutex::new(())) }
+    }
+
+    /// Reads all records of the history. A missing history file results in an empty history.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the history file exists but cannot be read or parsed.
+    pub fn records(&self) -> Result<Vec<EncodingRecord>> {
+        let _lock = self.lock.lock().map_err(|e| anyhow!("failed to lock encoding history: {}", e))?;
+        self.read_records()
+    }
+
+    /// Appends a completed encode to the history.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the history file cannot be read or written.
+    pub fn record(&self, record: EncodingRecord) -> Result<()> {
+        let _lock = self.lock.lock().map_err(|e| anyhow!("failed to lock encoding history: {}", e))?;
+
+        let mut records = self.read_records()?;
+        records.push(record);
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context("failed to create encoding history directory")?;
+        }
+
+        fs::write(&self.path, serde_json::to_string(&records)?).context("failed to write encoding history")
+    }
+
+    /// Estimates the output size and encoding time of a source encoded with the given profile.
+    ///
+    /// The estimate scales the encoding seconds per megapixel and second of the most recent encodes with
+    /// the same profile to the given source. The output size is scaled by the size of the source, i.e. its
+    /// duration and bitrate, so a more complex (higher bitrate) source is expected to result in a larger
+    /// output. If the bitrates are unknown, the output bytes per megapixel and second are scaled instead.
+    /// Without any recorded encodes of the profile, conservative defaults based on the size of the source
+    /// are used.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile_id` - The id of the encoding profile that will be used.
+    /// * `source` - The duration, resolution and bitrate of the source.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the history file cannot be read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use handbrake_core::{EncodingHistory, SourceProperties};
+    /// # fn main() -> anyhow::Result<()> {
+    /// let history = EncodingHistory::new("/output/encoding_history.json");
+    /// let source = SourceProperties::from_title(7200, "1920x1080", 30_000_000_000);
+    /// let estimate = history.estimate("h264_1080p_22crf_live_action_medium", &source)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn estimate(&self, profile_id: &str, source: &SourceProperties) -> Result<EncodingEstimate> {
+        let records = self.records()?;
+
+        let calibration: Vec<&EncodingRecord> = records
+            .iter()
+            .rev()
+            .filter(|record| record.profile_id == profile_id && record.source.megapixel_seconds() > 0.0)
+            .take(CALIBRATION_WINDOW)
+            .collect();
+
+        if calibration.is_empty() || source.megapixel_seconds() <= 0.0 {
+            return Ok(EncodingEstimate {
+                output_size: (source.size() * DEFAULT_SIZE_RATIO) as u64,
+                encode_time: source.megapixel_seconds() * DEFAULT_SPEED,
+                calibration_samples: 0,
+            });
+        }
+
+        let size_ratios: Vec<f32> = calibration
+            .iter()
+            .filter(|record| record.source.size() > 0.0)
+            .map(|record| record.output_size as f32 / record.source.size())
+            .collect();
+
+        let output_size = if source.size() > 0.0 && !size_ratios.is_empty() {
+            mean(&size_ratios) * source.size()
+        } else {
+            let size_rates: Vec<f32> = calibration
+                .iter()
+                .map(|record| record.output_size as f32 / record.source.megapixel_seconds())
+                .collect();
+            mean(&size_rates) * source.megapixel_seconds()
+        };
+
+        let speeds: Vec<f32> = calibration.iter().map(|record| record.encode_time / record.source.megapixel_seconds()).collect();
+
+        Ok(EncodingEstimate { output_size: output_size as u64, encode_time: mean(&speeds) * source.megapixel_seconds(), calibration_samples: calibration.len() })
+    }
+
+    fn read_records(&self) -> Result<Vec<EncodingRecord>> {
+        if !Path::new(&self.path).exists() {
+            return Ok(vec![]);
+        }
+
+        let contents = fs::read_to_string(&self.path).context("failed to read encoding history")?;
+        serde_json::from_str(&contents).context("failed to parse encoding history")
+    }
+}
+
+fn mean(values: &[f32]) -> f32 {
+    values.iter().sum::<f32>() / values.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(source: SourceProperties, output_size: u64) -> EncodingRecord {
+        EncodingRecord { profile_id: "profile".to_string(), source, analysis: None, output_size, encode_time: 3600.0 }
+    }
+
+    fn history(name: &str, records: &[EncodingRecord]) -> EncodingHistory {
+        let path = std::env::temp_dir().join(format!("ripper_estimation_{}_{}.json", name, std::process::id()));
+        fs::write(&path, serde_json::to_string(records).unwrap()).unwrap();
+        EncodingHistory::new(path.to_str().unwrap())
+    }
+
+    #[test]
+    fn scales_output_size_with_source_bitrate() {
+        let history = history("bitrate", &[record(SourceProperties::from_title(3600, "1920x1080", 20_000_000_000), 5_000_000_000)]);
+
+        let simple = history
+            .estimate("profile", &SourceProperties::from_title(3600, "1920x1080", 10_000_000_000))
+            .unwrap();
+        let complex = history
+            .estimate("profile", &SourceProperties::from_title(3600, "1920x1080", 30_000_000_000))
+            .unwrap();
+
+        assert_eq!(simple.calibration_samples, 1);
+        assert!((simple.output_size as f32 - 2_500_000_000.0).abs() < 1_000_000.0);
+        assert!((complex.output_size as f32 - 7_500_000_000.0).abs() < 1_000_000.0);
+        assert_eq!(simple.encode_time, complex.encode_time);
+    }
+
+    #[test]
+    fn scales_output_size_with_resolution_without_bitrates() {
+        let source = SourceProperties { duration: 3600.0, width: 1920, height: 1080, bit_rate: 0 };
+        let history = history("resolution", &[record(source.clone(), 4_000_000_000)]);
+
+        let estimate = history.estimate("profile", &SourceProperties { duration: 1800.0, ..source }).unwrap();
+
+        assert!((estimate.output_size as f32 - 2_000_000_000.0).abs() < 1_000_000.0);
+    }
+}
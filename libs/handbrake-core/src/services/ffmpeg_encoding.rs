@@ -0,0 +1,180 @@
+use anyhow::{Context, Result};
+use std::io::{BufRead, BufReader};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::info;
+
+use crate::services::encoding::{build_command, write_atomically};
+use crate::{probe_file, Encoder, EncoderProgress, EncodingJob, MediaInfo, ProcessPriority};
+
+/// The output arguments used for profiles without `ffmpeg_args`.
+const DEFAULT_FFMPEG_ARGS: [&str; 10] = ["-c:v", "libx264", "-crf", "22", "-preset", "medium", "-c:a", "copy", "-c:s", "copy"];
+
+/// An `Encoder` using ffmpeg with the `ffmpeg_args` of the job's profile.
+///
+/// All streams of the input are mapped into the output, the profile arguments are inserted between
+/// the input and the output file, so they can contain codecs, filter graphs and stream selections.
+/// The filters of the job's source analysis are prepended to the video filters of the profile.
+/// The progress is read from the key value pairs ffmpeg writes with `-progress pipe:1`.
+#[derive(Debug, Clone)]
+pub struct FfmpegEncoder {
+    pub command: String,
+    pub ffprobe_command: String,
+    pub priority: ProcessPriority,
+}
+
+impl Encoder for FfmpegEncoder {
+    /// Encodes a single file with ffmpeg and reports its progress.
+    ///
+    /// The output is written to a partial file which is renamed once the encode succeeded. The progress
+    /// is calculated from the `out_time_us` reported by ffmpeg and the duration of the source. If the
+    /// duration of the source is unknown, the input file is probed first. The ETA is derived from the
+    /// remaining duration and the encoding `speed` reported by ffmpeg.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// * The file name of the input file cannot be determined.
+    /// * The output directory cannot be created.
+    /// * The encoding process cannot be started or exits unsuccessfully.
+    /// * The encoded file cannot be moved into place.
+    /// * The cancel flag is set while encoding.
+    fn encode(&self, job: &EncodingJob, report_progress: &dyn Fn(EncoderProgress), cancel: &AtomicBool) -> Result<String> {
+        let file = job.input.as_str();
+        let output_path = job.output_path()?;
+
+        std::fs::create_dir_all(output_path.parent().context("failed to get output directory")?).context("failed to create encoding output directory")?;
+
+        let duration = match job.source.duration {
+            duration if duration > 0.0 => duration,
+            _ => self.probe(file).map(|info| info.duration).unwrap_or(0.0),
+        };
+
+        let mut args: Vec<String> =
+            if job.profile.ffmpeg_args.is_empty() { DEFAULT_FFMPEG_ARGS.iter().map(|arg| arg.to_string()).collect() } else { job.profile.ffmpeg_args.clone() };
+
+        // the filters of the source analysis run before the video filters of the profile
+        let filters = job.analysis.as_ref().map(|analysis| analysis.ffmpeg_filters().join(",")).unwrap_or_default();
+
+        if !filters.is_empty() {
+            match args.iter().position(|arg| arg == "-vf" || arg == "-filter:v") {
+                Some(index) if index + 1 < args.len() => args[index + 1] = format!("{},{}", filters, args[index + 1]),
+                _ => args.extend(["-vf".to_string(), filters]),
+            }
+        }
+
+        info!("encoding file:{} with ffmpeg profile: {} into {}", file, job.profile.id, output_path.display());
+
+        write_atomically(&output_path, |partial_file| {
+            let mut process = build_command(&self.command, &self.priority)
+                .args(["-hide_banner", "-nostats", "-y", "-i", file, "-map", "0"])
+                .args(&args)
+                .args(["-progress", "pipe:1", partial_file])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .context("failed to spawn encoding process")?;
+
+            let stdout = BufReader::new(process.stdout.take().context("failed to capture stdout")?);
+
+            let mut block = vec![];
+
+            for line in stdout.lines() {
+                if cancel.load(Ordering::Relaxed) {
+                    process.kill()?;
+                    process.wait().ok();
+                    anyhow::bail!("operation aborted");
+                }
+
+                let line = line?;
+                let is_last = line.trim().starts_with("progress=");
+
+                block.push(line);
+
+                // every block of key value pairs is terminated by its progress line
+                if is_last {
+                    report_progress(parse_progress(block.iter().map(String::as_str), duration));
+                    block.clear();
+                }
+            }
+
+            let status = process.wait().context("failed to wait for encoding process")?;
+
+            if !status.success() {
+                anyhow::bail!("encoding process exited with {}", status);
+            }
+
+            Ok(())
+        })?;
+
+        Ok(output_path.to_string_lossy().to_string())
+    }
+
+    fn probe(&self, file: &str) -> Result<MediaInfo> {
+        probe_file(&self.ffprobe_command, file)
+    }
+}
+
+/// Turns a block of `key=value` lines written by ffmpeg with `-progress` into an `EncoderProgress`.
+///
+/// The progress is calculated from `out_time_us` and the duration of the source, the ETA from the remaining
+/// duration and the encoding `speed`. Values ffmpeg cannot determine yet (`N/A`) and an unknown duration of `0`
+/// count as no progress. A block ending with `progress=end` reports the encode as complete.
+///
+/// # Arguments
+///
+/// * `lines` - The lines of one progress block, up to and including its `progress` line.
+/// * `duration` - The duration of the source in seconds.
+fn parse_progress<'a>(lines: impl IntoIterator<Item = &'a str>, duration: f32) -> EncoderProgress {
+    let mut current_time = 0.0;
+    let mut current_speed = 0.0;
+
+    for line in lines {
+        let Some((key, value)) = line.trim().split_once('=') else { continue };
+
+        match key {
+            "out_time_us" => current_time = value.trim().parse::<f32>().unwrap_or(0.0).max(0.0) / 1_000_000.0,
+            "speed" => current_speed = value.trim().trim_end_matches('x').parse().unwrap_or(0.0),
+            "progress" if value.trim() == "end" => return EncoderProgress { progress: 1.0, eta: 0.0 },
+            _ => {}
+        }
+    }
+
+    let progress = if duration > 0.0 { (current_time / duration).clamp(0.0, 1.0) } else { 0.0 };
+    let eta = if current_speed > 0.0 { (duration - current_time).max(0.0) / current_speed } else { 0.0 };
+
+    EncoderProgress { progress, eta }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_progress() {
+        let block = ["frame=1200", "fps=48.00", "out_time_us=30000000", "out_time=00:00:30.000000", "speed=2.00x", "progress=continue"];
+
+        assert_eq!(parse_progress(block, 120.0), EncoderProgress { progress: 0.25, eta: 45.0 });
+    }
+
+    #[test]
+    fn test_parse_progress_not_available() {
+        let block = ["frame=0", "out_time_us=N/A", "out_time=N/A", "speed=N/A", "progress=continue"];
+
+        assert_eq!(parse_progress(block, 120.0), EncoderProgress { progress: 0.0, eta: 0.0 });
+    }
+
+    #[test]
+    fn test_parse_progress_unknown_duration() {
+        let block = ["out_time_us=30000000", "speed=2.00x", "progress=continue"];
+
+        assert_eq!(parse_progress(block, 0.0), EncoderProgress { progress: 0.0, eta: 0.0 });
+    }
+
+    #[test]
+    fn test_parse_progress_end() {
+        let block = ["out_time_us=119500000", "speed=2.00x", "progress=end"];
+
+        assert_eq!(parse_progress(block, 120.0), EncoderProgress { progress: 1.0, eta: 0.0 });
+    }
+}
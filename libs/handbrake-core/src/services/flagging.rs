@@ -0,0 +1,219 @@
+use anyhow::{Context, Result};
+use std::process::{Command, Stdio};
+use tracing::info;
+
+use crate::services::probing::terminologic_lang;
+use crate::{probe_file, MediaInfo, MediaStream};
+
+/// A subtitle track with less frames than this share of the largest track of the same language is considered forced.
+const FORCED_FRAME_RATIO: f32 = 0.25;
+
+/// Detects the subtitle tracks of a file that only contain forced subtitles.
+///
+/// A subtitle track is considered forced if:
+/// * Its position is contained in `hints` (e.g. because MakeMKV flagged the stream as forced).
+/// * It already has the forced flag set.
+/// * Its title contains "forced".
+/// * It has less than a quarter of the frames of the largest subtitle track of the same language.
+///
+/// # Arguments
+///
+/// * `info` - The `MediaInfo` of the file.
+/// * `hints` - The positions of subtitle tracks (counted among the subtitle tracks only) that are known to be forced.
+///
+/// # Returns
+///
+/// The positions of the forced tracks among the subtitle tracks of the file.
+pub fn detect_forced_subtitles(info: &MediaInfo, hints: &[usize]) -> Vec<usize> {
+    let subtitles = info.streams.iter().filter(|stream| stream.codec_type == "subtitle").collect::<Vec<&MediaStream>>();
+
+    subtitles
+        .iter()
+        .enumerate()
+        .filter(|(position, stream)| {
+            let largest = subtitles
+                .iter()
+                .filter(|other| other.language == stream.language)
+                .filter_map(|other| other.frames)
+                .max()
+                .unwrap_or(0);
+
+            let is_small = stream
+                .frames
+                .is_some_and(|frames| largest > 0 && (frames as f32) < largest as f32 * FORCED_FRAME_RATIO);
+
+            hints.contains(position) || stream.forced || stream.title.to_lowercase().contains("forced") || is_small
+        })
+        .map(|(position, _)| position)
+        .collect()
+}
+
+/// Flags the forced subtitle tracks of an output file, so players show them automatically.
+///
+/// The forced tracks are detected in `source` with `detect_forced_subtitles`. Encoders and remuxers keep the order
+/// of the subtitle tracks, so the subtitle tracks of `output` are matched to the source tracks in order by language
+/// and codec. Matched forced tracks get the forced flag, the first of them in the primary audio language of the output
+/// (its first audio track) also gets the default flag, all other matched tracks lose the default flag.
+/// Tracks of the output that cannot be matched (e.g. subtitles added by OCR) are left untouched.
+///
+/// # Arguments
+///
+/// * `command` - A string slice that holds the tagging command to be executed (path of mkvpropedit).
+/// * `ffprobe_command` - A string slice that holds the probing command to be executed (path of ffprobe).
+/// * `source` - A string slice that holds the path of the source file (the ripped title).
+/// * `output` - A string slice that holds the path of the output file whose tracks should be flagged.
+/// * `hints` - The positions of source subtitle tracks that are known to be forced.
+///
+/// # Returns
+///
+/// This function returns a `Result` containing the number of tracks that were flagged as forced.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The source or output file cannot be probed.
+/// * The tagging process cannot be started or exits with an error.
+///
+/// # Example
+///
+/// ```no_run
+/// # use handbrake_core::flag_forced_subtitles;
+/// # fn main() -> anyhow::Result<()> {
+/// let flagged = flag_forced_subtitles("mkvpropedit", "ffprobe", "/output/title_t00.mkv", "/output/encoding/title_t00.mkv", &[2])?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn flag_forced_subtitles(command: &str, ffprobe_command: &str, source: &str, output: &str, hints: &[usize]) -> Result<usize> {
+    let source_info = probe_file(ffprobe_command, source).context("failed to probe source file")?;
+    let output_info = probe_file(ffprobe_command, output).context("failed to probe output file")?;
+
+    let forced = detect_forced_subtitles(&source_info, hints);
+    let flags = subtitle_flags(&source_info, &output_info, &forced);
+
+    let mut args: Vec<String> = vec![];
+
+    for flag in &flags {
+        args.extend(["--edit".to_string(), format!("track:s{}", flag.position + 1)]);
+        args.extend(["--set".to_string(), format!("flag-forced={}", flag.forced as u8)]);
+        args.extend(["--set".to_string(), format!("flag-default={}", flag.default as u8)]);
+    }
+
+    let flagged = flags.iter().filter(|flag| flag.forced).count();
+
+    if args.is_empty() {
+        return Ok(0);
+    }
+
+    info!("flagging {} forced subtitle tracks of file: {}", flagged, output);
+
+    let status = Command::new(command)
+        .arg(output)
+        .args(&args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .context("failed to spawn tagging process")?;
+
+    // mkvpropedit exits with 1 if the file was modified successfully but warnings occurred
+    if !matches!(status.code(), Some(0) | Some(1)) {
+        anyhow::bail!("tagging process exited with {}", status);
+    }
+
+    Ok(flagged)
+}
+
+/// The flags of a subtitle track of the output, identified by its position among the subtitle tracks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SubtitleFlags {
+    position: usize,
+    forced: bool,
+    default: bool,
+}
+
+/// Matches the subtitle tracks of the output to the source tracks in order and decides their forced and default flags.
+///
+/// Only one track can be the default, so the default flag is reserved for the first forced track in the language of the
+/// first audio track of the output. Forced tracks of other languages would otherwise be shown with the wrong audio.
+fn subtitle_flags(source_info: &MediaInfo, output_info: &MediaInfo, forced: &[usize]) -> Vec<SubtitleFlags> {
+    let subtitles = |info: &MediaInfo| {
+        info.streams
+            .iter()
+            .filter(|stream| stream.codec_type == "subtitle")
+            .cloned()
+            .collect::<Vec<MediaStream>>()
+    };
+    let source_subtitles = subtitles(source_info);
+    let output_subtitles = subtitles(output_info);
+
+    let primary_lang = output_info.languages("audio").first().map(|lang| terminologic_lang(lang).to_string());
+
+    let mut flags = vec![];
+    let mut has_default = false;
+    let mut next_source = 0;
+
+    for (position, stream) in output_subtitles.iter().enumerate() {
+        let matched = source_subtitles
+            .iter()
+            .enumerate()
+            .skip(next_source)
+            .find(|(_, source_stream)| source_stream.language == stream.language && source_stream.codec_name == stream.codec_name)
+            .map(|(source_position, _)| source_position);
+
+        let Some(source_position) = matched else { continue };
+        next_source = source_position + 1;
+
+        let is_forced = forced.contains(&source_position);
+        let is_default = is_forced && !has_default && primary_lang.as_deref() == Some(terminologic_lang(&stream.language));
+
+        has_default |= is_default;
+
+        flags.push(SubtitleFlags { position, forced: is_forced, default: is_default });
+    }
+
+    flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(codec_type: &str, codec_name: &str, language: &str) -> MediaStream {
+        MediaStream { codec_type: codec_type.to_string(), codec_name: codec_name.to_string(), language: language.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_subtitle_flags_default_only_in_primary_audio_language() {
+        let subtitles = vec![
+            stream("subtitle", "hdmv_pgs_subtitle", "eng"),
+            stream("subtitle", "hdmv_pgs_subtitle", "eng"),
+            stream("subtitle", "hdmv_pgs_subtitle", "ger"),
+            stream("subtitle", "hdmv_pgs_subtitle", "ger"),
+        ];
+
+        let mut source_streams = vec![stream("video", "h264", "und"), stream("audio", "dts", "eng"), stream("audio", "ac3", "ger")];
+        source_streams.extend(subtitles.clone());
+        let source = MediaInfo { streams: source_streams, ..Default::default() };
+
+        let mut output_streams = vec![stream("video", "hevc", "und"), stream("audio", "ac3", "deu"), stream("audio", "dts", "eng")];
+        output_streams.extend(subtitles);
+        let output = MediaInfo { streams: output_streams, ..Default::default() };
+
+        assert_eq!(
+            subtitle_flags(&source, &output, &[1, 3]),
+            vec![
+                SubtitleFlags { position: 0, forced: false, default: false },
+                SubtitleFlags { position: 1, forced: true, default: false },
+                SubtitleFlags { position: 2, forced: false, default: false },
+                SubtitleFlags { position: 3, forced: true, default: true },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_subtitle_flags_without_forced_track_in_primary_language() {
+        let streams = vec![stream("audio", "ac3", "eng"), stream("subtitle", "hdmv_pgs_subtitle", "ger")];
+        let info = MediaInfo { streams, ..Default::default() };
+
+        assert_eq!(subtitle_flags(&info, &info, &[0]), vec![SubtitleFlags { position: 0, forced: true, default: false }]);
+    }
+}
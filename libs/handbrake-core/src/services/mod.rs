@@ -2,4 +2,43 @@ pub mod profiles;
 pub use profiles::{get_encoding_profiles, Profile};
 
 pub mod encoding;
-pub use encoding::{encode_files, EncodingProgressPayload};
+pub use encoding::{clean_partial_outputs, encode_file, encode_files, partial_output_path, EncodingProgressPayload, ProcessPriority};
+
+pub mod encoder;
+pub use encoder::{Encoder, EncoderProgress, HandBrakeEncoder};
+
+pub mod ffmpeg_encoding;
+pub use ffmpeg_encoding::FfmpegEncoder;
+
+pub mod queue;
+pub use queue::{EncodingJob, EncodingQueue};
+
+pub mod remuxing;
+pub use remuxing::remux_files;
+
+pub mod probing;
+pub use probing::{probe_file, MediaChapter, MediaInfo, MediaStream};
+
+pub mod verification;
+pub use verification::{verify_file, QualityMetric, VerificationOptions, VerificationReport};
+
+pub mod estimation;
+pub use estimation::{EncodingEstimate, EncodingHistory, EncodingRecord, SourceProperties};
+
+pub mod tagging;
+pub use tagging::{write_metadata, ContainerMetadata};
+
+pub mod subtitles;
+pub use subtitles::{ocr_subtitles, SubtitleOcrOptions, SubtitleOutput};
+
+pub mod flagging;
+pub use flagging::{detect_forced_subtitles, flag_forced_subtitles};
+
+pub mod analysis;
+pub use analysis::{analyze_source, AnalysisOptions, Crop, FieldMode, SourceAnalysis};
+
+pub mod downmix;
+pub use downmix::{add_downmix_tracks, select_downmix_tracks, AudioTrack, DownmixOptions};
+
+pub mod previews;
+pub use previews::{generate_previews, PreviewOptions, Previews};
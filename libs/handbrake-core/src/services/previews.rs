@@ -0,0 +1,151 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tracing::info;
+
+use crate::probe_file;
+
+const CLIP_FILE_NAME: &str = "clip.mp4";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PreviewOptions {
+    pub frames: u32,
+    pub width: u32,
+    pub clip_duration: u32,
+}
+
+impl Default for PreviewOptions {
+    fn default() -> Self {
+        Self { frames: 4, width: 480, clip_duration: 10 }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Previews {
+    pub frames: Vec<String>,
+    pub clip: Option<String>,
+}
+
+impl Previews {
+    /// Returns the previews inside a directory, if all frames (and the clip, if requested) were generated before.
+    pub fn cached(output_dir: &str, clip: bool, options: &PreviewOptions) -> Option<Self> {
+        let previews = Self::paths(output_dir, clip, options);
+
+        let complete = previews.frames.iter().chain(previews.clip.iter()).all(|file| Path::new(file).exists());
+        complete.then_some(previews)
+    }
+
+    fn paths(output_dir: &str, clip: bool, options: &PreviewOptions) -> Self {
+        Self {
+            frames: (0..options.frames)
+                .map(|frame| Path::new(output_dir).join(format!("frame_{}.jpg", frame)).to_string_lossy().to_string())
+                .collect(),
+            clip: clip.then(|| Path::new(output_dir).join(CLIP_FILE_NAME).to_string_lossy().to_string()),
+        }
+    }
+}
+
+/// Generates preview frames and optionally a short low resolution clip of a media file.
+///
+/// The frames are taken at evenly distributed positions of the file and scaled to `options.width`,
+/// the clip is taken from the middle of the file. Previews that already exist in `output_dir` are
+/// reused, so the directory can be used as cache (see `Previews::cached`).
+///
+/// # Arguments
+///
+/// * `command` - A string slice that holds the preview command to be executed (path of ffmpeg).
+/// * `ffprobe_command` - A string slice that holds the probing command used to read the duration (path of ffprobe).
+/// * `file` - A string slice that holds the path of the media file.
+/// * `output_dir` - A string slice that holds the directory the previews are written to.
+/// * `clip` - Whether a clip should be generated in addition to the frames.
+/// * `duration` - The playable duration of the file in seconds, probed if `None`. It should be given for truncated
+///   files (e.g. partial rips), whose header may still report the duration of the complete media.
+/// * `options` - The `PreviewOptions` containing the number of frames, their width and the clip duration.
+///
+/// # Returns
+///
+/// This function returns a `Result` containing the `Previews` with the paths of the frames and the clip.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The output directory cannot be created.
+/// * The media file cannot be probed.
+/// * The preview process cannot be started or exits unsuccessfully.
+/// * The preview process didn't write a frame, e.g. because the position is past the end of a truncated file.
+///
+/// # Example
+///
+/// ```no_run
+/// # use handbrake_core::{generate_previews, PreviewOptions};
+/// # fn main() -> anyhow::Result<()> {
+/// let previews = generate_previews("ffmpeg", "ffprobe", "/output/title_t00.mkv", "/output/previews/title_t00", true, None, &PreviewOptions::default())?;
+/// println!("frames: {:?}, clip: {:?}", previews.frames, previews.clip);
+/// # Ok(())
+/// # }
+/// ```
+pub fn generate_previews(
+    command: &str, ffprobe_command: &str, file: &str, output_dir: &str, clip: bool, duration: Option<f32>, options: &PreviewOptions,
+) -> Result<Previews> {
+    fs::create_dir_all(output_dir).context("failed to create preview directory")?;
+
+    let previews = Previews::paths(output_dir, clip, options);
+
+    if let Some(previews) = Previews::cached(output_dir, clip, options) {
+        return Ok(previews);
+    }
+
+    info!("generating previews of file: {} into {}", file, output_dir);
+
+    let duration = match duration {
+        Some(duration) => duration,
+        None => probe_file(ffprobe_command, file).context("failed to probe file")?.duration,
+    };
+    let scale = format!("scale={}:-2", options.width);
+
+    for (frame, frame_file) in previews.frames.iter().enumerate().filter(|(_, frame_file)| !Path::new(frame_file).exists()) {
+        let position = duration * (frame + 1) as f32 / (options.frames + 1) as f32;
+
+        run_ffmpeg(
+            Command::new(command)
+                .args(["-hide_banner", "-nostats", "-y", "-ss", &position.to_string(), "-i", file])
+                .args(["-map", "0:v:0", "-frames:v", "1", "-vf", &scale, "-q:v", "4", frame_file]),
+        )?;
+
+        // ffmpeg exits successfully without writing a frame if the position is past the end of the file
+        if !Path::new(frame_file).exists() {
+            anyhow::bail!("no frame found at {}s of file: {}", position, file);
+        }
+    }
+
+    if let Some(clip_file) = previews.clip.as_ref().filter(|clip_file| !Path::new(clip_file).exists()) {
+        let start = (duration - options.clip_duration as f32).max(0.0) / 2.0;
+
+        run_ffmpeg(
+            Command::new(command)
+                .args(["-hide_banner", "-nostats", "-y", "-ss", &start.to_string(), "-t", &options.clip_duration.to_string(), "-i", file])
+                .args(["-map", "0:v:0", "-vf", &scale, "-c:v", "libx264", "-preset", "veryfast", "-crf", "30", "-an", "-sn"])
+                .args(["-movflags", "+faststart", clip_file]),
+        )?;
+    }
+
+    Ok(previews)
+}
+
+/// Runs a preview process and checks its exit status.
+fn run_ffmpeg(process: &mut Command) -> Result<()> {
+    let status = process
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .context("failed to spawn preview process")?;
+
+    if !status.success() {
+        anyhow::bail!("preview process exited with {}", status);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+use tracing::info;
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct MediaInfo {
+    pub duration: f32,
+    pub size: u64,
+    pub bit_rate: u64,
+    pub streams: Vec<MediaStream>,
+    pub chapters: Vec<MediaChapter>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct MediaStream {
+    pub index: usize,
+    pub codec_type: String,
+    pub codec_name: String,
+    pub language: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub channels: Option<u32>,
+    pub forced: bool,
+    pub title: String,
+    pub frames: Option<u64>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct MediaChapter {
+    pub start: f32,
+    pub end: f32,
+    pub title: String,
+}
+
+impl MediaInfo {
+    /// Returns the languages of all streams of the given codec type (e.g. `audio` or `subtitle`).
+    pub fn languages(&self, codec_type: &str) -> Vec<&str> {
+        self.streams
+            .iter()
+            .filter(|stream| stream.codec_type == codec_type)
+            .map(|stream| stream.language.as_str())
+            .collect()
+    }
+}
+
+/// Maps the bibliographic form of an ISO 639-2 language code (e.g. `ger`, used by MakeMKV and ffprobe) to
+/// its terminologic form (e.g. `deu`), so both forms of a language compare equal. Other codes are kept.
+pub(crate) fn terminologic_lang(lang: &str) -> &str {
+    match lang {
+        "alb" => "sqi",
+        "arm" => "hye",
+        "baq" => "eus",
+        "bur" => "mya",
+        "chi" => "zho",
+        "cze" => "ces",
+        "dut" => "nld",
+        "fre" => "fra",
+        "geo" => "kat",
+        "ger" => "deu",
+        "gre" => "ell",
+        "ice" => "isl",
+        "mac" => "mkd",
+        "mao" => "mri",
+        "may" => "msa",
+        "per" => "fas",
+        "rum" => "ron",
+        "slo" => "slk",
+        "tib" => "bod",
+        "wel" => "cym",
+        lang => lang,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeOutput {
+    format: ProbeFormat,
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+    #[serde(default)]
+    chapters: Vec<ProbeChapter>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeFormat {
+    duration: Option<String>,
+    size: Option<String>,
+    bit_rate: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeStream {
+    index: usize,
+    codec_type: Option<String>,
+    codec_name: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    channels: Option<u32>,
+    #[serde(default)]
+    tags: ProbeTags,
+    #[serde(default)]
+    disposition: ProbeDisposition,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ProbeDisposition {
+    #[serde(default)]
+    forced: u8,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeChapter {
+    start_time: String,
+    end_time: String,
+    #[serde(default)]
+    tags: ProbeTags,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ProbeTags {
+    language: Option<String>,
+    title: Option<String>,
+    #[serde(flatten)]
+    other: HashMap<String, String>,
+}
+
+impl ProbeTags {
+    /// Returns the frame count written by mkvmerge, which uses `NUMBER_OF_FRAMES` or a language specific variant
+    /// like `NUMBER_OF_FRAMES-eng` as tag name. The plain tag is preferred if a stream has several of them.
+    fn number_of_frames(&self) -> Option<u64> {
+        self.other
+            .get("NUMBER_OF_FRAMES")
+            .or_else(|| {
+                self.other
+                    .iter()
+                    .filter(|(key, _)| key.starts_with("NUMBER_OF_FRAMES-"))
+                    .min_by_key(|(key, _)| key.as_str())
+                    .map(|(_, value)| value)
+            })
+            .and_then(|frames| frames.trim().parse().ok())
+    }
+}
+
+/// Reads the container, stream and chapter information of a media file using ffprobe.
+///
+/// # Arguments
+///
+/// * `command` - A string slice that holds the probing command to be executed (path of ffprobe).
+/// * `file` - A string slice that holds the path of the media file to probe.
+///
+/// # Returns
+///
+/// This function returns a `Result` containing the `MediaInfo` of the file if successful.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The probing process cannot be started or exits unsuccessfully.
+/// * The output of the probing process cannot be parsed.
+///
+/// # Example
+///
+/// ```no_run
+/// # use handbrake_core::probe_file;
+/// # fn main() -> anyhow::Result<()> {
+/// let info = probe_file("ffprobe", "/output/encoding/title_t00.mkv")?;
+/// println!("duration: {}s, audio languages: {:?}", info.duration, info.languages("audio"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn probe_file(command: &str, file: &str) -> Result<MediaInfo> {
+    info!("probing file: {} with command: {}", file, command);
+
+    let output = Command::new(command)
+        .args(["-v", "error", "-print_format", "json", "-show_format", "-show_streams", "-show_chapters", file])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .context("failed to spawn probing process")?;
+
+    if !output.status.success() {
+        anyhow::bail!("probing process exited with {}", output.status);
+    }
+
+    let probe: ProbeOutput = serde_json::from_slice(&output.stdout).context("failed to parse probing output")?;
+
+    Ok(MediaInfo {
+        duration: probe.format.duration.and_then(|d| d.parse().ok()).unwrap_or(0.0),
+        size: probe.format.size.and_then(|s| s.parse().ok()).unwrap_or(0),
+        bit_rate: probe.format.bit_rate.and_then(|b| b.parse().ok()).unwrap_or(0),
+        streams: probe
+            .streams
+            .into_iter()
+            .map(|stream| MediaStream {
+                index: stream.index,
+                frames: stream.tags.number_of_frames(),
+                codec_type: stream.codec_type.unwrap_or_default(),
+                codec_name: stream.codec_name.unwrap_or_default(),
+                language: stream.tags.language.unwrap_or_else(|| "und".to_string()),
+                title: stream.tags.title.unwrap_or_default(),
+                width: stream.width,
+                height: stream.height,
+                channels: stream.channels,
+                forced: stream.disposition.forced == 1,
+            })
+            .collect(),
+        chapters: probe
+            .chapters
+            .into_iter()
+            .map(|chapter| MediaChapter {
+                start: chapter.start_time.parse().unwrap_or(0.0),
+                end: chapter.end_time.parse().unwrap_or(0.0),
+                title: chapter.tags.title.unwrap_or_default(),
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_of_frames() {
+        let tags: ProbeTags = serde_json::from_str(r#"{"language": "eng", "NUMBER_OF_FRAMES": "1234"}"#).unwrap();
+        assert_eq!(tags.number_of_frames(), Some(1234));
+
+        let tags: ProbeTags = serde_json::from_str(r#"{"language": "eng", "NUMBER_OF_FRAMES-eng": "567"}"#).unwrap();
+        assert_eq!(tags.number_of_frames(), Some(567));
+
+        let tags: ProbeTags = serde_json::from_str(r#"{"language": "eng", "title": "Forced"}"#).unwrap();
+        assert_eq!(tags.number_of_frames(), None);
+    }
+
+    #[test]
+    fn test_number_of_frames_with_plain_and_language_tag() {
+        let tags: ProbeTags = serde_json::from_str(r#"{"NUMBER_OF_FRAMES-eng": "567", "NUMBER_OF_FRAMES": "1234", "language": "eng"}"#).unwrap();
+
+        assert_eq!(tags.language.as_deref(), Some("eng"));
+        assert_eq!(tags.number_of_frames(), Some(1234));
+    }
+}
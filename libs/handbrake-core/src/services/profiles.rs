@@ -8,6 +8,23 @@ pub struct Profile {
     pub label: String,
     pub file_name: String,
     pub preset_name: String,
+    #[serde(default)]
+    pub ffmpeg_args: Vec<String>,
+    #[serde(default)]
+    pub hdr: bool,
+    #[serde(default)]
+    pub dolby_vision: bool,
+}
+
+impl Profile {
+    /// Returns whether the profile keeps the dynamic range of a source.
+    ///
+    /// Profiles have to declare that they preserve HDR (10 bit output with HDR10, HDR10+ or HLG metadata)
+    /// and Dolby Vision, otherwise encoding such a source would produce washed out colors or drop the
+    /// Dolby Vision metadata. SDR sources are supported by every profile.
+    pub fn supports_dynamic_range(&self, hdr: bool, dolby_vision: bool) -> bool {
+        (!hdr || self.hdr) && (!dolby_vision || self.dolby_vision)
+    }
 }
 
 /// Loads encoding profiles from a given base directory path.
@@ -19,7 +36,7 @@ pub struct Profile {
 /// # Arguments
 ///
 /// * `profile_base_path` - A string slice that holds the path to the directory
-///                         containing the `index.json` file and the encoding profiles.
+///   containing the `index.json` file and the encoding profiles.
 ///
 /// # Returns
 ///
@@ -35,8 +52,8 @@ pub struct Profile {
 ///
 /// # Example
 ///
-/// ```
-/// use your_crate::get_encoding_profiles;
+/// ```no_run
+/// use handbrake_core::get_encoding_profiles;
 ///
 /// let profiles = get_encoding_profiles("/path/to/profiles").expect("Failed to get encoding profiles");
 /// for profile in profiles {
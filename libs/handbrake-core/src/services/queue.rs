@@ -0,0 +1,164 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+use tracing::{error, info};
+
+use crate::{Encoder, EncoderProgress, EncodingHistory, EncodingProgressPayload, EncodingRecord, Profile, SourceAnalysis, SourceProperties};
+
+/// A single file that should be encoded by the `EncodingQueue`.
+///
+/// The queue forwards the progress reported by the `Encoder` through `sender` as `"progress"` events and
+/// passes `cancel_flag` on to it. Once the job is finished, exactly one of the events `"encoded"`, `"failed"`
+/// or `"cancelled"` is sent, each carrying the `step` of the job.
+/// If `analysis` is set, its crop and deinterlace decisions override the settings of the profile.
+#[derive(Debug, Clone)]
+pub struct EncodingJob {
+    pub profile: Profile,
+    pub input: String,
+    pub output_dir: String,
+    pub step: usize,
+    pub source: SourceProperties,
+    pub analysis: Option<SourceAnalysis>,
+    pub cancel_flag: Arc<AtomicBool>,
+    pub sender: Sender<(&'static str, Option<EncodingProgressPayload>)>,
+}
+
+impl EncodingJob {
+    /// Returns the path of the encoded file: the `encoding` directory inside the output directory, keeping the file name of the input.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file name of the input cannot be determined.
+    pub fn output_path(&self) -> Result<PathBuf> {
+        let file_name = Path::new(&self.input).file_name().context("failed to get file name")?;
+        Ok(Path::new(&self.output_dir).join("encoding/").join(file_name))
+    }
+}
+
+/// A queue that encodes files with a fixed number of concurrent workers sharing one `Encoder`.
+///
+/// Jobs are processed in the order they were enqueued. The queue can be cloned and shared between
+/// multiple ripping jobs, so the number of concurrent encoding processes stays limited across discs.
+/// If an `EncodingHistory` is given, every successful encode is recorded to calibrate estimates. Jobs whose
+/// output already exists, e.g. because the job is resumed after a restart, are reported as encoded right away.
+#[derive(Debug, Clone)]
+pub struct EncodingQueue {
+    sender: Sender<EncodingJob>,
+}
+
+impl EncodingQueue {
+    /// Creates a new `EncodingQueue` and spawns its worker threads.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoder` - The `Encoder` backend used to encode the files (e.g. `HandBrakeEncoder` or `FfmpegEncoder`).
+    /// * `workers` - The number of files that are encoded concurrently (at least one worker is spawned).
+    /// * `history` - The history successful encodes are recorded to, if any.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of `EncodingQueue`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use handbrake_core::{EncodingQueue, HandBrakeEncoder, ProcessPriority};
+    /// # use std::sync::Arc;
+    /// let encoder = HandBrakeEncoder { command: "HandBrakeCLI".to_string(), ffprobe_command: "ffprobe".to_string(), priority: ProcessPriority::default() };
+    /// let queue = EncodingQueue::new(Arc::new(encoder), 2, None);
+    /// ```
+    pub fn new(encoder: Arc<dyn Encoder>, workers: usize, history: Option<EncodingHistory>) -> Self {
+        let (sender, receiver) = mpsc::channel::<EncodingJob>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for worker in 0..workers.max(1) {
+            let receiver = receiver.clone();
+            let encoder = encoder.clone();
+            let history = history.clone();
+
+            thread::spawn(move || run_worker(worker, encoder.as_ref(), history.as_ref(), &receiver));
+        }
+
+        Self { sender }
+    }
+
+    /// Adds a job to the end of the queue.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if all workers of the queue have stopped.
+    pub fn enqueue(&self, job: EncodingJob) -> Result<()> {
+        info!("enqueueing file: {} for encoding", job.input);
+        self.sender.send(job).map_err(|_| anyhow!("encoding queue is closed"))
+    }
+}
+
+/// Receives jobs from the queue and encodes them one after another until the queue is dropped.
+fn run_worker(worker: usize, encoder: &dyn Encoder, history: Option<&EncodingHistory>, receiver: &Mutex<Receiver<EncodingJob>>) {
+    loop {
+        let job = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => break,
+        };
+
+        let Ok(job) = job else { break };
+
+        let payload = Some(EncodingProgressPayload { progress: 1.0, eta: 0.0, step: job.step });
+
+        if job.cancel_flag.load(Ordering::Relaxed) {
+            job.sender.send(("cancelled", payload)).ok();
+            continue;
+        }
+
+        if job.output_path().is_ok_and(|output_path| output_path.exists()) {
+            info!("worker {} skipped file: {}, it is already encoded", worker, job.input);
+            job.sender.send(("encoded", payload)).ok();
+            continue;
+        }
+
+        info!("worker {} started encoding file: {}", worker, job.input);
+        let started = Instant::now();
+
+        let report_progress = |progress: EncoderProgress| {
+            job.sender
+                .send(("progress", Some(EncodingProgressPayload { progress: progress.progress, eta: progress.eta, step: job.step })))
+                .ok();
+        };
+
+        match encoder.encode(&job, &report_progress, &job.cancel_flag) {
+            Ok(output_file) => {
+                if let Some(history) = history {
+                    record_encoding(history, &job, &output_file, started.elapsed().as_secs_f32());
+                }
+
+                job.sender.send(("encoded", payload)).ok();
+            }
+            Err(e) if job.cancel_flag.load(Ordering::Relaxed) => {
+                info!("worker {} aborted encoding file {}: {:?}", worker, job.input, e);
+                job.sender.send(("cancelled", payload)).ok();
+            }
+            Err(e) => {
+                error!("worker {} failed to encode file {}: {:?}", worker, job.input, e);
+                job.sender.send(("failed", payload)).ok();
+            }
+        }
+    }
+}
+
+/// Records a successful encode in the history, logging instead of failing the job on errors.
+fn record_encoding(history: &EncodingHistory, job: &EncodingJob, output_file: &str, encode_time: f32) {
+    let output_size = match std::fs::metadata(output_file) {
+        Ok(metadata) => metadata.len(),
+        Err(e) => return error!("failed to read size of encoded file {}: {:?}", output_file, e),
+    };
+
+    let record = EncodingRecord { profile_id: job.profile.id.clone(), source: job.source.clone(), analysis: job.analysis.clone(), output_size, encode_time };
+
+    if let Err(e) = history.record(record) {
+        error!("failed to record encoding history: {:?}", e);
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc::Sender, Arc};
+use tracing::info;
+
+use utils::ProgressTracker;
+
+use crate::services::encoding::write_atomically;
+use crate::EncodingProgressPayload;
+
+const GUI_PROGRESS_PREFIX: &str = "#GUI#progress";
+
+/// Remuxes a list of files without re-encoding them and sends progress updates through a channel.
+///
+/// Each file is written to the `encoding` directory inside `output_dir`, so the remuxed files end up at the
+/// same place as encoded files would. The files are remuxed with mkvmerge, keeping only the audio and subtitle tracks
+/// of the given languages, or all tracks if `langs` is empty. Remuxes are written into partial files and renamed once
+/// they are complete. If all tracks are kept and the input files aren't needed afterwards (`keep_files` is `false`),
+/// they are moved into place instead, which neither needs time nor additional disk space. Files whose output already
+/// exists are skipped.
+///
+/// # Arguments
+///
+/// * `command` - A string slice that holds the remuxing command to be executed (path of mkvmerge).
+/// * `files` - A slice of string slices representing the paths of the files to be remuxed.
+/// * `output_dir` - A string slice specifying the directory where remuxed files will be saved.
+/// * `langs` - The ISO 639-2 language codes of the audio and subtitle tracks to keep (e.g. `deu`, `eng`).
+/// * `keep_files` - Whether the input files are still needed, e.g. to encode them or to archive them.
+/// * `cancel_flag` - An `Arc<AtomicBool>` that can be used to signal cancellation of the operation.
+/// * `sender` - A `Sender` channel for sending progress updates and completion notifications.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * Any of the input files cannot be moved or processed.
+/// * The output directory cannot be created.
+/// * The remuxing process cannot be started or exits with an error.
+/// * The cancel flag is set while remuxing.
+///
+/// # Example
+///
+/// ```no_run
+/// # use handbrake_core::remux_files;
+/// # use std::sync::atomic::AtomicBool;
+/// # use std::sync::mpsc::channel;
+/// # use std::sync::Arc;
+/// # fn main() -> anyhow::Result<()> {
+/// let cancel_flag = Arc::new(AtomicBool::new(false));
+/// let (sender, receiver) = channel();
+///
+/// remux_files("mkvmerge", &["/output/title_t00.mkv"], "/output", &["deu", "eng"], false, cancel_flag, sender)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn remux_files(
+    command: &str, files: &[&str], output_dir: &str, langs: &[&str], keep_files: bool, cancel_flag: Arc<AtomicBool>,
+    sender: Sender<(&'static str, Option<EncodingProgressPayload>)>,
+) -> Result<()> {
+    let remux_output_dir = Path::new(output_dir).join("encoding/");
+
+    if !remux_output_dir.exists() {
+        std::fs::create_dir_all(&remux_output_dir).context("failed to create remux output directory")?;
+    }
+
+    for (i, &file) in files.iter().enumerate() {
+        if cancel_flag.load(Ordering::Relaxed) {
+            anyhow::bail!("operation aborted");
+        }
+
+        let file_name = Path::new(file).file_name().context("failed to get file name")?;
+        let output_path = remux_output_dir.join(file_name);
+
+        if output_path.exists() {
+            info!("skipping file: {}, it is already remuxed", file);
+        } else if langs.is_empty() && !keep_files {
+            // the remux is edited in place afterwards (e.g. to flag subtitles and add tags), so the file can only be
+            // moved if nothing else reads it anymore
+            info!("keeping file: {} untouched, moving it to {}", file, output_path.display());
+            std::fs::rename(file, &output_path).context("failed to move file")?;
+        } else {
+            write_atomically(&output_path, |partial_file| remux_file(command, file, partial_file, langs, i, &cancel_flag, &sender))?;
+        }
+
+        sender
+            .send(("progress", Some(EncodingProgressPayload { progress: 1.0, eta: 0.0, step: i })))
+            .unwrap();
+    }
+
+    sender.send(("done", None)).unwrap();
+
+    Ok(())
+}
+
+/// Remuxes a single file with mkvmerge, keeping only the audio and subtitle tracks of the given languages, or all
+/// tracks if no languages are given.
+fn remux_file(
+    command: &str, file: &str, output_file: &str, langs: &[&str], step: usize, cancel_flag: &Arc<AtomicBool>,
+    sender: &Sender<(&'static str, Option<EncodingProgressPayload>)>,
+) -> Result<()> {
+    let langs = langs.join(",");
+    let track_filters = if langs.is_empty() { vec![] } else { vec!["--audio-tracks", &langs, "--subtitle-tracks", &langs] };
+
+    info!("remuxing file: {} keeping languages: {} into {}", file, if langs.is_empty() { "all" } else { &langs }, output_file);
+
+    let mut process = Command::new(command)
+        .args(["--gui-mode", "--output", output_file])
+        .args(&track_filters)
+        .arg(file)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("failed to spawn remuxing process")?;
+
+    let stdout = BufReader::new(process.stdout.take().context("failed to capture stdout")?);
+    let mut progress_tracker = ProgressTracker::new();
+
+    for line in stdout.lines() {
+        if cancel_flag.load(Ordering::Relaxed) {
+            process.kill()?;
+            process.wait().ok();
+            anyhow::bail!("operation aborted");
+        }
+
+        let line = line?;
+
+        if let Some(value) = line.trim().strip_prefix(GUI_PROGRESS_PREFIX) {
+            let percent: f32 = value.trim().trim_end_matches('%').parse().context("failed to parse progress value")?;
+            progress_tracker.update(percent, 100.0).unwrap();
+
+            sender
+                .send(("progress", Some(EncodingProgressPayload { progress: percent / 100.0, eta: progress_tracker.get_eta(), step })))
+                .unwrap();
+        }
+    }
+
+    let status = process.wait().context("failed to wait for remuxing process")?;
+
+    // mkvmerge exits with 1 if the file was written successfully but warnings occurred
+    if !matches!(status.code(), Some(0) | Some(1)) {
+        anyhow::bail!("remuxing process exited with {}", status);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,630 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tracing::{error, info};
+
+use crate::services::encoding::write_atomically;
+use crate::services::probing::terminologic_lang;
+use crate::{detect_forced_subtitles, probe_file, MediaStream};
+
+const PGS_CODEC: &str = "hdmv_pgs_subtitle";
+const VOBSUB_CODEC: &str = "dvd_subtitle";
+const TEXT_CODEC: &str = "subrip";
+
+/// The display time of the last PGS subtitle of a track, if the track doesn't end it explicitly.
+const DEFAULT_DISPLAY_TIME: f32 = 5.0;
+
+/// The maximum number of pixels of a decoded PGS image, so corrupt streams can't allocate huge images.
+const MAX_IMAGE_PIXELS: usize = 4096 * 4096;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SubtitleOutput {
+    /// Places the SRT files beside the output file, e.g. `title_t00.de.forced.srt`.
+    #[default]
+    Sidecar,
+    /// Muxes the SRT files as additional subtitle tracks into the output file.
+    Mux,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SubtitleOcrOptions {
+    pub enabled: bool,
+    pub langs: Vec<String>,
+    pub output: SubtitleOutput,
+    pub tesseract_command: String,
+    pub mkvextract_command: String,
+    pub vobsub2srt_command: String,
+}
+
+impl Default for SubtitleOcrOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            langs: vec![],
+            output: SubtitleOutput::Sidecar,
+            tesseract_command: "tesseract".to_string(),
+            mkvextract_command: "mkvextract".to_string(),
+            vobsub2srt_command: "vobsub2srt".to_string(),
+        }
+    }
+}
+
+/// The id and position of an object that is shown by a PGS composition.
+type CompositionObject = (u16, usize, usize);
+
+#[derive(Debug)]
+struct SubtitleImage {
+    start: f32,
+    end: f32,
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+/// Converts the image based subtitle tracks of a file to SRT subtitles using OCR.
+///
+/// PGS (Blu-ray) and VobSub (DVD) tracks of `source` are extracted with mkvextract. PGS subtitles are
+/// decoded and every subtitle image is recognized with tesseract, VobSub tracks are converted with
+/// vobsub2srt (which uses tesseract as well). Depending on the configured output, the resulting SRT files
+/// are placed beside `output` with Jellyfin compatible names (e.g. `title_t00.de.forced.srt`) or muxed into
+/// `output` as additional subtitle tracks. Forced tracks are detected with `detect_forced_subtitles` and
+/// keep their forced flag. Tracks that fail to convert are skipped, as well as languages
+/// the output already contains text subtitles for (e.g. because they were muxed in a previous run).
+///
+/// # Arguments
+///
+/// * `ffprobe_command` - A string slice that holds the probing command to be executed (path of ffprobe).
+/// * `mkvmerge_command` - A string slice that holds the muxing command used for the `mux` output (path of mkvmerge).
+/// * `source` - A string slice that holds the path of the file containing the image based subtitles (the ripped title).
+/// * `output` - A string slice that holds the path of the output file the subtitles belong to.
+/// * `options` - The `SubtitleOcrOptions` containing the languages to convert, the output and the OCR commands.
+///
+/// # Returns
+///
+/// This function returns a `Result` containing the paths of the written SRT files. If the subtitles are
+/// muxed into the output, the list is empty.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The source file cannot be probed.
+/// * The temporary working directory cannot be created.
+/// * The subtitles cannot be muxed into the output file.
+///
+/// # Example
+///
+/// ```no_run
+/// # use handbrake_core::{ocr_subtitles, SubtitleOcrOptions};
+/// # fn main() -> anyhow::Result<()> {
+/// let options = SubtitleOcrOptions { enabled: true, langs: vec!["ger".to_string()], ..Default::default() };
+/// let files = ocr_subtitles("ffprobe", "mkvmerge", "/output/title_t00.mkv", "/output/encoding/title_t00.mkv", &options)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn ocr_subtitles(ffprobe_command: &str, mkvmerge_command: &str, source: &str, output: &str, options: &SubtitleOcrOptions) -> Result<Vec<String>> {
+    let source_info = probe_file(ffprobe_command, source).context("failed to probe source file")?;
+    let output_info = probe_file(ffprobe_command, output).context("failed to probe output file")?;
+
+    let text_langs = output_info
+        .streams
+        .iter()
+        .filter(|stream| stream.codec_type == "subtitle" && stream.codec_name == TEXT_CODEC)
+        .map(|stream| stream.language.as_str())
+        .collect::<Vec<&str>>();
+
+    let forced = detect_forced_subtitles(&source_info, &[]);
+
+    let streams = source_info
+        .streams
+        .iter()
+        .filter(|stream| stream.codec_type == "subtitle")
+        .enumerate()
+        .filter(|(_, stream)| stream.codec_name == PGS_CODEC || stream.codec_name == VOBSUB_CODEC)
+        .filter(|(_, stream)| options.langs.is_empty() || options.langs.contains(&stream.language))
+        .filter(|(_, stream)| !text_langs.contains(&stream.language.as_str()))
+        .map(|(position, stream)| MediaStream { forced: forced.contains(&position), ..stream.clone() })
+        .collect::<Vec<MediaStream>>();
+
+    if streams.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let work_dir = PathBuf::from(format!("{}.ocr", output));
+    fs::create_dir_all(&work_dir).context("failed to create ocr working directory")?;
+
+    let mut subtitles = vec![];
+
+    for stream in &streams {
+        info!("converting {} subtitle track {} ({}) of file: {}", stream.codec_name, stream.index, stream.language, source);
+
+        match convert_stream(source, stream, &work_dir, options) {
+            Ok(Some(srt_file)) => subtitles.push((stream, srt_file)),
+            Ok(None) => info!("subtitle track {} of file: {} contains no text", stream.index, source),
+            Err(e) => error!("failed to convert subtitle track {} of file {}: {:?}", stream.index, source, e),
+        }
+    }
+
+    let result = match options.output {
+        SubtitleOutput::Sidecar => place_sidecars(output, &subtitles),
+        SubtitleOutput::Mux => mux_subtitles(mkvmerge_command, output, &subtitles).map(|_| vec![]),
+    };
+
+    fs::remove_dir_all(&work_dir).ok();
+
+    result
+}
+
+/// Extracts a single subtitle track and converts it to an SRT file inside the working directory.
+fn convert_stream(source: &str, stream: &MediaStream, work_dir: &Path, options: &SubtitleOcrOptions) -> Result<Option<PathBuf>> {
+    let track = work_dir.join(stream.index.to_string());
+    let srt_file = track.with_extension("srt");
+
+    let extension = if stream.codec_name == PGS_CODEC { "sup" } else { "sub" };
+    let extracted = track.with_extension(extension);
+
+    let status = Command::new(&options.mkvextract_command)
+        .args([source, "tracks", &format!("{}:{}", stream.index, extracted.display())])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .context("failed to spawn extraction process")?;
+
+    if !status.success() {
+        anyhow::bail!("extraction process exited with {}", status);
+    }
+
+    if stream.codec_name == PGS_CODEC {
+        let images = read_pgs(&fs::read(&extracted).context("failed to read extracted subtitles")?)?;
+        let mut entries = vec![];
+
+        for (i, image) in images.iter().enumerate() {
+            let image_file = work_dir.join(format!("{}_{}.pgm", stream.index, i));
+            fs::write(&image_file, [format!("P5\n{} {}\n255\n", image.width, image.height).as_bytes(), &image.pixels].concat())?;
+
+            let text = recognize_text(&options.tesseract_command, &image_file, tesseract_lang(&stream.language))?;
+
+            if !text.is_empty() {
+                entries.push(format!("{}\n{} --> {}\n{}\n", entries.len() + 1, srt_timestamp(image.start), srt_timestamp(image.end), text));
+            }
+        }
+
+        fs::write(&srt_file, entries.join("\n")).context("failed to write srt file")?;
+    } else {
+        let status = Command::new(&options.vobsub2srt_command)
+            .args(["--tesseract-lang", tesseract_lang(&stream.language), &track.to_string_lossy()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .context("failed to spawn vobsub conversion process")?;
+
+        if !status.success() {
+            anyhow::bail!("vobsub conversion process exited with {}", status);
+        }
+    }
+
+    let has_text = fs::read_to_string(&srt_file).is_ok_and(|contents| !contents.trim().is_empty());
+    Ok(has_text.then_some(srt_file))
+}
+
+/// Recognizes the text of a single subtitle image with tesseract.
+fn recognize_text(command: &str, image_file: &Path, lang: &str) -> Result<String> {
+    let output = Command::new(command)
+        .args([&image_file.to_string_lossy(), "stdout", "-l", lang, "--psm", "6"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .context("failed to spawn ocr process")?;
+
+    if !output.status.success() {
+        anyhow::bail!("ocr process exited with {}", output.status);
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    Ok(text.lines().map(str::trim).filter(|line| !line.is_empty()).collect::<Vec<&str>>().join("\n"))
+}
+
+/// Moves the SRT files beside the output file, named `<output>.<lang>[.forced].srt`.
+fn place_sidecars(output: &str, subtitles: &[(&MediaStream, PathBuf)]) -> Result<Vec<String>> {
+    let output_path = Path::new(output);
+    let stem = output_path.file_stem().context("failed to get file name")?.to_string_lossy();
+
+    let mut files = vec![];
+
+    for (stream, srt_file) in subtitles {
+        let suffix = format!("{}{}.srt", iso_639_1(&stream.language), if stream.forced { ".forced" } else { "" });
+
+        let mut sidecar = output_path.with_file_name(format!("{}.{}", stem, suffix));
+        if files.contains(&sidecar.to_string_lossy().to_string()) {
+            sidecar = output_path.with_file_name(format!("{}.{}.{}", stem, stream.index, suffix));
+        }
+
+        fs::rename(srt_file, &sidecar).context("failed to move srt file")?;
+        files.push(sidecar.to_string_lossy().to_string());
+    }
+
+    Ok(files)
+}
+
+/// Muxes the SRT files as additional subtitle tracks into the output file.
+fn mux_subtitles(command: &str, output: &str, subtitles: &[(&MediaStream, PathBuf)]) -> Result<()> {
+    if subtitles.is_empty() {
+        return Ok(());
+    }
+
+    write_atomically(Path::new(output), |partial_file| {
+        let mut process = Command::new(command);
+        process.args(["--output", partial_file, output]);
+
+        for (stream, srt_file) in subtitles {
+            process
+                .args(["--language", &format!("0:{}", stream.language)])
+                .args(["--forced-display-flag", &format!("0:{}", if stream.forced { "yes" } else { "no" })])
+                .args(["--track-name", &format!("0:{} (OCR)", stream.language.to_uppercase())])
+                .arg(srt_file);
+        }
+
+        let status = process
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .context("failed to spawn muxing process")?;
+
+        // mkvmerge exits with 1 if the file was written successfully but warnings occurred
+        if !matches!(status.code(), Some(0) | Some(1)) {
+            anyhow::bail!("muxing process exited with {}", status);
+        }
+
+        Ok(())
+    })
+}
+
+/// Decodes the subtitle images of a PGS (`.sup`) stream.
+///
+/// A presentation composition segment with objects starts displaying a subtitle once its display set is
+/// complete, the next presentation composition segment ends it. The images are rendered as black text on
+/// a white background, which gives the best results with tesseract.
+///
+/// # Errors
+///
+/// Returns an error if a segment is invalid or truncated, or if an object cannot be decoded.
+fn read_pgs(data: &[u8]) -> Result<Vec<SubtitleImage>> {
+    let mut palette: HashMap<u8, (u8, u8)> = HashMap::new();
+    let mut objects: HashMap<u16, (usize, usize, Vec<u8>)> = HashMap::new();
+    let mut composition: Option<(f32, Vec<CompositionObject>)> = None;
+    let mut current: Option<SubtitleImage> = None;
+    let mut images = vec![];
+
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let header = data.get(pos..pos + 13).context("truncated pgs segment header")?;
+
+        if &header[..2] != b"PG" {
+            anyhow::bail!("invalid pgs segment at offset {}", pos);
+        }
+
+        let pts = u32::from_be_bytes([header[2], header[3], header[4], header[5]]) as f32 / 90_000.0;
+        let segment_type = header[10];
+        let size = u16::from_be_bytes([header[11], header[12]]) as usize;
+        let body = data.get(pos + 13..pos + 13 + size).context("truncated pgs segment")?;
+
+        pos += 13 + size;
+
+        match segment_type {
+            // presentation composition segment
+            0x16 if body.len() >= 11 => {
+                if let Some(mut image) = current.take() {
+                    image.end = pts;
+                    images.push(image);
+                }
+
+                let mut composition_objects = vec![];
+                let mut offset = 11;
+
+                for _ in 0..body[10] {
+                    let Some(object) = body.get(offset..offset + 8) else { break };
+                    let id = u16::from_be_bytes([object[0], object[1]]);
+                    let x = u16::from_be_bytes([object[4], object[5]]) as usize;
+                    let y = u16::from_be_bytes([object[6], object[7]]) as usize;
+
+                    composition_objects.push((id, x, y));
+                    offset += if object[3] & 0x40 != 0 { 16 } else { 8 };
+                }
+
+                composition = (!composition_objects.is_empty()).then_some((pts, composition_objects));
+            }
+            // palette definition segment
+            0x14 => {
+                for entry in body.get(2..).unwrap_or_default().chunks_exact(5) {
+                    palette.insert(entry[0], (entry[1], entry[4]));
+                }
+            }
+            // object definition segment
+            0x15 if body.len() >= 4 => {
+                let id = u16::from_be_bytes([body[0], body[1]]);
+
+                if body[3] & 0x80 != 0 && body.len() >= 11 {
+                    let width = u16::from_be_bytes([body[7], body[8]]) as usize;
+                    let height = u16::from_be_bytes([body[9], body[10]]) as usize;
+                    objects.insert(id, (width, height, body[11..].to_vec()));
+                } else if let Some((_, _, rle)) = objects.get_mut(&id) {
+                    rle.extend_from_slice(&body[4..]);
+                }
+            }
+            // end of display set segment
+            0x80 => {
+                if let Some((start, composition_objects)) = composition.take() {
+                    current = render_composition(start, &composition_objects, &objects, &palette)?;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(mut image) = current.take() {
+        image.end = image.start + DEFAULT_DISPLAY_TIME;
+        images.push(image);
+    }
+
+    Ok(images)
+}
+
+/// Renders the objects of a composition into a single grayscale image with a small margin.
+///
+/// Returns `None` if none of the objects of the composition are defined.
+///
+/// # Errors
+///
+/// Returns an error if an object cannot be decoded or the image would be larger than `MAX_IMAGE_PIXELS`.
+fn render_composition(
+    start: f32, composition_objects: &[CompositionObject], objects: &HashMap<u16, (usize, usize, Vec<u8>)>, palette: &HashMap<u8, (u8, u8)>,
+) -> Result<Option<SubtitleImage>> {
+    const MARGIN: usize = 10;
+
+    let placed = composition_objects
+        .iter()
+        .filter_map(|(id, x, y)| objects.get(id).map(|(width, height, rle)| (*x, *y, *width, *height, rle)))
+        .collect::<Vec<_>>();
+
+    let (Some(left), Some(top)) = (placed.iter().map(|(x, ..)| *x).min(), placed.iter().map(|(_, y, ..)| *y).min()) else {
+        return Ok(None);
+    };
+    let right = placed.iter().map(|(x, _, width, ..)| x + width).max().unwrap_or(left);
+    let bottom = placed.iter().map(|(_, y, _, height, _)| y + height).max().unwrap_or(top);
+
+    let width = right - left + 2 * MARGIN;
+    let height = bottom - top + 2 * MARGIN;
+
+    if width * height > MAX_IMAGE_PIXELS {
+        anyhow::bail!("pgs composition of {}x{} pixels is too large", width, height);
+    }
+
+    let mut pixels = vec![255; width * height];
+
+    for (x, y, object_width, object_height, rle) in placed {
+        for (i, color) in decode_rle(rle, object_width, object_height)?.into_iter().enumerate() {
+            let (luma, alpha) = palette.get(&color).copied().unwrap_or((0, 0));
+
+            if alpha > 128 && luma > 128 {
+                let row = y - top + MARGIN + i / object_width;
+                let column = x - left + MARGIN + i % object_width;
+                pixels[row * width + column] = 0;
+            }
+        }
+    }
+
+    Ok(Some(SubtitleImage { start, end: start, width, height, pixels }))
+}
+
+/// Decodes the run length encoded palette indices of a PGS object.
+///
+/// Each run is either a single non-zero palette index, or a zero byte followed by a flag byte holding the
+/// length (6 or 14 bits) and whether the run has a color other than `0`. A zero flag ends the current line,
+/// the rest of the line is transparent.
+///
+/// # Errors
+///
+/// Returns an error if the object is larger than `MAX_IMAGE_PIXELS`, a run is incomplete, or the data
+/// ends before all pixels of the object are decoded.
+fn decode_rle(data: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
+    if width * height > MAX_IMAGE_PIXELS {
+        anyhow::bail!("pgs object of {}x{} pixels is too large", width, height);
+    }
+
+    let mut pixels = Vec::with_capacity(width * height);
+    let mut i = 0;
+
+    while i < data.len() && pixels.len() < width * height {
+        let byte = data[i];
+        i += 1;
+
+        if byte != 0 {
+            pixels.push(byte);
+            continue;
+        }
+
+        let flag = *data.get(i).context("truncated pgs run")?;
+        i += 1;
+
+        // end of line, the rest of the line is transparent
+        if flag == 0 {
+            let remainder = pixels.len() % width.max(1);
+            if remainder != 0 {
+                pixels.resize(pixels.len() + width - remainder, 0);
+            }
+            continue;
+        }
+
+        let mut length = (flag & 0x3F) as usize;
+        if flag & 0x40 != 0 {
+            length = (length << 8) | *data.get(i).context("truncated pgs run length")? as usize;
+            i += 1;
+        }
+
+        let color = if flag & 0x80 != 0 {
+            let color = *data.get(i).context("truncated pgs run color")?;
+            i += 1;
+            color
+        } else {
+            0
+        };
+
+        pixels.extend(std::iter::repeat_n(color, length));
+    }
+
+    if pixels.len() < width * height {
+        anyhow::bail!("truncated pgs object, decoded {} of {} pixels", pixels.len(), width * height);
+    }
+
+    pixels.truncate(width * height);
+    Ok(pixels)
+}
+
+fn srt_timestamp(seconds: f32) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!("{:0>2}:{:0>2}:{:0>2},{:0>3}", millis / 3_600_000, millis / 60_000 % 60, millis / 1000 % 60, millis % 1000)
+}
+
+/// Maps an ISO 639-2 language code to the name of the tesseract language model, which uses the terminologic codes.
+fn tesseract_lang(lang: &str) -> &str {
+    match terminologic_lang(lang) {
+        "zho" => "chi_sim",
+        "und" => "eng",
+        lang => lang,
+    }
+}
+
+/// Maps an ISO 639-2 language code to the ISO 639-1 code used in subtitle file names, if known.
+fn iso_639_1(lang: &str) -> &str {
+    match lang {
+        "ger" | "deu" => "de",
+        "eng" => "en",
+        "fre" | "fra" => "fr",
+        "spa" => "es",
+        "ita" => "it",
+        "dut" | "nld" => "nl",
+        "por" => "pt",
+        "pol" => "pl",
+        "cze" | "ces" => "cs",
+        "dan" => "da",
+        "swe" => "sv",
+        "nor" => "no",
+        "fin" => "fi",
+        "rus" => "ru",
+        "tur" => "tr",
+        "gre" | "ell" => "el",
+        "hun" => "hu",
+        "jpn" => "ja",
+        "chi" | "zho" => "zh",
+        "kor" => "ko",
+        lang => lang,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a PGS segment with the given presentation timestamp (in 90 kHz ticks), type and body.
+    fn segment(pts: u32, segment_type: u8, body: &[u8]) -> Vec<u8> {
+        let mut segment = b"PG".to_vec();
+        segment.extend(pts.to_be_bytes());
+        segment.extend([0; 4]);
+        segment.push(segment_type);
+        segment.extend((body.len() as u16).to_be_bytes());
+        segment.extend(body);
+        segment
+    }
+
+    /// A display set showing a 4x2 object at `(100, 50)` from second 1 to 3.
+    fn display_set() -> Vec<u8> {
+        let composition = [0x07, 0x80, 0x04, 0x38, 0x10, 0x00, 0x01, 0x80, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x64, 0x00, 0x32];
+        let palette = [0x00, 0x00, 0x01, 0xEB, 0x80, 0x80, 0xFF];
+        // first line: two pixels of color 1 and an end of line, second line: a run of four pixels of color 1
+        let object = [0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x0F, 0x00, 0x04, 0x00, 0x02, 0x01, 0x01, 0x00, 0x00, 0x00, 0x84, 0x01, 0x00, 0x00];
+        let clear = [0x07, 0x80, 0x04, 0x38, 0x10, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00];
+
+        [segment(90_000, 0x16, &composition), segment(90_000, 0x14, &palette), segment(90_000, 0x15, &object), segment(90_000, 0x80, &[]), segment(270_000, 0x16, &clear)]
+            .concat()
+    }
+
+    #[test]
+    fn decodes_rle_runs() {
+        // single pixel, short transparent run, long transparent run, short colored run, long colored run
+        let data = [0x05, 0x00, 0x02, 0x00, 0x40, 0x03, 0x00, 0x82, 0x07, 0x00, 0xC0, 0x02, 0x09];
+        let pixels = decode_rle(&data, 10, 1).unwrap();
+
+        assert_eq!(pixels, [5, 0, 0, 0, 0, 0, 7, 7, 9, 9]);
+    }
+
+    #[test]
+    fn pads_rle_lines_ended_early() {
+        let pixels = decode_rle(&[0x03, 0x00, 0x00, 0x04, 0x04, 0x04], 3, 2).unwrap();
+        assert_eq!(pixels, [3, 0, 0, 4, 4, 4]);
+    }
+
+    #[test]
+    fn rejects_truncated_rle_runs() {
+        assert!(decode_rle(&[0x01, 0x00], 2, 1).is_err());
+        assert!(decode_rle(&[0x00, 0x40], 64, 1).is_err());
+        assert!(decode_rle(&[0x00, 0x82], 2, 1).is_err());
+    }
+
+    #[test]
+    fn rejects_short_rle_buffers() {
+        assert!(decode_rle(&[0x01, 0x01], 2, 2).is_err());
+        assert!(decode_rle(&[], 1, 1).is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_rle_objects() {
+        assert!(decode_rle(&[0x01], 65_535, 65_535).is_err());
+    }
+
+    #[test]
+    fn reads_pgs_display_set() {
+        let images = read_pgs(&display_set()).unwrap();
+
+        assert_eq!(images.len(), 1);
+        assert_eq!((images[0].start, images[0].end), (1.0, 3.0));
+        assert_eq!((images[0].width, images[0].height), (24, 22));
+
+        let pixel = |row: usize, column: usize| images[0].pixels[row * images[0].width + column];
+        assert_eq!((pixel(10, 10), pixel(10, 11), pixel(10, 12)), (0, 0, 255));
+        assert_eq!((pixel(11, 10), pixel(11, 13)), (0, 0));
+        assert_eq!((pixel(9, 10), pixel(12, 10)), (255, 255));
+    }
+
+    #[test]
+    fn ends_last_pgs_subtitle_after_default_display_time() {
+        let data = display_set();
+        let last_segment = data.len() - 13 - 11;
+
+        let images = read_pgs(&data[..last_segment]).unwrap();
+        assert_eq!((images[0].start, images[0].end), (1.0, 1.0 + DEFAULT_DISPLAY_TIME));
+    }
+
+    #[test]
+    fn rejects_truncated_pgs_streams_without_panicking() {
+        let data = display_set();
+        let boundaries = [0, 13 + 19, 13 + 19 + 13 + 7, 13 + 19 + 13 + 7 + 13 + 20, 13 + 19 + 13 + 7 + 13 + 20 + 13, data.len()];
+
+        for length in 0..=data.len() {
+            let result = read_pgs(&data[..length]);
+            assert_eq!(result.is_ok(), boundaries.contains(&length), "prefix of {} bytes", length);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_pgs_segments() {
+        let mut data = display_set();
+        data[0] = b'X';
+
+        assert!(read_pgs(&data).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::process::{Command, Stdio};
+use tracing::info;
+
+use tmdb_client::Episode;
+
+use crate::probe_file;
+
+/// The metadata that is written into the container of an encoded or remuxed file.
+///
+/// For movies, `title` is the title of the movie. For TV shows, `title` is the name of the show and
+/// `episode` holds the TMDB episode the file belongs to. Chapter names are applied in order, chapters
+/// without a given name keep their current name.
+#[derive(Debug, Default, Clone)]
+pub struct ContainerMetadata {
+    pub title: String,
+    pub year: Option<u32>,
+    pub episode: Option<Episode>,
+    pub chapter_names: Vec<String>,
+}
+
+impl ContainerMetadata {
+    /// Returns the title shown by players, e.g. `Inception (2010)` or `Dark - S01E02 - Lügen`.
+    pub fn display_title(&self) -> String {
+        match (&self.episode, self.year) {
+            (Some(episode), _) => format!("{} - S{:0>2}E{:0>2} - {}", self.title, episode.season_number, episode.episode_number, episode.name),
+            (None, Some(year)) => format!("{} ({})", self.title, year),
+            (None, None) => self.title.clone(),
+        }
+    }
+}
+
+/// Writes the title, tags and chapter names of a file in place using mkvpropedit.
+///
+/// The segment title is set to the display title of the metadata. Additionally, Matroska tags are written:
+/// for movies the `TITLE` and `DATE_RELEASED` of the movie, for episodes the `TITLE`, `PART_NUMBER` and
+/// `DATE_RELEASED` of the episode together with the show title and season number on the collection and
+/// season level. If chapter names are given, the chapters of the file are read with ffprobe and rewritten
+/// with the new names, keeping their timestamps.
+///
+/// # Arguments
+///
+/// * `command` - A string slice that holds the tagging command to be executed (path of mkvpropedit).
+/// * `ffprobe_command` - A string slice that holds the probing command used to read the chapters (path of ffprobe).
+/// * `file` - A string slice that holds the path of the Matroska file to tag.
+/// * `metadata` - The `ContainerMetadata` that should be written into the file.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The chapters of the file cannot be read.
+/// * The temporary tag or chapter files cannot be written.
+/// * The tagging process cannot be started or exits with an error.
+///
+/// # Example
+///
+/// ```no_run
+/// # use handbrake_core::{write_metadata, ContainerMetadata};
+/// # fn main() -> anyhow::Result<()> {
+/// let metadata = ContainerMetadata { title: "Inception".to_string(), year: Some(2010), ..Default::default() };
+/// write_metadata("mkvpropedit", "ffprobe", "/output/encoding/title_t00.mkv", &metadata)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn write_metadata(command: &str, ffprobe_command: &str, file: &str, metadata: &ContainerMetadata) -> Result<()> {
+    let title = metadata.display_title();
+
+    info!("writing metadata: {} into file: {}", title, file);
+
+    let tags_file = format!("{}.tags.xml", file);
+    let chapters_file = format!("{}.chapters.xml", file);
+
+    fs::write(&tags_file, build_tags(metadata)).context("failed to write tags file")?;
+
+    let mut process = Command::new(command);
+    process
+        .args([file, "--edit", "info", "--set", &format!("title={}", title)])
+        .args(["--tags", &format!("global:{}", tags_file)]);
+
+    if !metadata.chapter_names.is_empty() {
+        let chapters = probe_file(ffprobe_command, file).context("failed to read chapters")?.chapters;
+
+        if !chapters.is_empty() {
+            let names = chapters
+                .iter()
+                .enumerate()
+                .map(|(i, chapter)| (chapter.start, metadata.chapter_names.get(i).unwrap_or(&chapter.title).as_str()))
+                .collect::<Vec<_>>();
+
+            fs::write(&chapters_file, build_chapters(&names)).context("failed to write chapters file")?;
+            process.args(["--chapters", &chapters_file]);
+        }
+    }
+
+    let status = process.stdout(Stdio::null()).stderr(Stdio::null()).status();
+
+    fs::remove_file(&tags_file).ok();
+    fs::remove_file(&chapters_file).ok();
+
+    let status = status.context("failed to spawn tagging process")?;
+
+    // mkvpropedit exits with 1 if the file was modified successfully but warnings occurred
+    if !matches!(status.code(), Some(0) | Some(1)) {
+        anyhow::bail!("tagging process exited with {}", status);
+    }
+
+    Ok(())
+}
+
+/// Builds a Matroska tags XML document from the metadata.
+fn build_tags(metadata: &ContainerMetadata) -> String {
+    let mut tags = vec![];
+
+    match &metadata.episode {
+        Some(episode) => {
+            tags.push(build_tag(70, &[("TITLE", metadata.title.clone())]));
+            tags.push(build_tag(60, &[("PART_NUMBER", episode.season_number.to_string())]));
+
+            let mut simple = vec![("TITLE", episode.name.clone()), ("PART_NUMBER", episode.episode_number.to_string())];
+            if let Some(air_date) = &episode.air_date {
+                simple.push(("DATE_RELEASED", air_date.clone()));
+            }
+
+            tags.push(build_tag(50, &simple));
+        }
+        None => {
+            let mut simple = vec![("TITLE", metadata.title.clone())];
+            if let Some(year) = metadata.year {
+                simple.push(("DATE_RELEASED", year.to_string()));
+            }
+
+            tags.push(build_tag(50, &simple));
+        }
+    }
+
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Tags>\n{}</Tags>\n", tags.concat())
+}
+
+/// Builds a single tag with the given target type value and simple tags.
+fn build_tag(target_type_value: u32, simple: &[(&str, String)]) -> String {
+    let simple = simple
+        .iter()
+        .map(|(name, value)| format!("    <Simple><Name>{}</Name><String>{}</String></Simple>\n", name, escape_xml(value)))
+        .collect::<String>();
+
+    format!("  <Tag>\n    <Targets><TargetTypeValue>{}</TargetTypeValue></Targets>\n{}  </Tag>\n", target_type_value, simple)
+}
+
+/// Builds a Matroska chapters XML document from the start times (in seconds) and names of the chapters.
+fn build_chapters(chapters: &[(f32, &str)]) -> String {
+    let atoms = chapters
+        .iter()
+        .map(|(start, name)| {
+            let nanos = (*start as f64 * 1_000_000_000.0).round() as u64;
+            let timestamp = format!(
+                "{:0>2}:{:0>2}:{:0>2}.{:0>9}",
+                nanos / 3_600_000_000_000,
+                nanos / 60_000_000_000 % 60,
+                nanos / 1_000_000_000 % 60,
+                nanos % 1_000_000_000
+            );
+
+            format!(
+                "    <ChapterAtom>\n      <ChapterTimeStart>{}</ChapterTimeStart>\n      <ChapterDisplay><ChapterString>{}</ChapterString><ChapterLanguage>und</ChapterLanguage></ChapterDisplay>\n    </ChapterAtom>\n",
+                timestamp,
+                escape_xml(name)
+            )
+        })
+        .collect::<String>();
+
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Chapters>\n  <EditionEntry>\n{}  </EditionEntry>\n</Chapters>\n", atoms)
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,257 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::process::{Command, Stdio};
+use tracing::info;
+
+use crate::probe_file;
+use crate::services::analysis::SourceAnalysis;
+use crate::services::probing::terminologic_lang;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityMetric {
+    Vmaf,
+    Ssim,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct VerificationOptions {
+    pub enabled: bool,
+    pub duration_tolerance: f32,
+    pub audio_langs: Vec<String>,
+    pub subtitle_langs: Vec<String>,
+    pub metric: Option<QualityMetric>,
+    pub samples: u32,
+    pub sample_duration: u32,
+    pub threshold: f32,
+}
+
+impl Default for VerificationOptions {
+    fn default() -> Self {
+        Self { enabled: false, duration_tolerance: 2.0, audio_langs: vec![], subtitle_langs: vec![], metric: None, samples: 3, sample_duration: 10, threshold: 0.0 }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct VerificationReport {
+    pub file: String,
+    pub source_duration: f32,
+    pub output_duration: f32,
+    pub missing_audio_langs: Vec<String>,
+    pub missing_subtitle_langs: Vec<String>,
+    pub score: Option<f32>,
+    pub passed: bool,
+    pub reasons: Vec<String>,
+}
+
+/// Verifies an encoded file against its source.
+///
+/// The verification checks that the duration of the output matches the duration of the source within
+/// `duration_tolerance` seconds, and that every language of `audio_langs` and `subtitle_langs` which exists
+/// in the source also exists in the output. If a quality metric is configured, the function additionally
+/// computes the average VMAF or SSIM score of `samples` evenly distributed segments using ffmpeg and
+/// compares it to the configured `threshold`. The crop and deinterlace filters of the source analysis are applied to the
+/// source before it is compared, so cropped or deinterlaced encodes are compared against the same picture.
+///
+/// # Arguments
+///
+/// * `ffprobe_command` - A string slice that holds the probing command to be executed (path of ffprobe).
+/// * `ffmpeg_command` - A string slice that holds the command used to compute quality metrics (path of ffmpeg).
+/// * `source` - A string slice that holds the path of the source file (the ripped title).
+/// * `output` - A string slice that holds the path of the encoded file.
+/// * `analysis` - The `SourceAnalysis` the output was encoded with, or `None` if the source wasn't analyzed.
+/// * `options` - The `VerificationOptions` describing which checks should be done.
+///
+/// # Returns
+///
+/// This function returns a `Result` containing a `VerificationReport`. A report with `passed` set to
+/// `false` lists the failed checks in `reasons`.
+///
+/// # Errors
+///
+/// This function will return an error if the files cannot be probed or the quality metric cannot be computed.
+///
+/// # Example
+///
+/// ```no_run
+/// # use handbrake_core::{verify_file, QualityMetric, VerificationOptions};
+/// # fn main() -> anyhow::Result<()> {
+/// let options = VerificationOptions { enabled: true, metric: Some(QualityMetric::Vmaf), threshold: 90.0, ..Default::default() };
+/// let report = verify_file("ffprobe", "ffmpeg", "/output/title_t00.mkv", "/output/encoding/title_t00.mkv", None, &options)?;
+///
+/// if !report.passed {
+///     println!("verification failed: {:?}", report.reasons);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn verify_file(
+    ffprobe_command: &str, ffmpeg_command: &str, source: &str, output: &str, analysis: Option<&SourceAnalysis>, options: &VerificationOptions,
+) -> Result<VerificationReport> {
+    info!("verifying file: {} against source: {}", output, source);
+
+    let source_info = probe_file(ffprobe_command, source).context("failed to probe source file")?;
+    let output_info = probe_file(ffprobe_command, output).context("failed to probe output file")?;
+
+    let mut report = VerificationReport { file: output.to_string(), source_duration: source_info.duration, output_duration: output_info.duration, ..Default::default() };
+
+    if (source_info.duration - output_info.duration).abs() > options.duration_tolerance {
+        report
+            .reasons
+            .push(format!("duration mismatch: source has {:.1}s, output has {:.1}s", source_info.duration, output_info.duration));
+    }
+
+    report.missing_audio_langs = missing_langs(&source_info.languages("audio"), &output_info.languages("audio"), &options.audio_langs);
+    report.missing_subtitle_langs = missing_langs(&source_info.languages("subtitle"), &output_info.languages("subtitle"), &options.subtitle_langs);
+
+    if !report.missing_audio_langs.is_empty() {
+        report.reasons.push(format!("missing audio tracks: {}", report.missing_audio_langs.join(", ")));
+    }
+
+    if !report.missing_subtitle_langs.is_empty() {
+        report
+            .reasons
+            .push(format!("missing subtitle tracks: {}", report.missing_subtitle_langs.join(", ")));
+    }
+
+    if let Some(metric) = options.metric {
+        let score = compute_score(ffmpeg_command, source, output, source_info.duration, metric, analysis, options).context("failed to compute quality score")?;
+
+        if score < options.threshold {
+            report
+                .reasons
+                .push(format!("{:?} score {:.3} is below the threshold of {:.3}", metric, score, options.threshold));
+        }
+
+        report.score = Some(score);
+    }
+
+    report.passed = report.reasons.is_empty();
+
+    info!(passed = report.passed, score = report.score, "verified file: {}", output);
+
+    Ok(report)
+}
+
+/// Returns the requested languages that exist in the source but not in the output. Languages are compared by their
+/// terminologic ISO 639-2 code, so `ger` in the options matches a `deu` track and vice versa.
+fn missing_langs(source_langs: &[&str], output_langs: &[&str], langs: &[String]) -> Vec<String> {
+    let contains = |langs: &[&str], lang: &str| langs.iter().any(|other| terminologic_lang(other) == terminologic_lang(lang));
+
+    langs
+        .iter()
+        .filter(|lang| contains(source_langs, lang) && !contains(output_langs, lang))
+        .cloned()
+        .collect()
+}
+
+/// Computes the average quality score of evenly distributed samples of the output compared to the source.
+fn compute_score(
+    ffmpeg_command: &str, source: &str, output: &str, duration: f32, metric: QualityMetric, analysis: Option<&SourceAnalysis>, options: &VerificationOptions,
+) -> Result<f32> {
+    let samples = options.samples.max(1);
+    let sample_duration = options.sample_duration.to_string();
+    let filter = metric_filter(metric, analysis);
+
+    let mut scores = vec![];
+
+    for sample in 0..samples {
+        let start = (duration * (sample + 1) as f32 / (samples + 1) as f32 - options.sample_duration as f32 / 2.0)
+            .max(0.0)
+            .to_string();
+
+        let process = Command::new(ffmpeg_command)
+            .args(["-hide_banner", "-nostats", "-ss", &start, "-t", &sample_duration, "-i", output])
+            .args(["-ss", &start, "-t", &sample_duration, "-i", source, "-lavfi", &filter, "-f", "null", "-"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .context("failed to spawn quality metric process")?;
+
+        if !process.status.success() {
+            anyhow::bail!("quality metric process exited with {}", process.status);
+        }
+
+        let stderr = String::from_utf8_lossy(&process.stderr);
+
+        let score = match metric {
+            QualityMetric::Vmaf => stderr.lines().find_map(|line| line.split("VMAF score:").nth(1)).map(|value| value.trim()),
+            QualityMetric::Ssim => stderr
+                .lines()
+                .filter(|line| line.contains("SSIM "))
+                .find_map(|line| line.split("All:").nth(1)?.split_whitespace().next()),
+        };
+
+        scores.push(
+            score
+                .context("failed to find quality score in output")?
+                .parse::<f32>()
+                .context("failed to parse quality score")?,
+        );
+    }
+
+    Ok(scores.iter().sum::<f32>() / scores.len() as f32)
+}
+
+/// Builds the filter graph comparing the output (`[0:v]`) to the source (`[1:v]`) with the given metric.
+///
+/// The filters of the source analysis are applied to the source first, so the reference has the same crop and
+/// field order as the output. Only then the output is scaled to the size of the reference.
+fn metric_filter(metric: QualityMetric, analysis: Option<&SourceAnalysis>) -> String {
+    let reference_filters = analysis.map(|analysis| analysis.ffmpeg_filters()).unwrap_or_default();
+    let reference = reference_filters.iter().map(|filter| format!("{},", filter)).collect::<String>();
+
+    let metric = match metric {
+        QualityMetric::Vmaf => "libvmaf",
+        QualityMetric::Ssim => "ssim",
+    };
+
+    format!("[0:v]setpts=PTS-STARTPTS[dist];[1:v]{}setpts=PTS-STARTPTS[ref];[dist][ref]scale2ref[dist][ref];[dist][ref]{}", reference, metric)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Crop, FieldMode};
+
+    #[test]
+    fn test_missing_langs() {
+        let langs = vec!["ger".to_string(), "eng".to_string(), "fre".to_string()];
+
+        assert_eq!(missing_langs(&["ger", "eng", "fre"], &["ger", "eng"], &langs), vec!["fre".to_string()]);
+        assert!(missing_langs(&["eng"], &["eng"], &langs).is_empty());
+    }
+
+    #[test]
+    fn test_missing_langs_bibliographic_and_terminologic_codes() {
+        let langs = vec!["deu".to_string(), "fre".to_string()];
+
+        assert!(missing_langs(&["ger", "fra"], &["ger", "fra"], &langs).is_empty());
+        assert_eq!(missing_langs(&["ger", "fra"], &["eng"], &langs), langs);
+    }
+
+    #[test]
+    fn test_metric_filter_without_analysis() {
+        assert_eq!(
+            metric_filter(QualityMetric::Ssim, None),
+            "[0:v]setpts=PTS-STARTPTS[dist];[1:v]setpts=PTS-STARTPTS[ref];[dist][ref]scale2ref[dist][ref];[dist][ref]ssim"
+        );
+    }
+
+    #[test]
+    fn test_metric_filter_crops_reference() {
+        let analysis = SourceAnalysis {
+            width: 1920,
+            height: 1080,
+            crop: Some(Crop { top: 140, bottom: 140, left: 0, right: 0 }),
+            field_mode: Some(FieldMode::Interlaced),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            metric_filter(QualityMetric::Vmaf, Some(&analysis)),
+            "[0:v]setpts=PTS-STARTPTS[dist];[1:v]bwdif,crop=1920:800:0:140,setpts=PTS-STARTPTS[ref];[dist][ref]scale2ref[dist][ref];[dist][ref]libvmaf"
+        );
+    }
+}
@@ -1,4 +1,5 @@
 mod services;
 
-pub use services::{detect_devices, filter_movie_main_features, filter_tv_series_main_features, read_disc_properties, rip_titles};
-pub use services::{AudioStream, Device, Disc, ProgressPayload, SubtitleStream, Title, VideoStream};
+pub use services::{detect_devices, filter_movie_main_features, filter_tv_series_main_features, read_disc_properties, rip_title_partially, rip_titles};
+pub use services::{parse_disc_label, suggest_disc_matches, LabelHints, MatchSuggestion, MediaType};
+pub use services::{AudioStream, Device, Disc, HdrFormat, ProgressPayload, SubtitleStream, Title, VideoStream};
@@ -41,14 +41,14 @@ pub struct Device {
 ///
 /// # Example
 ///
-/// ```rust
-/// use makemkv_core::{detect_devices};
+/// ```no_run
+/// use makemkv_core::detect_devices;
 /// use std::sync::{Arc, Mutex};
 ///
 /// let command = "makemkvcon";
 /// let makemkv_mutex = Arc::new(Mutex::new(()));
 ///
-/// match detect_devices(command, makemkv_mutex) {
+/// match detect_devices(command, &makemkv_mutex) {
 ///     Ok(devices) => println!("Detected devices: {:?}", devices),
 ///     Err(e) => eprintln!("Error detecting devices: {}", e),
 /// }
@@ -59,7 +59,7 @@ pub fn detect_devices(command: &str, makemkv_mutex: &Arc<Mutex<()>>) -> Result<V
     info!("detecting devices with command: {}", command);
 
     let mut process = Command::new(command)
-        .args(&["-r", "--cache=1", "info", "disc:999"])
+        .args(["-r", "--cache=1", "info", "disc:999"])
         .stdout(Stdio::piped())
         .spawn()
         .context("failed to spawn devices process")?;
@@ -87,7 +87,7 @@ pub fn detect_devices(command: &str, makemkv_mutex: &Arc<Mutex<()>>) -> Result<V
         })
         .collect();
 
-    if devices.len() == 0 {
+    if devices.is_empty() {
         anyhow::bail!("no devices found");
     }
 
@@ -0,0 +1,540 @@
+use anyhow::Result;
+use serde::Serialize;
+use tracing::debug;
+
+use tmdb_client::TmdbClient;
+
+use crate::Disc;
+
+/// Tokens of volume labels that describe the disc or its release instead of the media.
+const RELEASE_MARKERS: &[&str] = &[
+    "bd",
+    "bluray",
+    "blu",
+    "dvd",
+    "uhd",
+    "4k",
+    "hd",
+    "3d",
+    "pal",
+    "ntsc",
+    "secam",
+    "r0",
+    "r1",
+    "r2",
+    "r3",
+    "r4",
+    "r5",
+    "r6",
+    "ra",
+    "rb",
+    "rc",
+    "region",
+    "ws",
+    "fs",
+    "widescreen",
+    "fullscreen",
+    "uk",
+    "ger",
+    "eu",
+    "eur",
+    "euro",
+    "aus",
+    "nl",
+    "nordic",
+    "scan",
+    "retail",
+    "rental",
+];
+
+/// Region markers that are ordinary words as well (e.g. `IT` or `THIS_IS_US`), only removed at the end of a label.
+const REGION_MARKERS: &[&str] = &["us", "de", "fr", "it", "es", "na"];
+
+/// The number of search results per query that are ranked.
+const MAX_CANDIDATES: usize = 5;
+
+/// The type of media a suggestion refers to, named like the media types of rip jobs.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaType {
+    Movie,
+    TvShow,
+}
+
+/// The hints extracted from a disc volume label.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LabelHints {
+    /// The normalised title, i.e. the lowercase words of the label without season, disc and release markers.
+    pub title: String,
+    pub season: Option<u16>,
+    pub disc: Option<u16>,
+    pub year: Option<u32>,
+}
+
+/// A TMDB movie or TV series that might be on a disc, scored by how well it matches the disc.
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchSuggestion {
+    pub media_type: MediaType,
+    pub tmdb_id: u32,
+    pub title: String,
+    pub year: Option<u32>,
+    pub poster_path: Option<String>,
+    /// The season of the disc extracted from its label, for TV series only.
+    pub season: Option<u16>,
+    /// The number of the disc within its release extracted from its label.
+    pub disc: Option<u16>,
+    /// How confident the match is, from `0` (no match) to `1` (certain match).
+    pub confidence: f32,
+}
+
+/// Extracts the title, season, disc and year hints from a disc volume label.
+///
+/// The label is split at underscores, dots, dashes and spaces. Season markers (`S2`, `SEASON_2`),
+/// disc markers (`D1`, `DISC3`, `DISK_2`), combined markers (`S2D3`), years and release markers like
+/// `PAL`, `R2` or `BLURAY` are removed from the title. Region markers that are ordinary words as well,
+/// like `US` or `IT`, are only removed if they follow the title and only release markers come after them,
+/// so `THIS_IS_US_S1_D1` keeps its title while `AMELIE_FR` becomes `amelie`.
+///
+/// # Arguments
+///
+/// * `label` - The volume label of the disc, e.g. `THE_OFFICE_S2_DISC3`.
+///
+/// # Returns
+///
+/// The `LabelHints` of the label, e.g. the title `the office`, season `2` and disc `3`.
+///
+/// # Examples
+///
+/// ```
+/// use makemkv_core::parse_disc_label;
+///
+/// let hints = parse_disc_label("STAR_WARS_EP4_D1");
+/// assert_eq!(hints.title, "star wars ep4");
+/// assert_eq!(hints.disc, Some(1));
+/// ```
+pub fn parse_disc_label(label: &str) -> LabelHints {
+    let tokens = label
+        .split(|c: char| c == '_' || c == '.' || c == '-' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .fold(vec![], |mut tokens: Vec<String>, token| {
+            // `BLU_RAY` is a single marker, while `RAY` alone is a title
+            if token == "ray" && tokens.last().is_some_and(|last| last == "blu") {
+                tokens.pop();
+                tokens.push("bluray".to_string());
+            } else {
+                tokens.push(token);
+            }
+            tokens
+        });
+
+    let is_marker = |token: &String| RELEASE_MARKERS.contains(&token.as_str()) || REGION_MARKERS.contains(&token.as_str());
+
+    let mut hints = LabelHints::default();
+    let mut words: Vec<String> = vec![];
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = tokens[i].as_str();
+        let next_number = tokens.get(i + 1).and_then(|next| next.parse::<u16>().ok());
+
+        if let Some((season, disc)) = parse_season_disc(token) {
+            hints.season = hints.season.or(Some(season));
+            hints.disc = hints.disc.or(disc);
+        } else if let Some(disc) = parse_prefixed_number(token, &["disc", "disk", "dvd", "bd", "d"]) {
+            hints.disc = hints.disc.or(Some(disc));
+        } else if matches!(token, "season" | "staffel" | "series") && next_number.is_some() {
+            hints.season = hints.season.or(next_number);
+            i += 1;
+        } else if matches!(token, "disc" | "disk" | "dvd" | "cd") && next_number.is_some() {
+            hints.disc = hints.disc.or(next_number);
+            i += 1;
+        } else if let Some(year) = token
+            .parse::<u32>()
+            .ok()
+            .filter(|year| (1900..=2099).contains(year) && token.len() == 4 && !words.is_empty())
+        {
+            hints.year = Some(year);
+        } else if REGION_MARKERS.contains(&token) && !words.is_empty() && tokens[i + 1..].iter().all(is_marker) {
+            debug!("ignoring region marker {} of label {}", token, label);
+        } else if !RELEASE_MARKERS.contains(&token) {
+            words.push(token.to_string());
+        }
+
+        i += 1;
+    }
+
+    hints.title = words.join(" ");
+    hints
+}
+
+/// Suggests the TMDB movies and TV series that might be on a disc, ranked by confidence.
+///
+/// The volume label of the disc (or its name if it has none) is parsed with `parse_disc_label`. TV series
+/// are searched if the label contains a season, otherwise both movies and TV series are searched. If a
+/// search yields no results, it is retried with fallback queries: the title without words containing
+/// digits (e.g. `star wars` for `star wars ep4`), then with fewer words, and finally the disc name.
+///
+/// The candidates are ranked by the similarity of their titles (including alternative titles of movies)
+/// to the label, their release year if the label contains one, and how well their runtime matches the
+/// longest titles of the disc: the longest title for movies, the episodes of the season for TV series.
+///
+/// # Arguments
+///
+/// * `disc` - The `Disc` to find the movie or TV series of.
+/// * `lang` - The language of the searches, e.g. `de-DE`.
+/// * `client` - A reference to the `TmdbClient` used to search and fetch the candidates.
+///
+/// # Returns
+///
+/// * `Result<Vec<MatchSuggestion>>` - The suggestions ordered by descending confidence, empty if nothing was found.
+///
+/// # Errors
+///
+/// Returns an error if a search on TMDB fails. Candidates whose details cannot be fetched are ranked without their runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use makemkv_core::{read_disc_properties, suggest_disc_matches};
+/// # use std::sync::{Arc, Mutex};
+/// # use tmdb_client::TmdbClient;
+/// # async fn example(command: &str, mutex: &Arc<Mutex<()>>, client: &TmdbClient) -> anyhow::Result<()> {
+/// let disc = read_disc_properties(command, "/dev/sr0", mutex)?;
+/// let suggestions = suggest_disc_matches(&disc, "en-US", client).await?;
+///
+/// if let Some(best) = suggestions.first() {
+///     println!("{} ({:?}) with {:.0}% confidence", best.title, best.year, best.confidence * 100.);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn suggest_disc_matches(disc: &Disc, lang: &str, client: &TmdbClient) -> Result<Vec<MatchSuggestion>> {
+    let label = if disc.volume_name.is_empty() { &disc.name } else { &disc.volume_name };
+    let hints = parse_disc_label(label);
+    debug!("parsed disc label {} as {:?}", label, hints);
+
+    let mut queries = fallback_queries(&hints.title);
+    let disc_name = normalize(&disc.name);
+
+    if !disc_name.is_empty() && !queries.contains(&disc_name) {
+        queries.push(disc_name);
+    }
+
+    let mut suggestions = vec![];
+
+    if hints.season.is_none() {
+        for query in queries.iter() {
+            let results = client.search_movies(query, lang).await?.results;
+            if results.is_empty() {
+                continue;
+            }
+
+            for result in results.into_iter().take(MAX_CANDIDATES) {
+                let year = parse_year(&result.release_date);
+                let mut title_score = similarity(&hints.title, &result.title);
+                let mut runtime_score = None;
+
+                match client.get_movie(result.id, lang).await {
+                    Ok(movie) => {
+                        title_score = movie.titles().iter().map(|title| similarity(&hints.title, title)).fold(title_score, f32::max);
+                        runtime_score = movie.runtime.filter(|runtime| *runtime > 0).map(|runtime| movie_runtime_score(disc, runtime));
+                    }
+                    Err(e) => debug!("ranking movie {} without its details: {:?}", result.id, e),
+                }
+
+                suggestions.push(MatchSuggestion {
+                    media_type: MediaType::Movie,
+                    tmdb_id: result.id,
+                    title: result.title,
+                    year,
+                    poster_path: result.poster_path,
+                    season: None,
+                    disc: hints.disc,
+                    confidence: confidence(title_score, year_score(hints.year, year), runtime_score),
+                });
+            }
+
+            break;
+        }
+    }
+
+    for query in queries.iter() {
+        let results = client.search_tv_series(query, lang).await?.results;
+        if results.is_empty() {
+            continue;
+        }
+
+        for result in results.into_iter().take(MAX_CANDIDATES) {
+            let year = parse_year(&result.first_air_date);
+            let mut runtime_score = None;
+
+            if let Some(season) = hints.season {
+                match client.get_season_details(result.id, season, lang).await {
+                    Ok(season) => {
+                        let runtimes = season
+                            .episodes
+                            .iter()
+                            .filter_map(|episode| episode.runtime)
+                            .filter(|runtime| *runtime > 0)
+                            .collect::<Vec<u32>>();
+                        runtime_score = (!runtimes.is_empty()).then(|| episode_runtime_score(disc, &runtimes));
+                    }
+                    Err(e) => debug!("ranking tv series {} without season {}: {:?}", result.id, season, e),
+                }
+            }
+
+            suggestions.push(MatchSuggestion {
+                media_type: MediaType::TvShow,
+                tmdb_id: result.id,
+                confidence: confidence(similarity(&hints.title, &result.name), year_score(hints.year, year), runtime_score),
+                title: result.name,
+                year,
+                poster_path: result.poster_path,
+                season: hints.season,
+                disc: hints.disc,
+            });
+        }
+
+        break;
+    }
+
+    suggestions.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    Ok(suggestions)
+}
+
+/// Parses combined season and disc markers like `s2`, `s02` or `s2d3`.
+fn parse_season_disc(token: &str) -> Option<(u16, Option<u16>)> {
+    let rest = token.strip_prefix('s')?;
+    let (season, disc) = match rest.split_once('d') {
+        Some((season, disc)) => (season, Some(disc.parse::<u16>().ok()?)),
+        None => (rest, None),
+    };
+
+    Some((season.parse::<u16>().ok()?, disc))
+}
+
+/// Parses tokens consisting of one of the prefixes followed by a number, e.g. `disc3`.
+fn parse_prefixed_number(token: &str, prefixes: &[&str]) -> Option<u16> {
+    prefixes
+        .iter()
+        .find_map(|prefix| token.strip_prefix(prefix).filter(|rest| !rest.is_empty()).and_then(|rest| rest.parse::<u16>().ok()))
+}
+
+/// Returns the queries to search for a title, from the most to the least specific.
+fn fallback_queries(title: &str) -> Vec<String> {
+    let words = title.split(' ').filter(|word| !word.is_empty()).collect::<Vec<&str>>();
+    let mut queries: Vec<String> = vec![];
+
+    let mut push = |query: String| {
+        if !query.is_empty() && !queries.contains(&query) {
+            queries.push(query);
+        }
+    };
+
+    push(words.join(" "));
+
+    let without_numbers = words
+        .iter()
+        .filter(|word| !word.chars().any(|c| c.is_ascii_digit()))
+        .copied()
+        .collect::<Vec<&str>>();
+    push(without_numbers.join(" "));
+
+    for len in (1..without_numbers.len()).rev() {
+        push(without_numbers[..len].join(" "));
+    }
+
+    queries
+}
+
+/// Lowercases a title and replaces everything but letters and digits by single spaces.
+fn normalize(title: &str) -> String {
+    title
+        .to_lowercase()
+        .replace('&', " and ")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Returns the similarity of two titles from `0` to `1`, as the Dice coefficient of their character bigrams.
+///
+/// Leading articles are ignored, as volume labels often omit them.
+fn similarity(a: &str, b: &str) -> f32 {
+    fn bigrams(title: &str) -> Vec<(char, char)> {
+        let normalized = normalize(title);
+        let title = ["the ", "a ", "an ", "der ", "die ", "das "]
+            .iter()
+            .find_map(|article| normalized.strip_prefix(article))
+            .unwrap_or(&normalized);
+        let chars = title.chars().filter(|c| !c.is_whitespace()).collect::<Vec<char>>();
+        chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+    }
+
+    let (a, mut b) = (bigrams(a), bigrams(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.;
+    }
+
+    let total = a.len() + b.len();
+    let mut shared = 0;
+
+    for bigram in a.iter() {
+        if let Some(position) = b.iter().position(|other| other == bigram) {
+            b.swap_remove(position);
+            shared += 1;
+        }
+    }
+
+    (2 * shared) as f32 / total as f32
+}
+
+fn parse_year(date: &str) -> Option<u32> {
+    date.get(..4).and_then(|year| year.parse().ok())
+}
+
+/// Scores the release year of a candidate against the year of the label, `None` if either is unknown.
+fn year_score(expected: Option<u32>, actual: Option<u32>) -> Option<f32> {
+    match (expected?, actual?) {
+        (expected, actual) if expected == actual => Some(1.),
+        (expected, actual) if expected.abs_diff(actual) == 1 => Some(0.5),
+        _ => Some(0.),
+    }
+}
+
+/// Scores how close the longest title of the disc is to the runtime (in minutes) of a movie.
+fn movie_runtime_score(disc: &Disc, runtime: u32) -> f32 {
+    let Some(longest) = disc.titles.iter().map(|title| title.duration).max() else { return 0. };
+    let runtime = (runtime * 60) as f32;
+
+    (1. - (longest as f32 - runtime).abs() / (runtime * 0.25)).max(0.)
+}
+
+/// Scores the share of the longest titles of the disc that match the runtime (in minutes) of an episode within ±15%.
+fn episode_runtime_score(disc: &Disc, runtimes: &[u32]) -> f32 {
+    let mut durations = disc
+        .titles
+        .iter()
+        .map(|title| title.duration)
+        .filter(|duration| *duration >= 5 * 60)
+        .collect::<Vec<u32>>();
+    durations.sort_unstable_by(|a, b| b.cmp(a));
+    durations.truncate(runtimes.len().max(1));
+
+    if durations.is_empty() {
+        return 0.;
+    }
+
+    let matching = durations
+        .iter()
+        .filter(|duration| {
+            runtimes
+                .iter()
+                .any(|runtime| (**duration as f32 - (runtime * 60) as f32).abs() <= (runtime * 60) as f32 * 0.15)
+        })
+        .count();
+
+    matching as f32 / durations.len() as f32
+}
+
+/// Combines the scores to a confidence, weighting the title with 60%, the runtime with 30% and the year with 10%.
+///
+/// Unknown scores are left out and the weights of the known scores are scaled accordingly.
+fn confidence(title: f32, year: Option<f32>, runtime: Option<f32>) -> f32 {
+    let scores = [(title, 0.6), (runtime.unwrap_or(0.), if runtime.is_some() { 0.3 } else { 0. }), (year.unwrap_or(0.), if year.is_some() { 0.1 } else { 0. })];
+    let weights = scores.iter().map(|(_, weight)| weight).sum::<f32>();
+
+    scores.iter().map(|(score, weight)| score * weight).sum::<f32>() / weights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Title;
+    use tmdb_client::MockTmdbServer;
+
+    fn disc(volume_name: &str, durations: &[u32]) -> Disc {
+        let titles = durations
+            .iter()
+            .enumerate()
+            .map(|(id, duration)| Title { id, duration: duration * 60, ..Default::default() })
+            .collect();
+        Disc { volume_name: volume_name.to_string(), titles, ..Default::default() }
+    }
+
+    #[test]
+    fn parses_disc_labels() {
+        assert_eq!(parse_disc_label("STAR_WARS_EP4_D1"), LabelHints { title: "star wars ep4".to_string(), disc: Some(1), ..Default::default() });
+        assert_eq!(parse_disc_label("THE_OFFICE_S2_DISC3"), LabelHints { title: "the office".to_string(), season: Some(2), disc: Some(3), ..Default::default() });
+        assert_eq!(parse_disc_label("FLEABAG_SEASON_2_DISC_1"), LabelHints { title: "fleabag".to_string(), season: Some(2), disc: Some(1), ..Default::default() });
+        assert_eq!(parse_disc_label("BREAKING.BAD.S03D2"), LabelHints { title: "breaking bad".to_string(), season: Some(3), disc: Some(2), ..Default::default() });
+        assert_eq!(parse_disc_label("INCEPTION_2010_PAL_R2"), LabelHints { title: "inception".to_string(), year: Some(2010), ..Default::default() });
+        assert_eq!(parse_disc_label("1917").title, "1917");
+    }
+
+    #[test]
+    fn keeps_words_that_look_like_release_markers() {
+        assert_eq!(parse_disc_label("IT_2017"), LabelHints { title: "it".to_string(), year: Some(2017), ..Default::default() });
+        assert_eq!(parse_disc_label("THIS_IS_US_S1_D1"), LabelHints { title: "this is us".to_string(), season: Some(1), disc: Some(1), ..Default::default() });
+        assert_eq!(parse_disc_label("RAY").title, "ray");
+        assert_eq!(parse_disc_label("AMELIE_FR").title, "amelie");
+        assert_eq!(parse_disc_label("THE_DARK_KNIGHT_DE_BLU_RAY").title, "the dark knight");
+    }
+
+    #[test]
+    fn builds_fallback_queries() {
+        assert_eq!(fallback_queries("star wars ep4"), vec!["star wars ep4", "star wars", "star"]);
+        assert_eq!(fallback_queries("inception"), vec!["inception"]);
+    }
+
+    #[test]
+    fn scores_title_similarity() {
+        assert_eq!(similarity("the office", "The Office"), 1.);
+        assert_eq!(similarity("office", "The Office"), 1.);
+        assert!(similarity("star wars ep4", "Star Wars") > similarity("star wars ep4", "Star Trek"));
+        assert_eq!(similarity("", "Inception"), 0.);
+    }
+
+    #[tokio::test]
+    async fn suggests_movie_matching_label_and_runtime() {
+        let server = MockTmdbServer::start().await;
+        let disc = disc("INCEPTION_2010_D1", &[148, 12, 3]);
+
+        let suggestions = suggest_disc_matches(&disc, "en-US", &server.client()).await.unwrap();
+
+        assert_eq!(suggestions[0].media_type, MediaType::Movie);
+        assert_eq!(suggestions[0].tmdb_id, 27205);
+        assert_eq!(suggestions[0].disc, Some(1));
+        assert!(suggestions[0].confidence > 0.95);
+        assert!(suggestions
+            .iter()
+            .any(|suggestion| suggestion.media_type == MediaType::TvShow && suggestion.confidence < 0.5));
+    }
+
+    #[tokio::test]
+    async fn suggests_tv_series_for_season_labels() {
+        let server = MockTmdbServer::start().await;
+        let disc = disc("FLEABAG_S2_D1", &[25, 26, 25, 27, 26, 25, 154]);
+
+        let suggestions = suggest_disc_matches(&disc, "en-US", &server.client()).await.unwrap();
+
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!((suggestions[0].media_type, suggestions[0].tmdb_id, suggestions[0].season), (MediaType::TvShow, 67070, Some(2)));
+        assert!(suggestions[0].confidence > 0.9);
+        assert!(!server.requests().iter().any(|request| request.starts_with("/3/search/movie")));
+    }
+
+    #[tokio::test]
+    async fn retries_searches_with_fallback_queries() {
+        let server = MockTmdbServer::start().await;
+        server.respond_once("/search/tv", 200, r#"{"page":1,"total_results":0,"total_pages":0,"results":[]}"#);
+        let disc = disc("FLEABAG_EXTRAS_S2", &[26]);
+
+        let suggestions = suggest_disc_matches(&disc, "en-US", &server.client()).await.unwrap();
+
+        assert_eq!(suggestions[0].tmdb_id, 67070);
+        assert!(server.requests().iter().any(|request| request.contains("query=fleabag&")));
+    }
+}
@@ -46,6 +46,51 @@ pub struct VideoStream {
     pub order_weight: i8,
     pub mkv_flags: String,
     pub output_conversion_type: String,
+    pub hdr_format: HdrFormat,
+    pub dolby_vision: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HdrFormat {
+    #[default]
+    Sdr,
+    Hdr10,
+    Hdr10Plus,
+    Hlg,
+}
+
+impl VideoStream {
+    /// Returns whether the stream has a high dynamic range, either as HDR10, HDR10+, HLG or Dolby Vision.
+    pub fn is_hdr(&self) -> bool {
+        self.hdr_format != HdrFormat::Sdr || self.dolby_vision
+    }
+
+    /// Detects the HDR format of the stream from the names MakeMKV reports for it.
+    ///
+    /// MakeMKV mentions the transfer characteristics of UHD streams in the stream names
+    /// (e.g. `Mpeg-H HEVC Main10@L5.1 3840x2160 23.976 HDR10+`).
+    fn detect_hdr_format(&self) -> HdrFormat {
+        let names = [&self.codec_long, &self.tree_info, &self.panel_title, &self.mkv_flags].map(|name| name.to_uppercase());
+
+        if names.iter().any(|name| name.contains("HDR10+") || name.contains("HDR10 PLUS")) {
+            HdrFormat::Hdr10Plus
+        } else if names.iter().any(|name| name.contains("HDR10") || name.contains("SMPTE 2084")) {
+            // BT.2020 alone only describes the color primaries, which SDR streams can use as well
+            HdrFormat::Hdr10
+        } else if names.iter().any(|name| name.contains("HLG")) {
+            HdrFormat::Hlg
+        } else {
+            HdrFormat::Sdr
+        }
+    }
+
+    /// Returns whether the stream names mention Dolby Vision.
+    fn mentions_dolby_vision(&self) -> bool {
+        [&self.codec_long, &self.tree_info, &self.panel_title, &self.mkv_flags]
+            .iter()
+            .any(|name| name.to_lowercase().contains("dolby vision"))
+    }
 }
 
 #[derive(Debug, Default, Clone, Serialize)]
@@ -90,6 +135,23 @@ pub struct SubtitleStream {
     pub mkv_flags: String,
     pub mkv_flags_text: String,
     pub output_conversion_type: String,
+    pub forced: bool,
+}
+
+/// The stream flag MakeMKV sets for subtitle streams that only contain forced subtitles.
+const FORCED_SUBTITLES_FLAG: u32 = 4096;
+
+impl SubtitleStream {
+    /// Returns whether the stream only contains forced subtitles.
+    ///
+    /// A stream is considered forced if MakeMKV marks it with the forced subtitles stream flag,
+    /// or if its name or MKV flags contain "forced" (e.g. `PGS English (forced only)`).
+    pub fn is_forced(&self) -> bool {
+        let flags = self.stream_flags.parse::<u32>().unwrap_or(0);
+        let names = [&self.tree_info, &self.panel_title, &self.mkv_flags_text];
+
+        flags & FORCED_SUBTITLES_FLAG != 0 || names.iter().any(|name| name.to_lowercase().contains("forced"))
+    }
 }
 
 #[derive(Debug, Default, Clone, Serialize)]
@@ -105,8 +167,36 @@ pub struct Disc {
     pub titles: Vec<Title>,
 }
 
+impl Disc {
+    /// Returns a fingerprint identifying the disc, e.g. to cache data that was generated from it.
+    ///
+    /// The fingerprint is a FNV-1a hash of the disc names and the id, duration, size and segments of every
+    /// title, so it stays the same whenever the disc is inserted again, but differs between releases.
+    pub fn fingerprint(&self) -> String {
+        let mut hash: u64 = 0xcbf29ce484222325;
+
+        let mut update = |value: &str| {
+            for byte in value.bytes().chain(std::iter::once(0)) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        };
+
+        update(&self.name);
+        update(&self.volume_name);
+
+        for title in &self.titles {
+            update(&format!("{}:{}:{}:{}", title.id, title.duration, title.disk_size_bytes, title.segments_map));
+        }
+
+        format!("{:016x}", hash)
+    }
+}
+
 enum StreamType {
     Video,
+    /// An additional video stream of a title, e.g. the Dolby Vision enhancement layer of UHD discs.
+    SecondaryVideo,
     Audio,
     Subtitle,
 }
@@ -143,15 +233,15 @@ const SINFO_PREFIX: &str = "SINFO:";
 ///
 /// # Example
 ///
-/// ```rust
-/// use makemkv_core::{read_properties};
+/// ```no_run
+/// use makemkv_core::read_disc_properties;
 /// use std::sync::{Arc, Mutex};
 ///
 /// let command = "makemkvcon";
 /// let device = "/dev/sr0";
 /// let makemkv_mutex = Arc::new(Mutex::new(()));
 ///
-/// match read_properties(command, device, makemkv_mutex) {
+/// match read_disc_properties(command, device, &makemkv_mutex) {
 ///     Ok(disc) => println!("Disc properties: {:?}", disc),
 ///     Err(e) => eprintln!("Error reading properties: {}", e),
 /// }
@@ -161,8 +251,8 @@ pub fn read_disc_properties(command: &str, device: &str, makemkv_mutex: &Arc<Mut
 
     info!("reading properties for device: {} with command: {}", device, command);
 
-    let process = Command::new(command.to_owned())
-        .args(&["-r", "info", format!("dev:{}", device).as_str()])
+    let process = Command::new(command)
+        .args(["-r", "info", format!("dev:{}", device).as_str()])
         .stdout(Stdio::piped())
         .spawn()
         .context("failed to spawn disc properties process")?;
@@ -179,7 +269,7 @@ pub fn read_disc_properties(command: &str, device: &str, makemkv_mutex: &Arc<Mut
         let line = line.context("failed to read line")?;
         let columns = parse_csv_line(&line);
 
-        match columns.get(0).map(|s| s.as_str()) {
+        match columns.first().map(|s| s.as_str()) {
             Some(x) if x.starts_with(CINFO_PREFIX) => handle_cinfo(&mut disc, x, &columns).context("failed to handle cinfo")?,
             Some(x) if x.starts_with(TINFO_PREFIX) => handle_tinfo(&mut disc, x, &columns).context("failed to handle tinfo")?,
             Some(x) if x.starts_with(SINFO_PREFIX) => {
@@ -253,6 +343,9 @@ fn handle_sinfo(disc: &mut Disc, stream_type: &mut StreamType, audio_stream_id:
 
     if code == 1 {
         match value.as_str() {
+            "Video" if disc.titles.get(title_id).is_some_and(|title| !title.video_stream.stream_type.is_empty()) => {
+                *stream_type = StreamType::SecondaryVideo;
+            }
             "Video" => {
                 *stream_type = StreamType::Video;
                 *audio_stream_id = -1;
@@ -272,6 +365,7 @@ fn handle_sinfo(disc: &mut Disc, stream_type: &mut StreamType, audio_stream_id:
 
     match stream_type {
         StreamType::Video => handle_video_stream(&mut disc.titles[title_id].video_stream, code, value),
+        StreamType::SecondaryVideo => handle_secondary_video_stream(&mut disc.titles[title_id].video_stream, code, value),
         StreamType::Audio => handle_audio_stream(&mut disc.titles[title_id].audio_streams, *audio_stream_id as usize, code, value),
         StreamType::Subtitle => handle_subtitle_stream(&mut disc.titles[title_id].subtitle_streams, *subtitle_stream_id as usize, code, value),
     }
@@ -298,6 +392,29 @@ fn handle_video_stream(stream: &mut VideoStream, code: usize, value: String) ->
         _ => error!("unhandled video stream code: {}", code),
     }
 
+    stream.hdr_format = stream.detect_hdr_format();
+    stream.dolby_vision = stream.dolby_vision || stream.mentions_dolby_vision();
+
+    Ok(())
+}
+
+/// Handles information of an additional video stream of a title.
+///
+/// Dolby Vision enhancement layers of UHD discs are additional HEVC streams, but so are other secondary
+/// video streams (e.g. picture-in-picture commentaries), so the stream only marks the title as Dolby Vision
+/// if MakeMKV names it as Dolby Vision or enhancement layer. Blu-ray 3D discs carry their second view as
+/// MVC stream, which is ignored.
+fn handle_secondary_video_stream(stream: &mut VideoStream, code: usize, value: String) -> Result<()> {
+    let is_enhancement_layer = match code {
+        7 | 30 | 31 | 38 => {
+            let value = value.to_lowercase();
+            value.contains("dolby vision") || value.contains("enhancement layer")
+        }
+        _ => false,
+    };
+
+    stream.dolby_vision = stream.dolby_vision || is_enhancement_layer;
+
     Ok(())
 }
 
@@ -364,5 +481,61 @@ fn handle_subtitle_stream(streams: &mut Vec<SubtitleStream>, index: usize, code:
         _ => error!("unhandled subtitle stream code: {}", code),
     }
 
+    stream.forced = stream.is_forced();
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(codec_long: &str) -> VideoStream {
+        VideoStream { codec_long: codec_long.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn detects_hdr_formats_from_stream_names() {
+        assert_eq!(stream("Mpeg-H HEVC Main10@L5.1 3840x2160 23.976 HDR10+").detect_hdr_format(), HdrFormat::Hdr10Plus);
+        assert_eq!(stream("Mpeg-H HEVC Main10@L5.1 3840x2160 23.976 HDR10").detect_hdr_format(), HdrFormat::Hdr10);
+        assert_eq!(stream("Mpeg-H HEVC Main10 SMPTE 2084").detect_hdr_format(), HdrFormat::Hdr10);
+        assert_eq!(stream("Mpeg-H HEVC Main10 HLG").detect_hdr_format(), HdrFormat::Hlg);
+    }
+
+    #[test]
+    fn bt2020_alone_is_not_hdr() {
+        let stream = stream("Mpeg-H HEVC Main10@L5.1 3840x2160 23.976 BT.2020");
+
+        assert_eq!(stream.detect_hdr_format(), HdrFormat::Sdr);
+        assert!(!stream.is_hdr());
+    }
+
+    #[test]
+    fn dolby_vision_is_hdr() {
+        let stream = VideoStream { dolby_vision: true, ..Default::default() };
+
+        assert!(stream.is_hdr());
+    }
+
+    #[test]
+    fn secondary_hevc_stream_without_markers_is_not_dolby_vision() {
+        let mut stream = VideoStream::default();
+
+        handle_secondary_video_stream(&mut stream, 1, "Video".to_string()).unwrap();
+        handle_secondary_video_stream(&mut stream, 5, "V_MPEGH/ISO/HEVC".to_string()).unwrap();
+        handle_secondary_video_stream(&mut stream, 7, "Mpeg-H HEVC Main10@L5.1".to_string()).unwrap();
+        handle_secondary_video_stream(&mut stream, 19, "1920x1080".to_string()).unwrap();
+
+        assert!(!stream.dolby_vision);
+    }
+
+    #[test]
+    fn secondary_stream_with_enhancement_layer_marker_is_dolby_vision() {
+        let mut stream = VideoStream::default();
+
+        handle_secondary_video_stream(&mut stream, 5, "V_MPEGH/ISO/HEVC".to_string()).unwrap();
+        handle_secondary_video_stream(&mut stream, 30, "Dolby Vision enhancement layer".to_string()).unwrap();
+
+        assert!(stream.dolby_vision);
+    }
+}
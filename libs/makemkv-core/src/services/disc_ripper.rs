@@ -1,9 +1,12 @@
 use anyhow::{anyhow, Context, Result};
 use serde::Serialize;
+use std::fs;
 use std::io::{BufRead, BufReader};
 use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{mpsc::Sender, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 use tracing::info;
 
 use utils::{parse_csv_line, ProgressTracker};
@@ -31,7 +34,8 @@ const PRGV_PREFIX: &str = "PRGV:";
 /// * `command` - The command to run for ripping titles (e.g., `makemkvcon`).
 /// * `makemkv_mutex` - A mutex to ensure exclusive access to the MakeMKV process.
 /// * `cancel_flag` - An atomic boolean flag to signal cancellation of the ripping process.
-/// * `sender` - A channel sender for sending progress updates and completion notifications.
+/// * `sender` - A channel sender for sending progress updates and completion notifications. After each
+///   title is ripped completely, a `"ripped"` event with the step of the title is sent.
 /// * `output_dir` - The directory to output the ripped titles.
 /// * `device` - The device identifier for the disc (e.g., `/dev/sr0`).
 /// * `ids` - A vector of title IDs to rip.
@@ -50,7 +54,11 @@ const PRGV_PREFIX: &str = "PRGV:";
 ///
 /// # Examples
 ///
-/// ```
+/// ```no_run
+/// # use makemkv_core::rip_titles;
+/// # use std::sync::atomic::AtomicBool;
+/// # use std::sync::{Arc, Mutex};
+/// # fn main() -> anyhow::Result<()> {
 /// let command = "makemkvcon";
 /// let makemkv_mutex = Arc::new(Mutex::new(()));
 /// let cancel_flag = Arc::new(AtomicBool::new(false));
@@ -59,7 +67,9 @@ const PRGV_PREFIX: &str = "PRGV:";
 /// let device = "/dev/sr0";
 /// let ids = vec![1, 2, 3];
 ///
-/// rip_titles(command, &makemkv_mutex, cancel_flag, sender, output_dir, device, ids)?;
+/// rip_titles(command, &makemkv_mutex, cancel_flag, sender, output_dir, device, &ids)?;
+/// # Ok(())
+/// # }
 /// ```
 pub fn rip_titles(
     command: &str, makemkv_mutex: &Arc<Mutex<()>>, cancel_flag: Arc<AtomicBool>, sender: Sender<(&str, Option<ProgressPayload>)>, output_dir: &str, device: &str,
@@ -69,7 +79,7 @@ pub fn rip_titles(
 
     for (i, &id) in ids.iter().enumerate() {
         let mut process = Command::new(command)
-            .args(&["--messages=-stdout", "--progress=-same", "-r", "mkv", &format!("dev:{}", device), &id.to_string(), output_dir])
+            .args(["--messages=-stdout", "--progress=-same", "-r", "mkv", &format!("dev:{}", device), &id.to_string(), output_dir])
             .stdout(Stdio::piped())
             .spawn()
             .context("failed to spawn ripping process")?;
@@ -91,7 +101,7 @@ pub fn rip_titles(
             let line = line?;
             let columns = parse_csv_line(&line);
 
-            match columns.get(0).map(|s| s.as_str()) {
+            match columns.first().map(|s| s.as_str()) {
                 Some(x) if x.starts_with(PRGT_PREFIX) => {
                     current_step = columns.get(2).context("missing step title value")?.to_string();
                 }
@@ -117,9 +127,102 @@ pub fn rip_titles(
 
             sender.send(("progress", Some(payload))).unwrap();
         }
+
+        process.wait().context("failed to wait for ripping process")?;
+
+        let payload = ProgressPayload { step_title: current_step, step_details: current_step_details, progress: 1.0, eta: 0.0, step: i };
+        sender.send(("ripped", Some(payload))).unwrap();
     }
 
     sender.send(("done", None)).unwrap();
 
     Ok(())
 }
+
+/// Rips the beginning of a title from a disc device, e.g. to generate preview frames of it.
+///
+/// MakeMKV writes the title progressively, so the ripping process is stopped as soon as the output file
+/// reaches `max_bytes`. Short titles are ripped completely.
+///
+/// The process is killed mid-write, so the file ends with an incomplete cluster and has no cues. ffmpeg and
+/// ffprobe read such files up to the last complete frame, but seeking is done by scanning and the header may
+/// still report the duration of the complete title. Consumers should therefore derive the covered duration
+/// from the file size (see `generate_previews`) instead of probing it.
+///
+/// # Arguments
+///
+/// * `command` - The command to run for ripping the title (e.g., `makemkvcon`).
+/// * `makemkv_mutex` - A mutex to ensure exclusive access to the MakeMKV process.
+/// * `output_dir` - The directory to output the ripped title. It should not contain other Matroska files.
+/// * `device` - The device identifier for the disc (e.g., `/dev/sr0`).
+/// * `id` - The title ID to rip.
+/// * `max_bytes` - The size after which the ripping process is stopped.
+///
+/// # Returns
+///
+/// * `Result<String>` - The path of the ripped file if successful, otherwise an error indicating what went wrong.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The mutex cannot be locked.
+/// - The output directory cannot be created or read.
+/// - The ripping process cannot be spawned.
+/// - The ripping process didn't write a file.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use makemkv_core::rip_title_partially;
+/// # use std::sync::{Arc, Mutex};
+/// # fn main() -> anyhow::Result<()> {
+/// let makemkv_mutex = Arc::new(Mutex::new(()));
+/// let file = rip_title_partially("makemkvcon", &makemkv_mutex, "/path/to/previews/rip", "/dev/sr0", 2, 256 * 1024 * 1024)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn rip_title_partially(command: &str, makemkv_mutex: &Arc<Mutex<()>>, output_dir: &str, device: &str, id: usize, max_bytes: u64) -> Result<String> {
+    let _lock = makemkv_mutex.lock().map_err(|e| anyhow!("failed to lock makemkv_mutex: {}", e))?;
+
+    fs::create_dir_all(output_dir).context("failed to create partial rip directory")?;
+
+    info!("partially ripping title {} of device: {} into {}", id, device, output_dir);
+
+    let mut process = Command::new(command)
+        .args(["-r", "mkv", &format!("dev:{}", device), &id.to_string(), output_dir])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("failed to spawn ripping process")?;
+
+    loop {
+        if process.try_wait().context("failed to wait for ripping process")?.is_some() {
+            break;
+        }
+
+        if find_ripped_file(output_dir)?.is_some_and(|(_, size)| size >= max_bytes) {
+            process.kill().ok();
+            process.wait().ok();
+            break;
+        }
+
+        thread::sleep(Duration::from_millis(500));
+    }
+
+    let (file, _) = find_ripped_file(output_dir)?.context("ripping process didn't write a file")?;
+    Ok(file)
+}
+
+/// Returns the path and size of the Matroska file inside a directory, if there is one.
+fn find_ripped_file(dir: &str) -> Result<Option<(String, u64)>> {
+    let file = fs::read_dir(dir)
+        .context("failed to read partial rip directory")?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.extension().is_some_and(|extension| extension == "mkv"));
+
+    Ok(file.map(|path| {
+        let size = fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or(0);
+        (path.to_string_lossy().to_string(), size)
+    }))
+}
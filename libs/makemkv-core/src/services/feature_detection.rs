@@ -30,13 +30,17 @@ use crate::{Disc, Title};
 ///
 /// # Examples
 ///
-/// ```
-/// let disc = ...; // Disc with titles
+/// ```no_run
+/// # use makemkv_core::{filter_movie_main_features, Disc};
+/// # use tmdb_client::TmdbClient;
+/// # async fn example(disc: Disc) -> anyhow::Result<()> {
 /// let langs = vec!["deu", "eng"];
 /// let tmdb_id = 12345;
-/// let client = TmdbClient::new();
+/// let client = TmdbClient::new("your_api_key_here");
 ///
-/// let filtered_disc = filter_movie_candidates(disc, langs, tmdb_id, &client).await?;
+/// let filtered_disc = filter_movie_main_features(disc, &langs, tmdb_id, &client).await?;
+/// # Ok(())
+/// # }
 /// ```
 pub async fn filter_movie_main_features(disc: Disc, langs: &[&str], tmdb_id: u32, client: &TmdbClient) -> Result<Disc> {
     let movie = client.get_movie(tmdb_id, langs[0]).await.context("failed to fetch movie details")?;
@@ -82,13 +86,17 @@ pub async fn filter_movie_main_features(disc: Disc, langs: &[&str], tmdb_id: u32
 /// - Have at least one audio stream with a language code present in the provided `langs`.
 /// - Have a runtime within ±10% of the actual episode runtimes from TMDB for the specified season and episodes.
 ///
+/// If an episode group is given (e.g. the DVD order of the series), the season and episodes refer to
+/// the groups of the episode group and the positions of the episodes within them instead.
+///
 /// # Arguments
 ///
 /// * `disc` - The `Disc` object containing a list of titles to be filtered.
 /// * `langs` - A slice of language codes (`&[&str]`) to filter the audio streams.
-/// * `season` - The season number of the TV series to fetch details for.
+/// * `season` - The season number of the TV series to fetch details for, `0` for the specials.
 /// * `episodes` - A slice of episode numbers (`&[u16]`) to filter the runtimes.
 /// * `tmdb_id` - The TMDB ID of the TV series to fetch details for.
+/// * `episode_group` - The TMDB ID of the episode group the season and episodes refer to, if not the aired order.
 /// * `client` - A reference to the `TmdbClient` used to fetch TV series details.
 ///
 /// # Returns
@@ -101,27 +109,45 @@ pub async fn filter_movie_main_features(disc: Disc, langs: &[&str], tmdb_id: u32
 ///
 /// # Examples
 ///
-/// ```
-/// let disc = ...; // Disc with titles
+/// ```no_run
+/// # use makemkv_core::{filter_tv_series_main_features, Disc};
+/// # use tmdb_client::TmdbClient;
+/// # async fn example(disc: Disc) -> anyhow::Result<()> {
 /// let langs = ["deu", "eng"];
 /// let season = 1;
 /// let episodes = [1, 2, 3];
 /// let tmdb_id = 12345;
-/// let client = TmdbClient::new();
+/// let client = TmdbClient::new("your_api_key_here");
 ///
-/// let filtered_disc = filter_tv_series_candidates(disc, &langs, season, &episodes, tmdb_id, &client).await?;
+/// let filtered_disc = filter_tv_series_main_features(disc, &langs, season, &episodes, tmdb_id, None, &client).await?;
+/// # Ok(())
+/// # }
 /// ```
-pub async fn filter_tv_series_main_features(disc: Disc, langs: &[&str], season: u16, episodes: &[u16], tmdb_id: u32, client: &TmdbClient) -> Result<Disc> {
-    let tv_series = client.get_tv_series(tmdb_id, langs[0]).await.context("failed to fetch TV series details")?;
-
-    let episode_runtimes: Vec<f32> = tv_series
-        .seasons
-        .get((season - 1) as usize)
-        .context("season not found")?
-        .episodes
-        .iter()
-        .filter_map(|episode| if episodes.contains(&episode.episode_number) { Some((episode.runtime.unwrap_or(0) * 60) as f32) } else { None })
-        .collect();
+pub async fn filter_tv_series_main_features(
+    disc: Disc, langs: &[&str], season: u16, episodes: &[u16], tmdb_id: u32, episode_group: Option<&str>, client: &TmdbClient,
+) -> Result<Disc> {
+    let episode_runtimes: Vec<f32> = match episode_group {
+        Some(group_id) => client
+            .get_episode_group(group_id, langs[0])
+            .await
+            .context("failed to fetch episode group")?
+            .season(season)
+            .context("season not found in episode group")?
+            .episodes
+            .iter()
+            .filter_map(|episode| if episodes.contains(&episode.number()) { Some((episode.runtime.unwrap_or(0) * 60) as f32) } else { None })
+            .collect(),
+        None => client
+            .get_tv_series(tmdb_id, langs[0])
+            .await
+            .context("failed to fetch TV series details")?
+            .season(season)
+            .context("season not found")?
+            .episodes
+            .iter()
+            .filter_map(|episode| if episodes.contains(&episode.episode_number) { Some((episode.runtime.unwrap_or(0) * 60) as f32) } else { None })
+            .collect(),
+    };
 
     fn filter_titles(disc: &Disc, langs: &[&str], episode_runtimes: &[f32], threshold: f32) -> Vec<Title> {
         disc.titles
@@ -156,3 +182,79 @@ pub async fn filter_tv_series_main_features(disc: Disc, langs: &[&str], season:
 
     Ok(filtered_disc)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AudioStream;
+    use tmdb_client::MockTmdbServer;
+
+    fn title(id: usize, duration: u32, lang_code: &str) -> Title {
+        let audio_streams = vec![AudioStream { lang_code: lang_code.to_string(), ..Default::default() }];
+        Title { id, duration, audio_streams, ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn keeps_movie_titles_matching_runtime_and_language() {
+        let server = MockTmdbServer::start().await;
+        let disc = Disc { titles: vec![title(0, 148 * 60, "eng"), title(1, 12 * 60, "eng"), title(2, 150 * 60, "fra"), title(3, 141 * 60, "deu")], ..Default::default() };
+
+        let filtered = filter_movie_main_features(disc, &["eng", "deu"], 27205, &server.client()).await.unwrap();
+
+        assert_eq!(filtered.titles.iter().map(|title| title.id).collect::<Vec<usize>>(), vec![0, 3]);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_wider_runtime_threshold() {
+        let server = MockTmdbServer::start().await;
+        let disc = Disc { titles: vec![title(0, 100 * 60, "eng"), title(1, 12 * 60, "eng")], ..Default::default() };
+
+        let filtered = filter_movie_main_features(disc, &["eng"], 27205, &server.client()).await.unwrap();
+
+        assert_eq!(filtered.titles.iter().map(|title| title.id).collect::<Vec<usize>>(), vec![0]);
+    }
+
+    #[tokio::test]
+    async fn fails_for_unknown_movie() {
+        let server = MockTmdbServer::start().await;
+        let disc = Disc { titles: vec![title(0, 148 * 60, "eng")], ..Default::default() };
+
+        assert!(filter_movie_main_features(disc, &["eng"], 1, &server.client()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn keeps_tv_series_titles_matching_episode_runtimes() {
+        let server = MockTmdbServer::start().await;
+        let disc = Disc { titles: vec![title(0, 26 * 60, "eng"), title(1, 27 * 60, "eng"), title(2, 3 * 60, "eng"), title(3, 52 * 60, "eng")], ..Default::default() };
+
+        let filtered = filter_tv_series_main_features(disc, &["eng"], 2, &[1, 2], 67070, None, &server.client())
+            .await
+            .unwrap();
+
+        assert_eq!(filtered.titles.iter().map(|title| title.id).collect::<Vec<usize>>(), vec![0, 1]);
+    }
+
+    #[tokio::test]
+    async fn keeps_tv_series_specials() {
+        let server = MockTmdbServer::start().await;
+        let disc = Disc { titles: vec![title(0, 26 * 60, "eng"), title(1, 81 * 60, "eng")], ..Default::default() };
+
+        let filtered = filter_tv_series_main_features(disc, &["eng"], 0, &[1], 67070, None, &server.client())
+            .await
+            .unwrap();
+
+        assert_eq!(filtered.titles.iter().map(|title| title.id).collect::<Vec<usize>>(), vec![1]);
+    }
+
+    #[tokio::test]
+    async fn maps_titles_against_episode_group() {
+        let server = MockTmdbServer::start().await;
+        let disc = Disc { titles: vec![title(0, 26 * 60, "eng"), title(1, 81 * 60, "eng")], ..Default::default() };
+
+        let filtered = filter_tv_series_main_features(disc, &["eng"], 3, &[1], 67070, Some("5c8d1f5a0e0a26116a04c0d1"), &server.client())
+            .await
+            .unwrap();
+
+        assert_eq!(filtered.titles.iter().map(|title| title.id).collect::<Vec<usize>>(), vec![1]);
+    }
+}
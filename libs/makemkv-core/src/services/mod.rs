@@ -6,6 +6,7 @@ pub mod disc_reader;
 pub use disc_reader::read_disc_properties;
 pub use disc_reader::AudioStream;
 pub use disc_reader::Disc;
+pub use disc_reader::HdrFormat;
 pub use disc_reader::SubtitleStream;
 pub use disc_reader::Title;
 pub use disc_reader::VideoStream;
@@ -14,6 +15,14 @@ pub mod feature_detection;
 pub use feature_detection::filter_movie_main_features;
 pub use feature_detection::filter_tv_series_main_features;
 
+pub mod disc_matching;
+pub use disc_matching::parse_disc_label;
+pub use disc_matching::suggest_disc_matches;
+pub use disc_matching::LabelHints;
+pub use disc_matching::MatchSuggestion;
+pub use disc_matching::MediaType;
+
 pub mod disc_ripper;
+pub use disc_ripper::rip_title_partially;
 pub use disc_ripper::rip_titles;
 pub use disc_ripper::ProgressPayload;
@@ -29,7 +29,9 @@ impl ServarrClient {
     /// # Examples
     ///
     /// ```
-    /// let Servarr_client = ServarrClient::new("your_api_key_here", "http://localhost:7878");
+    /// use servarr_clients::ServarrClient;
+    ///
+    /// let servarr_client = ServarrClient::new("http://localhost:7878", "your_api_key_here");
     /// ```
     pub fn new(base_url: &str, api_key: &str) -> Self {
         Self { client: Client::new(), api_key: api_key.to_string(), base_url: base_url.to_string() }
@@ -53,8 +55,14 @@ impl ServarrClient {
     ///
     /// # Examples
     ///
-    /// ```
-    /// let response: Result<GenericSearchResponse<MovieSearchResult>, Error> = Servarr_client.request("/movie/lookup/tmdb?tmdbId=566525").await;
+    /// ```no_run
+    /// # use reqwest::Method;
+    /// # use servarr_clients::{Movie, ServarrClient};
+    /// # async fn example(servarr_client: ServarrClient) -> anyhow::Result<()> {
+    /// let url = servarr_client.build_url("api/v3/movie/lookup/tmdb?tmdbId=566525");
+    /// let movie: Movie = servarr_client.request(Method::GET, &url, None).await?;
+    /// # Ok(())
+    /// # }
     /// ```
     pub async fn request<T: DeserializeOwned>(&self, method: Method, url: &str, body: Option<String>) -> Result<T> {
         let mut builder = self.client.request(method, url);
@@ -106,7 +114,9 @@ impl ServarrClient {
     /// # Example
     ///
     /// ```
-    /// let client = MyClient { base_url: "https://api.example.com".to_string() };
+    /// use servarr_clients::ServarrClient;
+    ///
+    /// let client = ServarrClient::new("https://api.example.com", "your_api_key_here");
     /// let url = client.build_url("endpoint");
     /// assert_eq!(url, "https://api.example.com/endpoint");
     /// ```
@@ -139,11 +149,10 @@ impl ServarrClient {
     /// # Example
     ///
     /// ```
-    /// use std::collections::HashMap;
-    /// let client = MyClient { base_url: "https://api.example.com".to_string() };
-    /// let mut params = HashMap::new();
-    /// params.insert("key1", "value1");
-    /// params.insert("key2", "value2");
+    /// use servarr_clients::ServarrClient;
+    ///
+    /// let client = ServarrClient::new("https://api.example.com", "your_api_key_here");
+    /// let params = [("key1", "value1"), ("key2", "value2")];
     ///
     /// let url = client.build_url_with_params("endpoint", &params).unwrap();
     /// assert_eq!(url.as_str(), "https://api.example.com/endpoint?key1=value1&key2=value2");
@@ -83,6 +83,23 @@ impl RadarrClient {
         Ok(())
     }
 
+    /// Initiates a scan for a movie in Radarr without renaming its files.
+    ///
+    /// This function is used for movies with multiple versions, whose file names have to be kept
+    /// so Jellyfin groups them as versions of the same movie.
+    ///
+    /// # Arguments
+    ///
+    /// * `movie_id` - The ID of the movie to scan.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An empty result on success, or an error if the request fails.
+    pub async fn rescan_movie(&self, movie_id: u32) -> Result<()> {
+        let scan_command_id = self.scan_movie(movie_id).await?;
+        self.client.await_command_completion(scan_command_id).await
+    }
+
     /// Retrieves movie information from Radarr based on TMDB ID.
     ///
     /// This function sends a request to fetch movie details from Radarr using the provided TMDB ID.
@@ -86,6 +86,23 @@ impl SonarrClient {
         Ok(())
     }
 
+    /// Initiates a scan for a TV show in Sonarr without renaming its files.
+    ///
+    /// This function is used for episodes with multiple versions, whose file names have to be kept
+    /// so Jellyfin groups them as versions of the same episode.
+    ///
+    /// # Arguments
+    ///
+    /// * `series_id` - The ID of the TV show to scan.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An empty result on success, or an error if the request fails.
+    pub async fn rescan_tv_show(&self, series_id: u32) -> Result<()> {
+        let scan_command_id = self.scan_tv_show(series_id).await?;
+        self.client.await_command_completion(scan_command_id).await
+    }
+
     /// Retrieves TV show information from Sonarr based on TVDB ID.
     ///
     /// This function sends a request to fetch TV show details from Sonarr using the provided TVDB ID.
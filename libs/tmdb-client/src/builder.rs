@@ -0,0 +1,147 @@
+use reqwest::{Client, Url};
+use std::time::Duration;
+
+use crate::{CacheOptions, ImageOptions, RateLimitOptions, RateLimiter, TmdbCache, TmdbClient, TMDB_BASE_URL};
+use crate::{Result, TmdbError};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_USER_AGENT: &str = concat!("ripper/", env!("CARGO_PKG_VERSION"));
+
+/// A builder for `TmdbClient`, e.g. to point the client at a proxy, a caching mirror or a local mock server.
+#[derive(Debug, Clone)]
+pub struct TmdbClientBuilder {
+    api_key: String,
+    base_url: String,
+    timeout: Duration,
+    user_agent: String,
+    client: Option<Client>,
+    cache: Option<CacheOptions>,
+    rate_limit: RateLimitOptions,
+    images: ImageOptions,
+}
+
+impl TmdbClientBuilder {
+    /// Creates a new `TmdbClientBuilder` with the provided API key and the default settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `api_key` - The API key for authenticating with the TMDB API.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of `TmdbClientBuilder`.
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            base_url: TMDB_BASE_URL.to_string(),
+            timeout: DEFAULT_TIMEOUT,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            client: None,
+            cache: None,
+            rate_limit: RateLimitOptions::default(),
+            images: ImageOptions::default(),
+        }
+    }
+
+    /// Sets the base URL the API endpoints are appended to (defaults to `https://api.themoviedb.org/3`).
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sets the timeout of a single request (defaults to 30 seconds). Ignored if a custom client is used.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the user agent sent with every request. Ignored if a custom client is used.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Uses a custom `reqwest::Client`, e.g. to share a connection pool or configure a proxy.
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Caches the responses in memory and, if a directory is set, on disk (disabled by default).
+    pub fn cache(mut self, options: CacheOptions) -> Self {
+        self.cache = Some(options);
+        self
+    }
+
+    /// Sets the rate limit and retry behaviour (defaults to 40 requests per second and 3 retries).
+    pub fn rate_limit(mut self, options: RateLimitOptions) -> Self {
+        self.rate_limit = options;
+        self
+    }
+
+    /// Sets the sizes artwork is downloaded in and the directory images are stored in (defaults to not storing them).
+    pub fn images(mut self, options: ImageOptions) -> Self {
+        self.images = options;
+        self
+    }
+
+    /// Builds the `TmdbClient`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the configured `TmdbClient`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the base URL is not a valid URL, the HTTP client cannot be created, or the cache
+    /// directory cannot be created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use tmdb_client::{TmdbClient, TmdbError};
+    /// # fn main() -> Result<(), TmdbError> {
+    /// let tmdb_client = TmdbClient::builder("your_api_key_here")
+    ///     .base_url("http://localhost:8080/3")
+    ///     .timeout(Duration::from_secs(10))
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn build(self) -> Result<TmdbClient> {
+        Url::parse(&self.base_url).map_err(|e| TmdbError::invalid_input(format!("could not parse base URL: {}", e)))?;
+
+        let client = match self.client {
+            Some(client) => client,
+            None => Client::builder()
+                .timeout(self.timeout)
+                .user_agent(self.user_agent)
+                .build()
+                .map_err(|e| TmdbError::network("could not create HTTP client", e))?,
+        };
+
+        let cache = match self.cache {
+            Some(options) if options.enabled => Some(TmdbCache::new(options)?),
+            _ => None,
+        };
+
+        Ok(TmdbClient { client, api_key: self.api_key, base_url: self.base_url, cache, rate_limiter: RateLimiter::new(self.rate_limit), images: self.images })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TmdbClient;
+
+    #[test]
+    fn builder_trims_trailing_slash_of_base_url() {
+        let client = TmdbClient::builder("key").base_url("http://localhost:8080/3/").build().unwrap();
+        assert_eq!(client.base_url(), "http://localhost:8080/3");
+    }
+
+    #[test]
+    fn builder_rejects_invalid_base_url() {
+        assert!(TmdbClient::builder("key").base_url("not a url").build().is_err());
+    }
+}
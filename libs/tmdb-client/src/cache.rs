@@ -0,0 +1,377 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, error, info};
+
+use crate::{Result, TmdbError};
+
+/// The endpoints of the TMDB API, which are cached for different durations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Endpoint {
+    Search,
+    Movie,
+    TvSeries,
+    Season,
+    EpisodeGroup,
+    Find,
+    Configuration,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CacheOptions {
+    pub enabled: bool,
+    /// The directory the responses are persisted to, so they survive restarts. Only cached in memory if unset.
+    pub dir: Option<String>,
+    /// The time to live of search results, in seconds.
+    pub search_ttl: u64,
+    /// The time to live of movie details, in seconds.
+    pub movie_ttl: u64,
+    /// The time to live of TV series details, in seconds.
+    pub tv_series_ttl: u64,
+    /// The time to live of season details, in seconds.
+    pub season_ttl: u64,
+    /// The maximum number of responses kept in memory, the least recently fetched ones are evicted first.
+    pub max_entries: usize,
+    /// The age in seconds after which responses are removed instead of revalidated, in memory and in the directory.
+    pub max_age: u64,
+}
+
+impl Default for CacheOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: None,
+            search_ttl: 60 * 60,
+            movie_ttl: 24 * 60 * 60,
+            tv_series_ttl: 24 * 60 * 60,
+            season_ttl: 24 * 60 * 60,
+            max_entries: 1000,
+            max_age: 30 * 24 * 60 * 60,
+        }
+    }
+}
+
+impl CacheOptions {
+    /// Returns the time to live of the responses of an endpoint.
+    pub fn ttl(&self, endpoint: Endpoint) -> Duration {
+        Duration::from_secs(match endpoint {
+            Endpoint::Search => self.search_ttl,
+            Endpoint::Movie | Endpoint::Find | Endpoint::Configuration => self.movie_ttl,
+            Endpoint::TvSeries | Endpoint::EpisodeGroup => self.tv_series_ttl,
+            Endpoint::Season => self.season_ttl,
+        })
+    }
+}
+
+/// A cached response of the TMDB API, together with the validators to revalidate it once it expired.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    pub body: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// The unix timestamp (in seconds) the response was fetched or last revalidated at.
+    pub fetched_at: u64,
+}
+
+impl CacheEntry {
+    /// Creates a new entry for a response that was fetched just now.
+    pub fn new(url: &str, body: String, etag: Option<String>, last_modified: Option<String>) -> Self {
+        Self { url: url.to_string(), body, etag, last_modified, fetched_at: now() }
+    }
+
+    /// Checks whether the entry is younger than the given time to live.
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        now().saturating_sub(self.fetched_at) < ttl.as_secs()
+    }
+
+    /// Marks the entry as fetched just now, e.g. after the server confirmed it is unchanged.
+    pub fn touch(&mut self) {
+        self.fetched_at = now();
+    }
+}
+
+/// An in-memory cache of TMDB responses by URL, optionally persisted to a directory as one JSON file per response.
+///
+/// The cache holds at most `max_entries` responses in memory and forgets responses older than `max_age`, so
+/// neither the memory nor the directory grow without bounds. The directory is accessed with `tokio::fs`, so
+/// lookups don't block the async executor.
+#[derive(Debug, Clone)]
+pub struct TmdbCache {
+    options: CacheOptions,
+    entries: Arc<Mutex<HashMap<String, CacheEntry>>>,
+}
+
+impl TmdbCache {
+    /// Creates a new `TmdbCache`, creating its directory if necessary and removing the responses in it that are
+    /// older than `max_age`.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `CacheOptions` containing the directory, the time to live per endpoint and the limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache directory cannot be created.
+    pub fn new(options: CacheOptions) -> Result<Self> {
+        if let Some(dir) = &options.dir {
+            std::fs::create_dir_all(dir).map_err(|e| TmdbError::io("could not create cache directory", e))?;
+
+            let pruned = prune_files(Path::new(dir), Duration::from_secs(options.max_age));
+            if pruned > 0 {
+                info!("removed {} stale responses from cache directory {}", pruned, dir);
+            }
+        }
+
+        Ok(Self { options, entries: Arc::new(Mutex::new(HashMap::new())) })
+    }
+
+    /// Returns the time to live of the responses of an endpoint.
+    pub fn ttl(&self, endpoint: Endpoint) -> Duration {
+        self.options.ttl(endpoint)
+    }
+
+    /// Returns the cached response of a URL, loading it from the cache directory if it is not in memory.
+    ///
+    /// Responses older than `max_age` are not returned, so they are fetched again instead of revalidated.
+    pub async fn get(&self, url: &str) -> Option<CacheEntry> {
+        let max_age = Duration::from_secs(self.options.max_age);
+
+        if let Some(entry) = self.entries.lock().unwrap().get(url) {
+            return Some(entry.clone()).filter(|entry| entry.is_fresh(max_age));
+        }
+
+        let file = self.entry_file(url)?;
+        let entry = serde_json::from_str::<CacheEntry>(&tokio::fs::read_to_string(file).await.ok()?).ok()?;
+
+        // different URLs might share a file name, the entry only belongs to the URL it was fetched from
+        if entry.url != url || !entry.is_fresh(max_age) {
+            return None;
+        }
+
+        self.remember(entry.clone());
+        Some(entry)
+    }
+
+    /// Stores the response of a URL in memory and in the cache directory.
+    pub async fn insert(&self, entry: CacheEntry) {
+        if let Some(file) = self.entry_file(&entry.url) {
+            if let Err(e) = write_entry(&file, &entry).await {
+                error!("failed to persist cached response of {}: {:?}", entry.url, e);
+            }
+        }
+
+        debug!("caching response of {}", entry.url);
+        self.remember(entry);
+    }
+
+    /// Removes all cached responses from memory and the cache directory.
+    ///
+    /// # Returns
+    ///
+    /// The number of cached responses that were removed.
+    pub async fn flush(&self) -> usize {
+        let flushed = {
+            let mut entries = self.entries.lock().unwrap();
+            let flushed = entries.len();
+            entries.clear();
+            flushed
+        };
+
+        let Some(dir) = &self.options.dir else { return flushed };
+        let Ok(mut files) = tokio::fs::read_dir(dir).await else { return flushed };
+
+        // every entry in memory is persisted as well, so the files are the complete cache
+        let mut flushed = 0;
+
+        while let Ok(Some(file)) = files.next_entry().await {
+            let file = file.path();
+            if file.extension().is_some_and(|extension| extension == "json") && tokio::fs::remove_file(&file).await.is_ok() {
+                flushed += 1;
+            }
+        }
+
+        flushed
+    }
+
+    /// Keeps a response in memory, evicting responses older than `max_age` and then the least recently fetched
+    /// responses if the cache holds more than `max_entries`.
+    fn remember(&self, entry: CacheEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(entry.url.clone(), entry);
+
+        if entries.len() <= self.options.max_entries {
+            return;
+        }
+
+        let max_age = Duration::from_secs(self.options.max_age);
+        entries.retain(|_, entry| entry.is_fresh(max_age));
+
+        while entries.len() > self.options.max_entries {
+            let Some(oldest) = entries.values().min_by_key(|entry| entry.fetched_at).map(|entry| entry.url.clone()) else { break };
+            entries.remove(&oldest);
+        }
+    }
+
+    /// Returns the file a URL is cached in, named after the FNV-1a hash of the URL.
+    fn entry_file(&self, url: &str) -> Option<PathBuf> {
+        let mut hash: u64 = 0xcbf29ce484222325;
+
+        for byte in url.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+
+        self.options.dir.as_ref().map(|dir| Path::new(dir).join(format!("{:016x}.json", hash)))
+    }
+}
+
+/// Writes an entry to a partial file first and renames it afterwards, so readers never see incomplete files.
+async fn write_entry(file: &Path, entry: &CacheEntry) -> std::io::Result<()> {
+    let json = serde_json::to_string(entry)?;
+    let partial_file = file.with_extension("partial");
+
+    tokio::fs::write(&partial_file, json).await?;
+    tokio::fs::rename(&partial_file, file).await
+}
+
+/// Removes the responses of a cache directory that weren't written within `max_age`, as well as partial files
+/// left behind by interrupted writes.
+///
+/// # Returns
+///
+/// The number of removed files.
+fn prune_files(dir: &Path, max_age: Duration) -> usize {
+    let Ok(files) = std::fs::read_dir(dir) else { return 0 };
+
+    files
+        .filter_map(|file| file.ok())
+        .filter(|file| {
+            let path = file.path();
+            let stale = file
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified.elapsed().unwrap_or_default() >= max_age);
+
+            path.extension().is_some_and(|extension| extension == "partial") || (path.extension().is_some_and(|extension| extension == "json") && stale)
+        })
+        .filter(|file| std::fs::remove_file(file.path()).is_ok())
+        .count()
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock, MockTmdbServer, TmdbClient};
+
+    fn cache_options(name: &str, ttl: u64) -> CacheOptions {
+        let dir = std::env::temp_dir().join(format!("tmdb-cache-{}-{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+
+        CacheOptions { dir: Some(dir.to_string_lossy().to_string()), search_ttl: ttl, movie_ttl: ttl, tv_series_ttl: ttl, season_ttl: ttl, ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn serves_fresh_responses_from_cache() {
+        let server = MockTmdbServer::start().await;
+        let client = TmdbClient::builder(mock::MOCK_API_KEY)
+            .base_url(&server.base_url())
+            .cache(cache_options("fresh", 60))
+            .build()
+            .unwrap();
+
+        client.get_tv_series(67070, "en-US").await.unwrap();
+        let tv_series = client.get_tv_series(67070, "en-US").await.unwrap();
+
+        assert_eq!(tv_series.seasons.len(), 3);
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn revalidates_expired_responses() {
+        let server = MockTmdbServer::start().await;
+        let client = TmdbClient::builder(mock::MOCK_API_KEY)
+            .base_url(&server.base_url())
+            .cache(cache_options("expired", 0))
+            .build()
+            .unwrap();
+
+        client.get_movie(27205, "en-US").await.unwrap();
+        let movie = client.get_movie(27205, "en-US").await.unwrap();
+
+        assert_eq!(movie.title, "Inception");
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn persists_responses_and_flushes_them() {
+        let server = MockTmdbServer::start().await;
+        let options = cache_options("persisted", 60);
+        let build = || {
+            TmdbClient::builder(mock::MOCK_API_KEY)
+                .base_url(&server.base_url())
+                .cache(options.clone())
+                .build()
+                .unwrap()
+        };
+
+        build().get_movie(27205, "en-US").await.unwrap();
+        build().get_movie(27205, "en-US").await.unwrap();
+        assert_eq!(server.requests().len(), 1);
+
+        let client = build();
+        assert_eq!(client.flush_cache().await, 1);
+
+        client.get_movie(27205, "en-US").await.unwrap();
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    fn entry(url: &str, age: u64) -> CacheEntry {
+        CacheEntry { fetched_at: now() - age, ..CacheEntry::new(url, "{}".to_string(), None, None) }
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_fetched_responses() {
+        let cache = TmdbCache::new(CacheOptions { max_entries: 2, ..Default::default() }).unwrap();
+
+        for (url, age) in [("/movie/1", 2), ("/movie/2", 1), ("/movie/3", 0)] {
+            cache.insert(entry(url, age)).await;
+        }
+
+        assert!(cache.get("/movie/1").await.is_none());
+        assert!(cache.get("/movie/2").await.is_some());
+        assert!(cache.get("/movie/3").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn forgets_responses_older_than_max_age() {
+        let cache = TmdbCache::new(CacheOptions { max_age: 60, ..Default::default() }).unwrap();
+        cache.insert(entry("/movie/1", 120)).await;
+
+        assert!(cache.get("/movie/1").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn prunes_stale_files_on_startup() {
+        let options = cache_options("pruned", 60);
+        let dir = options.dir.clone().unwrap();
+        let file_count = || std::fs::read_dir(&dir).unwrap().count();
+
+        TmdbCache::new(options.clone()).unwrap().insert(entry("/movie/1", 0)).await;
+        std::fs::write(Path::new(&dir).join("0000000000000000.partial"), "{").unwrap();
+        assert_eq!(file_count(), 2);
+
+        TmdbCache::new(options.clone()).unwrap();
+        assert_eq!(file_count(), 1);
+
+        TmdbCache::new(CacheOptions { max_age: 0, ..options }).unwrap();
+        assert_eq!(file_count(), 0);
+    }
+}
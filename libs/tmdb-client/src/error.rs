@@ -0,0 +1,102 @@
+use std::fmt;
+use std::time::Duration;
+
+/// The maximum number of characters of a response body kept in a `TmdbError::Decode`.
+const BODY_SNIPPET_LENGTH: usize = 200;
+
+/// A `Result` whose error is a `TmdbError`.
+pub type Result<T> = std::result::Result<T, TmdbError>;
+
+/// An error of the TMDB client, telling apart unknown media, invalid API keys, rate limits and
+/// unavailable or misbehaving TMDB servers.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tmdb_client::{TmdbClient, TmdbError};
+/// # async fn example(tmdb_client: TmdbClient) {
+/// match tmdb_client.get_movie(27205, "en-US").await {
+///     Err(TmdbError::NotFound { .. }) => println!("unknown movie"),
+///     Err(TmdbError::Network { .. } | TmdbError::Status { .. }) => println!("TMDB is unavailable"),
+///     result => println!("{:?}", result),
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum TmdbError {
+    /// The API key is missing or invalid (`401`).
+    Unauthorized { message: String },
+    /// The requested resource doesn't exist (`404`), or no media was found for an external ID.
+    NotFound { message: String },
+    /// The rate limit was still exceeded after all retries (`429`).
+    RateLimited { retry_after: Option<Duration> },
+    /// Any other unsuccessful status, e.g. a server error that persisted after all retries.
+    Status { status: u16, message: String },
+    /// The request could not be sent or its response could not be read, e.g. because TMDB is unreachable.
+    Network { message: String },
+    /// The response could not be deserialized, with the beginning of the response body.
+    Decode { message: String, body: String },
+    /// The arguments don't form a valid request, e.g. an invalid URL or image path, or missing IDs.
+    InvalidInput { message: String },
+    /// Reading or writing a file of the cache or the image directory failed.
+    Io { message: String },
+}
+
+impl TmdbError {
+    /// Creates the error of an unsuccessful status, using the `status_message` of the TMDB error body if present.
+    pub fn from_status(status: u16, body: &str, retry_after: Option<Duration>) -> Self {
+        let message = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|body| body.get("status_message").and_then(|message| message.as_str()).map(|message| message.to_string()))
+            .unwrap_or_default();
+
+        match status {
+            401 => Self::Unauthorized { message },
+            404 => Self::NotFound { message },
+            429 => Self::RateLimited { retry_after },
+            status => Self::Status { status, message },
+        }
+    }
+
+    /// Creates the error of a response body that could not be deserialized, keeping its beginning.
+    pub fn decode(error: serde_json::Error, body: &str) -> Self {
+        let body = match body.char_indices().nth(BODY_SNIPPET_LENGTH) {
+            Some((end, _)) => format!("{}...", &body[..end]),
+            None => body.to_string(),
+        };
+
+        Self::Decode { message: error.to_string(), body }
+    }
+
+    /// Creates the error of a request that could not be sent or whose response could not be read.
+    pub fn network(context: &str, error: reqwest::Error) -> Self {
+        Self::Network { message: format!("{}: {}", context, error) }
+    }
+
+    /// Creates the error of invalid arguments.
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::InvalidInput { message: message.into() }
+    }
+
+    /// Creates the error of a failed file system operation.
+    pub fn io(context: &str, error: std::io::Error) -> Self {
+        Self::Io { message: format!("{}: {}", context, error) }
+    }
+}
+
+impl fmt::Display for TmdbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unauthorized { message } => write!(f, "unauthorized by TMDB (401): {}", message),
+            Self::NotFound { message } => write!(f, "not found on TMDB (404): {}", message),
+            Self::RateLimited { retry_after } => write!(f, "rate limited by TMDB (429), retry after {:?}", retry_after),
+            Self::Status { status, message } => write!(f, "TMDB responded with {}: {}", status, message),
+            Self::Network { message } => write!(f, "could not reach TMDB: {}", message),
+            Self::Decode { message, body } => write!(f, "could not deserialize TMDB response: {} (body: {})", message, body),
+            Self::InvalidInput { message } => write!(f, "invalid TMDB request: {}", message),
+            Self::Io { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for TmdbError {}
@@ -0,0 +1,135 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::{Result, TmdbError};
+
+/// The kinds of artwork TMDB provides, which are downloaded in different sizes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageKind {
+    /// The poster of a movie, TV series or season (`poster_path`).
+    Poster,
+    /// The backdrop of a movie or TV series (`backdrop_path`).
+    Backdrop,
+    /// The still of an episode (`still_path`).
+    Still,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ImageOptions {
+    /// The directory downloaded images are stored in, so each image is only downloaded once. Not stored if unset.
+    pub dir: Option<String>,
+    /// The size posters are downloaded in, one of the `poster_sizes` of the TMDB configuration.
+    pub poster_size: String,
+    /// The size backdrops are downloaded in, one of the `backdrop_sizes` of the TMDB configuration.
+    pub backdrop_size: String,
+    /// The size stills are downloaded in, one of the `still_sizes` of the TMDB configuration.
+    pub still_size: String,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self { dir: None, poster_size: "w500".to_string(), backdrop_size: "w1280".to_string(), still_size: "w300".to_string() }
+    }
+}
+
+impl ImageOptions {
+    /// Returns the size artwork of a kind is downloaded in.
+    pub fn size(&self, kind: ImageKind) -> &str {
+        match kind {
+            ImageKind::Poster => &self.poster_size,
+            ImageKind::Backdrop => &self.backdrop_size,
+            ImageKind::Still => &self.still_size,
+        }
+    }
+
+    /// Returns the file an image is stored in, i.e. `<dir>/<size>/<file name>`, or `None` if images are not stored.
+    pub fn image_file(&self, path: &str, size: &str) -> Result<Option<PathBuf>> {
+        let file_name = image_file_name(path)?;
+        validate_segment(size)?;
+
+        Ok(self.dir.as_ref().map(|dir| Path::new(dir).join(size).join(file_name)))
+    }
+}
+
+/// Returns the file name of an image path like `/8ZTVqvKDQ8emSGUEMjsS4yHAwrp.jpg`.
+///
+/// # Errors
+///
+/// Returns an error if the path is not a single file name, e.g. `/../config.json`, so paths provided by
+/// users can't escape the image directory.
+pub fn image_file_name(path: &str) -> Result<&str> {
+    let file_name = path.strip_prefix('/').unwrap_or(path);
+    validate_segment(file_name)?;
+    Ok(file_name)
+}
+
+fn validate_segment(segment: &str) -> Result<()> {
+    let valid = !segment.is_empty() && !segment.starts_with('.') && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-');
+
+    if !valid {
+        return Err(TmdbError::invalid_input(format!("invalid image path segment: {}", segment)));
+    }
+
+    Ok(())
+}
+
+/// Writes an image to a file, creating its directory and replacing the file atomically.
+pub fn write_image(file: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| TmdbError::io("could not create image directory", e))?;
+    }
+
+    let partial_file = file.with_extension("partial");
+    std::fs::write(&partial_file, bytes).map_err(|e| TmdbError::io("could not write image", e))?;
+    std::fs::rename(&partial_file, file).map_err(|e| TmdbError::io("could not write image", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{mock, ImageKind, ImageOptions, MockTmdbServer, TmdbClient};
+
+    #[tokio::test]
+    async fn downloads_and_stores_images() {
+        let server = MockTmdbServer::start().await;
+        let dir = std::env::temp_dir().join(format!("tmdb_images_{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+
+        let options = ImageOptions { dir: Some(dir.to_string_lossy().to_string()), ..Default::default() };
+        let client = TmdbClient::builder(mock::MOCK_API_KEY)
+            .base_url(&server.base_url())
+            .images(options)
+            .build()
+            .unwrap();
+
+        let url = client.image_url("/oYuLEt3zVCKq57qu2F8dT7NIa6f.jpg", "w500").await.unwrap();
+        assert_eq!(url, format!("{}/t/p/w500/oYuLEt3zVCKq57qu2F8dT7NIa6f.jpg", server.base_url().trim_end_matches("/3")));
+
+        let image = client.get_image("/oYuLEt3zVCKq57qu2F8dT7NIa6f.jpg", "w500").await.unwrap();
+        client.get_image("/oYuLEt3zVCKq57qu2F8dT7NIa6f.jpg", "w500").await.unwrap();
+
+        assert_eq!(image, b"mock image w500/oYuLEt3zVCKq57qu2F8dT7NIa6f.jpg");
+        assert!(dir.join("w500").join("oYuLEt3zVCKq57qu2F8dT7NIa6f.jpg").exists());
+        assert_eq!(server.requests().iter().filter(|request| request.starts_with("/t/p/")).count(), 1);
+
+        let artwork = dir.join("media").join("fanart.jpg");
+        client
+            .save_artwork("/8ZTVqvKDQ8emSGUEMjsS4yHAwrp.jpg", ImageKind::Backdrop, &artwork)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&artwork).unwrap(), b"mock image w1280/8ZTVqvKDQ8emSGUEMjsS4yHAwrp.jpg");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_image_paths_and_sizes() {
+        let server = MockTmdbServer::start().await;
+        let client = server.client();
+
+        assert!(client.get_image("/../config.json", "w500").await.is_err());
+        assert!(client.get_image("/poster.jpg", "../w500").await.is_err());
+        assert!(client.get_image("/poster.jpg", "w501").await.is_err());
+        assert!(client.get_image("/poster.jpg", "original").await.is_ok());
+    }
+}
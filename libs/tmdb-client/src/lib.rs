@@ -1,17 +1,43 @@
-use anyhow::{Context, Result};
-use futures::future::try_join_all;
-use reqwest::{Client, Url};
-use tracing::info;
+use futures::future::join_all;
+use futures::stream::{self, Stream};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER};
+use reqwest::{Client, Response, StatusCode, Url};
+use std::time::Duration;
+use tracing::{debug, info, warn};
 
-pub use models::{Episode, Movie, TvSeason, TvSeries};
-pub use models::{GenericSearchResponse, MovieSearchResult, TvSeriesSearchResult};
+pub use builder::TmdbClientBuilder;
+pub use cache::{CacheEntry, CacheOptions, Endpoint, TmdbCache};
+pub use error::{Result, TmdbError};
+pub use images::{ImageKind, ImageOptions};
+pub use models::{AlternativeTitle, AlternativeTitles, Collection, CountryReleaseDates, Genre, MovieAppend, ReleaseDate, ReleaseDates, ReleaseType};
+pub use models::{Configuration, ImageConfiguration};
+pub use models::{Episode, ExternalIds, Movie, SeasonSummary, TvSeason, TvSeries};
+pub use models::{EpisodeGroup, EpisodeGroupList, EpisodeGroupSeason, EpisodeGroupSummary, EpisodeGroupType, GroupedEpisode};
+pub use models::{ExternalSource, FindResults, MediaIds, MovieExternalIds};
+pub use models::{GenericSearchResponse, MovieSearchResult, SearchParams, TvSeriesSearchResult};
+pub use rate_limit::{RateLimitOptions, RateLimiter};
 
+#[cfg(any(test, feature = "mock"))]
+pub use mock::MockTmdbServer;
+
+pub mod builder;
+pub mod cache;
+pub mod error;
+pub mod images;
 pub mod models;
+pub mod rate_limit;
+
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 
 #[derive(Debug, Clone)]
 pub struct TmdbClient {
     client: Client,
     api_key: String,
+    base_url: String,
+    cache: Option<TmdbCache>,
+    rate_limiter: RateLimiter,
+    images: ImageOptions,
 }
 
 const TMDB_BASE_URL: &str = "https://api.themoviedb.org/3";
@@ -30,17 +56,72 @@ impl TmdbClient {
     /// # Examples
     ///
     /// ```
+    /// use tmdb_client::TmdbClient;
+    ///
     /// let tmdb_client = TmdbClient::new("your_api_key_here");
     /// ```
     pub fn new(api_key: &str) -> Self {
-        Self { client: Client::new(), api_key: api_key.to_string() }
+        Self {
+            client: Client::new(),
+            api_key: api_key.to_string(),
+            base_url: TMDB_BASE_URL.to_string(),
+            cache: None,
+            rate_limiter: RateLimiter::new(RateLimitOptions::default()),
+            images: ImageOptions::default(),
+        }
+    }
+
+    /// Creates a `TmdbClientBuilder` to configure the base URL, timeout, user agent or HTTP client.
+    ///
+    /// # Arguments
+    ///
+    /// * `api_key` - The API key for authenticating with the TMDB API.
+    ///
+    /// # Returns
+    ///
+    /// A new instance of `TmdbClientBuilder`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tmdb_client::{TmdbClient, TmdbError};
+    /// # fn main() -> Result<(), TmdbError> {
+    /// let tmdb_client = TmdbClient::builder("your_api_key_here").base_url("https://tmdb.example.com/3").build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder(api_key: &str) -> TmdbClientBuilder {
+        TmdbClientBuilder::new(api_key)
+    }
+
+    /// Returns the base URL the API endpoints are appended to.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Removes all cached responses, so the next lookups are fetched from TMDB again.
+    ///
+    /// # Returns
+    ///
+    /// The number of cached responses that were removed, `0` if caching is disabled.
+    pub async fn flush_cache(&self) -> usize {
+        match &self.cache {
+            Some(cache) => cache.flush().await,
+            None => 0,
+        }
     }
 
     /// Sends a request to the TMDB API and deserializes the response into the specified type.
     ///
+    /// If caching is enabled, responses younger than the time to live of the endpoint are served
+    /// from the cache. Expired responses are revalidated with their `ETag` and `Last-Modified`
+    /// validators, so unchanged responses don't have to be transferred again. Requests are sent
+    /// through the rate limiter and retried as described in `send_request`.
+    ///
     /// # Arguments
     ///
-    /// * `endpoint` - The API endpoint to send the request to.
+    /// * `url` - The URL of the API endpoint to send the request to.
+    /// * `endpoint` - The kind of endpoint, which determines how long its responses are cached.
     ///
     /// # Returns
     ///
@@ -49,28 +130,130 @@ impl TmdbClient {
     ///
     /// # Errors
     ///
-    /// Returns an error if the request fails, the response status is not successful, or
-    /// deserialization of the response fails.
+    /// Returns a `TmdbError` if the request fails, the response status is not successful, or deserialization
+    /// of the response fails (`TmdbError::Decode`).
     ///
     /// # Examples
     ///
+    /// ```ignore
+    /// let response: Result<GenericSearchResponse<MovieSearchResult>> = tmdb_client.tmdb_request("search/movie?query=Inception&include_adult=false&language=en", Endpoint::Search).await;
     /// ```
-    /// let response: Result<GenericSearchResponse<MovieSearchResult>, Error> = tmdb_client.tmdb_request("search/movie?query=Inception&include_adult=false&language=en").await;
-    /// ```
-    async fn tmdb_request<T>(&self, url: &str) -> Result<T>
+    async fn tmdb_request<T>(&self, url: &str, endpoint: Endpoint) -> Result<T>
     where
         T: serde::de::DeserializeOwned,
     {
-        self.client
-            .get(url)
-            .bearer_auth(&self.api_key)
-            .send()
-            .await
-            .context(format!("could not fetch {}", url))?
-            .error_for_status()?
-            .json::<T>()
-            .await
-            .context("could not deserialize response")
+        let cached = match &self.cache {
+            Some(cache) => cache.get(url).await,
+            None => None,
+        };
+
+        if let (Some(cache), Some(entry)) = (&self.cache, &cached) {
+            if entry.is_fresh(cache.ttl(endpoint)) {
+                debug!("serving {} from cache", url);
+                return serde_json::from_str::<T>(&entry.body).map_err(|e| TmdbError::decode(e, &entry.body));
+            }
+        }
+
+        let response = self.send_request(url, cached.as_ref()).await?;
+
+        if let (Some(cache), Some(mut entry), StatusCode::NOT_MODIFIED) = (&self.cache, cached, response.status()) {
+            debug!("revalidated cached response of {}", url);
+            entry.touch();
+
+            let result = serde_json::from_str::<T>(&entry.body).map_err(|e| TmdbError::decode(e, &entry.body));
+            cache.insert(entry).await;
+            return result;
+        }
+
+        let header = |name| response.headers().get(name).and_then(|value| value.to_str().ok()).map(|value| value.to_string());
+        let (etag, last_modified) = (header(ETAG), header(LAST_MODIFIED));
+
+        let body = response.text().await.map_err(|e| TmdbError::network("could not read response", e))?;
+        let result = serde_json::from_str::<T>(&body).map_err(|e| TmdbError::decode(e, &body))?;
+
+        if let Some(cache) = &self.cache {
+            cache.insert(CacheEntry::new(url, body, etag, last_modified)).await;
+        }
+
+        Ok(result)
+    }
+
+    /// Sends a request to the TMDB API, respecting the rate limit and retrying transient failures.
+    ///
+    /// Every attempt takes a token of the rate limiter first. Requests that time out, fail to connect,
+    /// are rate limited (`429`) or fail with a server error (`5xx`) are retried with exponential backoff.
+    /// Rate limited requests wait for the duration of the `Retry-After` header instead, if present.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the API endpoint to send the request to.
+    /// * `cached` - The cached response of the URL, whose validators are sent to revalidate it.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the successful (or `304 Not Modified`) response.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request still fails after all retries, or if the response status is not
+    /// successful. Unsuccessful statuses are returned as `TmdbError::Unauthorized`, `TmdbError::NotFound`,
+    /// `TmdbError::RateLimited` or `TmdbError::Status`, so callers can tell them apart.
+    async fn send_request(&self, url: &str, cached: Option<&CacheEntry>) -> Result<Response> {
+        let options = self.rate_limiter.options();
+        let mut retry = 0;
+
+        loop {
+            self.rate_limiter.acquire().await;
+
+            let mut request = self.client.get(url).bearer_auth(&self.api_key);
+
+            if let Some(entry) = cached {
+                if let Some(etag) = &entry.etag {
+                    request = request.header(IF_NONE_MATCH, etag);
+                }
+                if let Some(last_modified) = &entry.last_modified {
+                    request = request.header(IF_MODIFIED_SINCE, last_modified);
+                }
+            }
+
+            let response = match request.send().await {
+                Ok(response) => response,
+                Err(e) if (e.is_timeout() || e.is_connect()) && retry < options.max_retries => {
+                    warn!("request to {} failed, retrying: {:?}", url, e);
+                    tokio::time::sleep(options.backoff(retry)).await;
+                    retry += 1;
+                    continue;
+                }
+                Err(e) => return Err(TmdbError::network(&format!("could not fetch {}", url), e)),
+            };
+
+            let status = response.status();
+
+            if status.is_success() || status == StatusCode::NOT_MODIFIED {
+                return Ok(response);
+            }
+
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
+
+            if (status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()) && retry < options.max_retries {
+                let delay = retry_after
+                    .unwrap_or_else(|| options.backoff(retry))
+                    .min(Duration::from_millis(options.max_backoff_ms));
+                warn!("request to {} failed with {}, retrying in {:?}", url, status, delay);
+
+                tokio::time::sleep(delay).await;
+                retry += 1;
+                continue;
+            }
+
+            let body = response.text().await.unwrap_or_default();
+            return Err(TmdbError::from_status(status.as_u16(), &body, retry_after));
+        }
     }
 }
 
@@ -91,11 +274,73 @@ impl TmdbClient {
     ///
     /// Returns an error if URL construction fails, or if the request fails.
     pub async fn search_movies(&self, query: &str, lang: &str) -> Result<GenericSearchResponse<MovieSearchResult>> {
-        let url = Url::parse_with_params(&format!("{}/search/movie", TMDB_BASE_URL), &[("include_adult", "false"), ("query", query), ("language", lang)])
-            .context("could not parse URL")?;
+        self.search_movies_with(query, lang, &SearchParams::default()).await
+    }
 
-        info!("Searching movies with query: {}", query);
-        self.tmdb_request::<GenericSearchResponse<MovieSearchResult>>(url.as_str()).await
+    /// Searches for movies on TMDB, returning the given page of the results released in the given year.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The search query string.
+    /// * `lang` - The language code for the search results.
+    /// * `params` - The page of the results and the release year to filter by.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the page of the `GenericSearchResponse<MovieSearchResult>` if the request is successful,
+    /// or an error if the request fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if URL construction fails, or if the request fails.
+    pub async fn search_movies_with(&self, query: &str, lang: &str, params: &SearchParams) -> Result<GenericSearchResponse<MovieSearchResult>> {
+        let url = self.search_url("movie", "year", query, lang, params)?;
+
+        info!("Searching movies with query: {} (page {})", query, params.page);
+        self.tmdb_request::<GenericSearchResponse<MovieSearchResult>>(url.as_str(), Endpoint::Search).await
+    }
+
+    /// Searches for movies on TMDB page by page, starting at the page of the given parameters.
+    ///
+    /// The next page is only fetched once the stream is polled again, so consumers can stop as soon
+    /// as they found the right result. The stream ends after the last page, after `max_pages` pages,
+    /// or after the first error.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The search query string.
+    /// * `lang` - The language code for the search results.
+    /// * `params` - The first page of the results and the release year to filter by.
+    /// * `max_pages` - The maximum number of pages to fetch.
+    ///
+    /// # Returns
+    ///
+    /// A `Stream` of the pages of the results.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::pin::pin;
+    /// # use futures::StreamExt;
+    /// # use tmdb_client::{SearchParams, TmdbClient, TmdbError};
+    /// # async fn example(tmdb_client: TmdbClient) -> Result<(), TmdbError> {
+    /// let mut pages = pin!(tmdb_client.search_movies_pages("Hamlet", "en-US", &SearchParams::default(), 5));
+    ///
+    /// while let Some(page) = pages.next().await {
+    ///     println!("{:?}", page?.results);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn search_movies_pages<'a>(
+        &'a self, query: &str, lang: &str, params: &SearchParams, max_pages: u32,
+    ) -> impl Stream<Item = Result<GenericSearchResponse<MovieSearchResult>>> + 'a {
+        let (query, lang) = (query.to_string(), lang.to_string());
+
+        search_pages(params, max_pages, move |params| {
+            let (query, lang) = (query.clone(), lang.clone());
+            async move { self.search_movies_with(&query, &lang, &params).await }
+        })
     }
 
     /// Searches for TV series on TMDB based on the given query and language.
@@ -114,15 +359,75 @@ impl TmdbClient {
     ///
     /// Returns an error if URL construction fails, or if the request fails.
     pub async fn search_tv_series(&self, query: &str, lang: &str) -> Result<GenericSearchResponse<TvSeriesSearchResult>> {
-        let url = Url::parse_with_params(&format!("{}/search/tv", TMDB_BASE_URL), &[("include_adult", "false"), ("query", query), ("language", lang)])
-            .context("could not parse URL")?;
+        self.search_tv_series_with(query, lang, &SearchParams::default()).await
+    }
 
-        info!("Searching tv shows with query: {}", query);
-        self.tmdb_request::<GenericSearchResponse<TvSeriesSearchResult>>(url.as_str()).await
+    /// Searches for TV series on TMDB, returning the given page of the results first aired in the given year.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The search query string.
+    /// * `lang` - The language code for the search results.
+    /// * `params` - The page of the results and the year of the first air date to filter by.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the page of the `GenericSearchResponse<TvSeriesSearchResult>` if the request is successful,
+    /// or an error if the request fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if URL construction fails, or if the request fails.
+    pub async fn search_tv_series_with(&self, query: &str, lang: &str, params: &SearchParams) -> Result<GenericSearchResponse<TvSeriesSearchResult>> {
+        let url = self.search_url("tv", "first_air_date_year", query, lang, params)?;
+
+        info!("Searching tv shows with query: {} (page {})", query, params.page);
+        self.tmdb_request::<GenericSearchResponse<TvSeriesSearchResult>>(url.as_str(), Endpoint::Search)
+            .await
+    }
+
+    /// Searches for TV series on TMDB page by page, like `search_movies_pages` does for movies.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The search query string.
+    /// * `lang` - The language code for the search results.
+    /// * `params` - The first page of the results and the year of the first air date to filter by.
+    /// * `max_pages` - The maximum number of pages to fetch.
+    ///
+    /// # Returns
+    ///
+    /// A `Stream` of the pages of the results.
+    pub fn search_tv_series_pages<'a>(
+        &'a self, query: &str, lang: &str, params: &SearchParams, max_pages: u32,
+    ) -> impl Stream<Item = Result<GenericSearchResponse<TvSeriesSearchResult>>> + 'a {
+        let (query, lang) = (query.to_string(), lang.to_string());
+
+        search_pages(params, max_pages, move |params| {
+            let (query, lang) = (query.clone(), lang.clone());
+            async move { self.search_tv_series_with(&query, &lang, &params).await }
+        })
+    }
+
+    /// Builds the URL of a search, e.g. `search/movie`, with the page and the year filter of the media type.
+    fn search_url(&self, media_type: &str, year_param: &str, query: &str, lang: &str, params: &SearchParams) -> Result<Url> {
+        let mut query_params = vec![("include_adult", "false".to_string()), ("query", query.to_string()), ("language", lang.to_string())];
+
+        if params.page > 1 {
+            query_params.push(("page", params.page.to_string()));
+        }
+        if let Some(year) = params.year {
+            query_params.push((year_param, year.to_string()));
+        }
+
+        Url::parse_with_params(&format!("{}/search/{}", self.base_url, media_type), &query_params).map_err(invalid_url)
     }
 
     /// Retrieves details of a specific movie from TMDB based on the given movie ID.
     ///
+    /// The release dates and alternative titles are appended to the response, use `get_movie_with` to
+    /// choose the appended sub-resources.
+    ///
     /// # Arguments
     ///
     /// * `id` - The TMDB ID of the movie.
@@ -135,14 +440,54 @@ impl TmdbClient {
     ///
     /// Returns an error if URL construction fails, or if the request fails.
     pub async fn get_movie(&self, id: u32, lang: &str) -> Result<Movie> {
-        let url = Url::parse_with_params(&format!("{}/movie/{}", TMDB_BASE_URL, id), &[("language", lang)]).context("could not parse URL")?;
+        self.get_movie_with(id, lang, &[MovieAppend::ReleaseDates, MovieAppend::AlternativeTitles]).await
+    }
+
+    /// Retrieves details of a specific movie from TMDB, with the given sub-resources appended to the response.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The TMDB ID of the movie.
+    /// * `lang` - The language of the details, e.g. `en-US`.
+    /// * `append` - The sub-resources to fetch in the same request, e.g. the release dates.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a `Movie` struct, with the appended sub-resources set, if the request is successful.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if URL construction fails, or if the request fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use tmdb_client::{MovieAppend, TmdbClient, TmdbError};
+    /// # async fn example(tmdb_client: TmdbClient) -> Result<(), TmdbError> {
+    /// let movie = tmdb_client.get_movie_with(27205, "en-US", &[MovieAppend::ReleaseDates]).await?;
+    /// println!("Rated {:?} in the US", movie.certification("US"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_movie_with(&self, id: u32, lang: &str, append: &[MovieAppend]) -> Result<Movie> {
+        let mut params = vec![("language", lang.to_string())];
+
+        if !append.is_empty() {
+            params.push(("append_to_response", append.iter().map(|append| append.as_str()).collect::<Vec<&str>>().join(",")));
+        }
+
+        let url = Url::parse_with_params(&format!("{}/movie/{}", self.base_url, id), &params).map_err(invalid_url)?;
 
         info!("Fetching movie with id: {}", id);
-        self.tmdb_request::<Movie>(url.as_str()).await
+        self.tmdb_request::<Movie>(url.as_str(), Endpoint::Movie).await
     }
 
     /// Fetches details of a TV series, including season details, from TMDB.
     ///
+    /// All seasons listed by TMDB are fetched, including the specials (season `0`). Seasons that cannot be
+    /// fetched (e.g. seasons that have not aired yet) are skipped, so `seasons` might not contain every
+    /// season. Look up seasons by their `season_number`.
+    ///
     /// # Arguments
     ///
     /// * `id` - The TMDB ID of the TV series.
@@ -153,16 +498,43 @@ impl TmdbClient {
     ///
     /// # Errors
     ///
-    /// Returns an error if URL construction fails, the request fails, or if fetching season details fails.
+    /// Returns an error if URL construction fails, the request fails, or if fetching all season details fails.
     pub async fn get_tv_series(&self, id: u32, lang: &str) -> Result<TvSeries> {
-        let url = Url::parse_with_params(&format!("{}/tv/{}", TMDB_BASE_URL, id), &[("language", lang), ("append_to_response", "external_ids")])
-            .context("could not parse URL")?;
+        let url = Url::parse_with_params(&format!("{}/tv/{}", self.base_url, id), &[("language", lang), ("append_to_response", "external_ids")]).map_err(invalid_url)?;
 
         info!("Fetching tv series with id: {}", id);
-        let mut response = self.tmdb_request::<TvSeries>(url.as_str()).await?;
+        let mut response = self.tmdb_request::<TvSeries>(url.as_str(), Endpoint::TvSeries).await?;
 
-        let season_futures = (1..=response.last_episode_to_air.season_number).map(|season| self.get_season_details(id, season, lang));
-        response.seasons = try_join_all(season_futures).await.context("could not fetch season details")?;
+        let mut season_numbers = response
+            .season_summaries
+            .iter()
+            .filter(|season| season.season_number > 0 || season.episode_count > 0)
+            .map(|season| season.season_number)
+            .collect::<Vec<u16>>();
+
+        if season_numbers.is_empty() {
+            season_numbers = (1..=response.last_episode_to_air.season_number).collect();
+        }
+
+        let season_futures = season_numbers.iter().map(|&season| self.get_season_details(id, season, lang));
+        let mut seasons = vec![];
+        let mut errors = vec![];
+
+        for (season, result) in season_numbers.iter().zip(join_all(season_futures).await) {
+            match result {
+                Ok(details) => seasons.push(details),
+                Err(e) => {
+                    warn!("skipping season {} of tv series {}: {:?}", season, id, e);
+                    errors.push(e);
+                }
+            }
+        }
+
+        if seasons.is_empty() && !errors.is_empty() {
+            return Err(errors.remove(0));
+        }
+
+        response.seasons = seasons;
 
         Ok(response)
     }
@@ -172,15 +544,605 @@ impl TmdbClient {
     /// # Arguments
     ///
     /// * `id` - The TMDB ID of the TV series.
-    /// * `season` - The season number.
+    /// * `season` - The season number, `0` for the specials.
     ///
     /// # Returns
     ///
     /// A `Result` containing a `TvSeason` struct if the request is successful, or an error if the request fails.
     pub async fn get_season_details(&self, id: u32, season: u16, lang: &str) -> Result<TvSeason> {
-        let url = Url::parse_with_params(&format!("{}/tv/{}/season/{}", TMDB_BASE_URL, id, season), &[("language", lang)]).context("could not parse URL")?;
+        let url = Url::parse_with_params(&format!("{}/tv/{}/season/{}", self.base_url, id, season), &[("language", lang)]).map_err(invalid_url)?;
 
         info!("Fetching tv season details for id {} and season {}", id, season);
-        self.tmdb_request::<TvSeason>(url.as_str()).await
+        self.tmdb_request::<TvSeason>(url.as_str(), Endpoint::Season).await
+    }
+
+    /// Fetches the episode groups of a TV series from TMDB, e.g. to find its DVD order.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The TMDB ID of the TV series.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `EpisodeGroupSummary` of every episode group of the series.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if URL construction fails, or if the request fails.
+    pub async fn get_episode_groups(&self, id: u32) -> Result<Vec<EpisodeGroupSummary>> {
+        let url = Url::parse(&format!("{}/tv/{}/episode_groups", self.base_url, id)).map_err(invalid_url)?;
+
+        info!("Fetching episode groups of tv series with id: {}", id);
+        Ok(self.tmdb_request::<EpisodeGroupList>(url.as_str(), Endpoint::EpisodeGroup).await?.results)
+    }
+
+    /// Fetches an episode group with the ordering of its episodes from TMDB.
+    ///
+    /// The groups of the episode group take the place of seasons, e.g. the volumes of a DVD release,
+    /// and list the episodes in their order, together with their aired season and episode number.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_id` - The TMDB ID of the episode group.
+    /// * `lang` - The language code for the episode names.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `EpisodeGroup` if the request is successful, or an error if the request fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if URL construction fails, or if the request fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tmdb_client::{EpisodeGroupType, TmdbClient, TmdbError};
+    /// # async fn example(tmdb_client: TmdbClient) -> Result<(), TmdbError> {
+    /// let groups = tmdb_client.get_episode_groups(67070).await?;
+    /// let dvd_order = groups.iter().find(|group| group.group_type == EpisodeGroupType::Dvd).unwrap();
+    /// let group = tmdb_client.get_episode_group(&dvd_order.id, "en-US").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_episode_group(&self, group_id: &str, lang: &str) -> Result<EpisodeGroup> {
+        let url = Url::parse_with_params(&format!("{}/tv/episode_group/{}", self.base_url, group_id), &[("language", lang)]).map_err(invalid_url)?;
+
+        info!("Fetching episode group with id: {}", group_id);
+        self.tmdb_request::<EpisodeGroup>(url.as_str(), Endpoint::EpisodeGroup).await
+    }
+
+    /// Retrieves the external IDs (IMDb and TVDB) of a TV series, without fetching its seasons.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The TMDB ID of the TV series.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `ExternalIds` of the TV series, each of which might be unknown.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if URL construction fails, or if the request fails.
+    pub async fn get_tv_series_external_ids(&self, id: u32) -> Result<ExternalIds> {
+        let url = Url::parse(&format!("{}/tv/{}/external_ids", self.base_url, id)).map_err(invalid_url)?;
+
+        info!("Fetching external ids of tv series with id: {}", id);
+        self.tmdb_request::<ExternalIds>(url.as_str(), Endpoint::TvSeries).await
+    }
+
+    /// Finds movies and TV series by their ID in an external database, e.g. IMDb or TVDB.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID in the external database, e.g. `tt1375666` for IMDb.
+    /// * `source` - The external database the ID belongs to.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the movies and TV series with the ID, which are empty if nothing was found.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if URL construction fails, or if the request fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use tmdb_client::{ExternalSource, TmdbClient, TmdbError};
+    /// # async fn example(tmdb_client: TmdbClient) -> Result<(), TmdbError> {
+    /// let results = tmdb_client.find_by_external_id("tt1375666", ExternalSource::Imdb).await?;
+    /// println!("TMDB ID: {:?}", results.movie_results.first().map(|movie| movie.id));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn find_by_external_id(&self, id: &str, source: ExternalSource) -> Result<FindResults> {
+        let url = Url::parse_with_params(&format!("{}/find/{}", self.base_url, id), &[("external_source", source.as_str())]).map_err(invalid_url)?;
+
+        info!("Finding media with {}: {}", source.as_str(), id);
+        self.tmdb_request::<FindResults>(url.as_str(), Endpoint::Find).await
+    }
+
+    /// Resolves the IDs of a movie, so that the TMDB ID Radarr needs is known for any provided ID.
+    ///
+    /// The TMDB ID is looked up by the IMDb ID if missing, the IMDb ID is then fetched from the movie
+    /// details. Movies have no TVDB ID, so it is kept as provided.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The known IDs of the movie, of which at least the TMDB or the IMDb ID has to be set.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `MediaIds` with the TMDB ID and, if known to TMDB, the IMDb ID set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if neither ID is set, no movie has the IMDb ID, or a request fails.
+    pub async fn resolve_movie_ids(&self, mut ids: MediaIds) -> Result<MediaIds> {
+        if ids.tmdb_id.is_none() {
+            let imdb_id = ids
+                .imdb_id
+                .as_deref()
+                .ok_or_else(|| TmdbError::invalid_input("neither a TMDB nor an IMDb ID was provided for the movie"))?;
+            let results = self.find_by_external_id(imdb_id, ExternalSource::Imdb).await?;
+            let movie = results
+                .movie_results
+                .first()
+                .ok_or_else(|| TmdbError::NotFound { message: format!("no movie found with IMDb ID {}", imdb_id) })?;
+            ids.tmdb_id = Some(movie.id);
+        }
+
+        if ids.imdb_id.is_none() {
+            let tmdb_id = ids.tmdb_id.unwrap_or_default();
+            let movie = self.get_movie_with(tmdb_id, "en-US", &[MovieAppend::ExternalIds]).await?;
+            ids.imdb_id = movie.imdb_id.or(movie.external_ids.and_then(|external_ids| external_ids.imdb_id));
+        }
+
+        Ok(ids)
+    }
+
+    /// Resolves the IDs of a TV series, so that the TVDB ID Sonarr needs is known for any provided ID.
+    ///
+    /// The TMDB ID is looked up by the TVDB or IMDb ID if missing, the missing external IDs are then
+    /// fetched from the external IDs of the TV series.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The known IDs of the TV series, of which at least one has to be set.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `MediaIds` with the TMDB ID and, if known to TMDB, the IMDb and TVDB IDs set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no ID is set, no TV series has the external ID, or a request fails.
+    pub async fn resolve_tv_series_ids(&self, mut ids: MediaIds) -> Result<MediaIds> {
+        if ids.tmdb_id.is_none() {
+            let (id, source) = match (&ids.tvdb_id, &ids.imdb_id) {
+                (Some(tvdb_id), _) => (tvdb_id.to_string(), ExternalSource::Tvdb),
+                (None, Some(imdb_id)) => (imdb_id.clone(), ExternalSource::Imdb),
+                (None, None) => return Err(TmdbError::invalid_input("no TMDB, IMDb or TVDB ID was provided for the TV series")),
+            };
+
+            let results = self.find_by_external_id(&id, source).await?;
+            ids.tmdb_id = Some(
+                results
+                    .tv_results
+                    .first()
+                    .ok_or_else(|| TmdbError::NotFound { message: format!("no TV series found with {} {}", source.as_str(), id) })?
+                    .id,
+            );
+        }
+
+        if ids.tvdb_id.is_none() || ids.imdb_id.is_none() {
+            let external_ids = self.get_tv_series_external_ids(ids.tmdb_id.unwrap_or_default()).await?;
+            ids.tvdb_id = ids.tvdb_id.or(external_ids.tvdb_id);
+            ids.imdb_id = ids.imdb_id.or(external_ids.imdb_id);
+        }
+
+        Ok(ids)
+    }
+
+    /// Retrieves the configuration of the TMDB API, containing the base URLs and sizes of images.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Configuration` if the request is successful, or an error if the request fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if URL construction fails, or if the request fails.
+    pub async fn get_configuration(&self) -> Result<Configuration> {
+        let url = Url::parse(&format!("{}/configuration", self.base_url)).map_err(invalid_url)?;
+
+        info!("Fetching configuration");
+        self.tmdb_request::<Configuration>(url.as_str(), Endpoint::Configuration).await
+    }
+
+    /// Returns the URL of an image in the given size, using the image base URL of the TMDB configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the image, e.g. the `poster_path` of a movie.
+    /// * `size` - The size of the image, e.g. `w500` or `original`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the URL of the image, e.g. `https://image.tmdb.org/t/p/w500/8ZTVqvKDQ8emSGUEMjsS4yHAwrp.jpg`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration cannot be fetched, the size is not available, or the path is invalid.
+    pub async fn image_url(&self, path: &str, size: &str) -> Result<String> {
+        let images = self.get_configuration().await?.images;
+
+        if size != "original" && !images.has_size(size) {
+            return Err(TmdbError::invalid_input(format!("image size {} is not available", size)));
+        }
+
+        Ok(format!("{}{}/{}", images.secure_base_url, size, images::image_file_name(path)?))
+    }
+
+    /// Retrieves an image in the given size, from the image directory if it was downloaded before.
+    ///
+    /// Images on TMDB never change (a changed image gets a new path), so stored images are never revalidated.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the image, e.g. the `poster_path` of a movie.
+    /// * `size` - The size of the image, e.g. `w500` or `original`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the bytes of the image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path or size is invalid, or if the image cannot be downloaded. Failing to store
+    /// the image is only logged.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use tmdb_client::{TmdbClient, TmdbError};
+    /// # async fn example(tmdb_client: TmdbClient) -> Result<(), TmdbError> {
+    /// let movie = tmdb_client.get_movie(27205, "en-US").await?;
+    /// let poster = tmdb_client.get_image(&movie.poster_path.unwrap(), "w500").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_image(&self, path: &str, size: &str) -> Result<Vec<u8>> {
+        let file = self.images.image_file(path, size)?;
+
+        if let Some(bytes) = file.as_ref().and_then(|file| std::fs::read(file).ok()) {
+            debug!("serving image {} in size {} from {:?}", path, size, file);
+            return Ok(bytes);
+        }
+
+        let url = self.image_url(path, size).await?;
+        info!("Downloading image: {}", url);
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| TmdbError::network(&format!("could not fetch {}", url), e))?;
+        let status = response.status();
+
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(TmdbError::from_status(status.as_u16(), &body, None));
+        }
+
+        let bytes = response.bytes().await.map_err(|e| TmdbError::network("could not read image", e))?.to_vec();
+
+        if let Some(file) = file {
+            if let Err(e) = images::write_image(&file, &bytes) {
+                warn!("failed to store image {}: {:?}", path, e);
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    /// Saves artwork to a file in the size configured for its kind, e.g. a poster as `poster.jpg` next to a movie.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the image, e.g. the `poster_path` of a movie.
+    /// * `kind` - The kind of the artwork, which determines its size.
+    /// * `file` - The file the artwork is written to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image cannot be retrieved or written.
+    pub async fn save_artwork(&self, path: &str, kind: ImageKind, file: &std::path::Path) -> Result<()> {
+        let bytes = self.get_image(path, self.images.size(kind)).await?;
+        images::write_image(file, &bytes)
+    }
+}
+
+/// Creates the error of a URL that could not be parsed.
+fn invalid_url(error: impl std::fmt::Display) -> TmdbError {
+    TmdbError::invalid_input(format!("could not parse URL: {}", error))
+}
+
+/// Creates a stream of search result pages, fetching each page with `fetch` once the previous one was consumed.
+///
+/// The stream starts at the page of `params` and ends after the last page of the results, after
+/// `max_pages` pages or after the first error.
+fn search_pages<T, F, Fut>(params: &SearchParams, max_pages: u32, fetch: F) -> impl Stream<Item = Result<GenericSearchResponse<T>>>
+where
+    F: Fn(SearchParams) -> Fut,
+    Fut: std::future::Future<Output = Result<GenericSearchResponse<T>>>,
+{
+    let first_page = params.page.max(1);
+    let last_page = first_page.saturating_add(max_pages).saturating_sub(1);
+    let year = params.year;
+
+    stream::unfold((Some(first_page), fetch), move |(page, fetch)| async move {
+        let page = page.filter(|page| *page <= last_page)?;
+        let result = fetch(SearchParams { page, year }).await;
+
+        let next_page = match &result {
+            Ok(response) if page < response.total_pages => Some(page + 1),
+            _ => None,
+        };
+
+        Some((result, (next_page, fetch)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn fetches_movie_from_mock_server() {
+        let server = MockTmdbServer::start().await;
+        let movie = server.client().get_movie(27205, "en-US").await.unwrap();
+
+        assert_eq!(movie.title, "Inception");
+        assert_eq!(movie.runtime, Some(148));
+        assert_eq!(server.requests(), vec!["/3/movie/27205?language=en-US&append_to_response=release_dates%2Calternative_titles"]);
+    }
+
+    #[tokio::test]
+    async fn fetches_movie_release_dates_and_alternative_titles() {
+        let server = MockTmdbServer::start().await;
+        let movie = server.client().get_movie(27205, "en-US").await.unwrap();
+
+        assert_eq!(movie.imdb_id.as_deref(), Some("tt1375666"));
+        assert_eq!(movie.year(), Some(2010));
+        assert_eq!(movie.certification("US"), Some("PG-13"));
+        assert_eq!(movie.certification("de"), Some("12"));
+        assert_eq!(movie.certification("FR"), None);
+        assert_eq!(movie.release_date_in("US", ReleaseType::Physical), Some("2010-12-07T00:00:00.000Z"));
+        assert_eq!(movie.titles(), vec!["Inception", "Origine", "A Origem", "Inception: The IMAX Experience"]);
+    }
+
+    #[tokio::test]
+    async fn appends_only_requested_sub_resources() {
+        let server = MockTmdbServer::start().await;
+        server.client().get_movie_with(27205, "en-US", &[]).await.unwrap();
+
+        assert_eq!(server.requests(), vec!["/3/movie/27205?language=en-US"]);
+    }
+
+    #[tokio::test]
+    async fn fetches_all_seasons_of_tv_series() {
+        let server = MockTmdbServer::start().await;
+        let tv_series = server.client().get_tv_series(67070, "en-US").await.unwrap();
+
+        assert_eq!(tv_series.name, "Fleabag");
+        assert_eq!(tv_series.seasons.len(), 3);
+        assert_eq!(tv_series.season(0).unwrap().name, "Specials");
+        assert_eq!(tv_series.season(2).unwrap().episodes.len(), 6);
+    }
+
+    #[tokio::test]
+    async fn searches_movies_and_tv_series() {
+        let server = MockTmdbServer::start().await;
+        let client = server.client();
+
+        assert_eq!(client.search_movies("Inception", "en-US").await.unwrap().results[0].id, 27205);
+        assert_eq!(client.search_tv_series("Fleabag", "en-US").await.unwrap().results[0].id, 67070);
+    }
+
+    #[tokio::test]
+    async fn passes_page_and_year_filters_to_searches() {
+        let server = MockTmdbServer::start().await;
+        let client = server.client();
+        let params = SearchParams { page: 2, year: Some(2010) };
+
+        client.search_movies_with("Inception", "en-US", &params).await.unwrap();
+        client.search_tv_series_with("Fleabag", "en-US", &params).await.unwrap();
+
+        assert_eq!(
+            server.requests(),
+            vec![
+                "/3/search/movie?include_adult=false&query=Inception&language=en-US&page=2&year=2010",
+                "/3/search/tv?include_adult=false&query=Fleabag&language=en-US&page=2&first_air_date_year=2010",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_search_pages_until_last_page() {
+        let server = MockTmdbServer::start().await;
+        server.respond("/search/movie", 200, r#"{"page":1,"total_results":3,"total_pages":3,"results":[]}"#);
+
+        let pages: Vec<_> = server.client().search_movies_pages("Hamlet", "en-US", &SearchParams::default(), 10).collect().await;
+
+        assert_eq!(pages.len(), 3);
+        assert_eq!(
+            server.requests(),
+            vec![
+                "/3/search/movie?include_adult=false&query=Hamlet&language=en-US",
+                "/3/search/movie?include_adult=false&query=Hamlet&language=en-US&page=2",
+                "/3/search/movie?include_adult=false&query=Hamlet&language=en-US&page=3",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_search_pages_up_to_limit() {
+        let server = MockTmdbServer::start().await;
+        server.respond("/search/tv", 200, r#"{"page":1,"total_results":100,"total_pages":5,"results":[]}"#);
+
+        let params = SearchParams { page: 2, year: Some(2016) };
+        let pages: Vec<_> = server.client().search_tv_series_pages("Fleabag", "en-US", &params, 2).collect().await;
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(
+            server.requests(),
+            vec![
+                "/3/search/tv?include_adult=false&query=Fleabag&language=en-US&page=2&first_air_date_year=2016",
+                "/3/search/tv?include_adult=false&query=Fleabag&language=en-US&page=3&first_air_date_year=2016",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stops_streaming_search_pages_after_error() {
+        let server = MockTmdbServer::start().await;
+        server.respond("/search/movie", 401, "{}");
+
+        let pages: Vec<_> = server.client().search_movies_pages("Hamlet", "en-US", &SearchParams::default(), 10).collect().await;
+
+        assert_eq!(pages.len(), 1);
+        assert!(pages[0].is_err());
+    }
+
+    #[tokio::test]
+    async fn fails_for_unknown_ids_and_invalid_keys() {
+        let server = MockTmdbServer::start().await;
+
+        assert!(server.client().get_movie(1, "en-US").await.is_err());
+
+        let client = TmdbClient::builder("invalid").base_url(&server.base_url()).build().unwrap();
+        assert!(client.get_movie(27205, "en-US").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn distinguishes_unauthorized_and_not_found() {
+        let server = MockTmdbServer::start().await;
+
+        let error = server.client().get_movie(1, "en-US").await.unwrap_err();
+        assert!(matches!(error, TmdbError::NotFound { .. }));
+
+        let client = TmdbClient::builder("invalid").base_url(&server.base_url()).build().unwrap();
+        let error = client.get_movie(27205, "en-US").await.unwrap_err();
+        assert!(matches!(error, TmdbError::Unauthorized { message } if message.starts_with("Invalid API key")));
+
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn keeps_body_snippet_of_undecodable_responses() {
+        let server = MockTmdbServer::start().await;
+        server.respond("/movie/27205", 200, &format!(r#"{{"id":"27205","title":"{}"}}"#, "x".repeat(300)));
+
+        let error = server.client().get_movie(27205, "en-US").await.unwrap_err();
+
+        match error {
+            TmdbError::Decode { body, .. } => {
+                assert!(body.starts_with(r#"{"id":"27205""#));
+                assert_eq!(body.chars().count(), 203);
+            }
+            error => panic!("expected a decode error, got {:?}", error),
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_unreachable_server_as_network_error() {
+        let rate_limit = RateLimitOptions { initial_backoff_ms: 10, ..Default::default() };
+        let client = TmdbClient::builder("key").base_url("http://127.0.0.1:9/3").rate_limit(rate_limit).build().unwrap();
+        let error = client.search_movies("Inception", "en-US").await.unwrap_err();
+
+        assert!(matches!(error, TmdbError::Network { .. }));
+    }
+
+    #[tokio::test]
+    async fn skips_seasons_that_cannot_be_fetched() {
+        let server = MockTmdbServer::start().await;
+        server.respond("/tv/67070/season/1", 404, "{}");
+
+        let tv_series = server.client().get_tv_series(67070, "en-US").await.unwrap();
+
+        assert_eq!(tv_series.seasons.iter().map(|season| season.season_number).collect::<Vec<u16>>(), vec![0, 2]);
+    }
+
+    #[tokio::test]
+    async fn fetches_episode_groups() {
+        let server = MockTmdbServer::start().await;
+        let client = server.client();
+
+        let groups = client.get_episode_groups(67070).await.unwrap();
+        assert_eq!(groups[0].group_type, EpisodeGroupType::Dvd);
+
+        let group = client.get_episode_group(&groups[0].id, "en-US").await.unwrap();
+        let episode = group.season(2).unwrap().episode(1).unwrap();
+
+        assert_eq!((episode.season_number, episode.episode_number), (2, 1));
+        assert_eq!(group.season(3).unwrap().episode(1).unwrap().season_number, 0);
+    }
+
+    #[tokio::test]
+    async fn finds_media_by_external_id() {
+        let server = MockTmdbServer::start().await;
+        let client = server.client();
+
+        let results = client.find_by_external_id("tt1375666", ExternalSource::Imdb).await.unwrap();
+        assert_eq!(results.movie_results[0].id, 27205);
+        assert!(results.tv_results.is_empty());
+
+        let results = client.find_by_external_id("313447", ExternalSource::Tvdb).await.unwrap();
+        assert_eq!(results.tv_results[0].id, 67070);
+    }
+
+    #[tokio::test]
+    async fn resolves_movie_ids() {
+        let server = MockTmdbServer::start().await;
+        let client = server.client();
+        let expected = MediaIds { tmdb_id: Some(27205), imdb_id: Some("tt1375666".to_string()), tvdb_id: None };
+
+        let by_imdb = client
+            .resolve_movie_ids(MediaIds { imdb_id: Some("tt1375666".to_string()), ..Default::default() })
+            .await
+            .unwrap();
+        let by_tmdb = client.resolve_movie_ids(MediaIds { tmdb_id: Some(27205), ..Default::default() }).await.unwrap();
+
+        assert_eq!(by_imdb, expected);
+        assert_eq!(by_tmdb, expected);
+        assert!(matches!(client.resolve_movie_ids(MediaIds::default()).await, Err(TmdbError::InvalidInput { .. })));
+    }
+
+    #[tokio::test]
+    async fn resolves_tv_series_ids() {
+        let server = MockTmdbServer::start().await;
+        let client = server.client();
+        let expected = MediaIds { tmdb_id: Some(67070), imdb_id: Some("tt5687612".to_string()), tvdb_id: Some(313447) };
+
+        let by_tvdb = client
+            .resolve_tv_series_ids(MediaIds { tvdb_id: Some(313447), ..Default::default() })
+            .await
+            .unwrap();
+        let by_imdb = client
+            .resolve_tv_series_ids(MediaIds { imdb_id: Some("tt5687612".to_string()), ..Default::default() })
+            .await
+            .unwrap();
+        let by_tmdb = client.resolve_tv_series_ids(MediaIds { tmdb_id: Some(67070), ..Default::default() }).await.unwrap();
+
+        assert_eq!(by_tvdb, expected);
+        assert_eq!(by_imdb, expected);
+        assert_eq!(by_tmdb, expected);
+        assert!(!server.requests().iter().any(|request| request.contains("/season/")));
     }
 }
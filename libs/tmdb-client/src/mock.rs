@@ -0,0 +1,187 @@
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+use crate::TmdbClient;
+
+/// The API key the mock server accepts, requests with any other key are answered with `401 Unauthorized`.
+pub const MOCK_API_KEY: &str = "mock_api_key";
+
+const NOT_FOUND: &str = r#"{"success":false,"status_code":34,"status_message":"The resource you requested could not be found."}"#;
+const UNAUTHORIZED: &str = r#"{"success":false,"status_code":7,"status_message":"Invalid API key: You must be granted a valid key."}"#;
+
+/// The fixtures served by the mock server, by path below the API version (query parameters are ignored).
+const FIXTURES: &[(&str, &str)] = &[
+    ("/search/movie", include_str!("../fixtures/search_movie.json")),
+    ("/search/tv", include_str!("../fixtures/search_tv.json")),
+    ("/configuration", include_str!("../fixtures/configuration.json")),
+    ("/movie/27205", include_str!("../fixtures/movie_27205.json")),
+    ("/tv/67070", include_str!("../fixtures/tv_67070.json")),
+    ("/tv/67070/season/0", include_str!("../fixtures/tv_67070_season_0.json")),
+    ("/tv/67070/season/1", include_str!("../fixtures/tv_67070_season_1.json")),
+    ("/tv/67070/season/2", include_str!("../fixtures/tv_67070_season_2.json")),
+    ("/tv/67070/episode_groups", include_str!("../fixtures/tv_67070_episode_groups.json")),
+    ("/tv/episode_group/5c8d1f5a0e0a26116a04c0d1", include_str!("../fixtures/tv_episode_group_5c8d1f5a0e0a26116a04c0d1.json")),
+    ("/tv/67070/external_ids", include_str!("../fixtures/tv_67070_external_ids.json")),
+    ("/find/tt1375666", include_str!("../fixtures/find_tt1375666.json")),
+    ("/find/tt5687612", include_str!("../fixtures/find_tt5687612.json")),
+    ("/find/313447", include_str!("../fixtures/find_313447.json")),
+];
+
+#[derive(Default)]
+struct MockState {
+    responses: Mutex<HashMap<String, (u16, String)>>,
+    queued: Mutex<HashMap<String, VecDeque<(u16, String)>>>,
+    requests: Mutex<Vec<String>>,
+}
+
+/// A local mock of the TMDB API, to test TMDB lookups offline.
+///
+/// The server listens on a random local port and serves the fixtures of the `fixtures` directory:
+/// the movie `Inception` (`27205`, IMDb `tt1375666`), the TV series `Fleabag` (`67070`, IMDb `tt5687612`,
+/// TVDB `313447`) with both of its seasons, its specials and its DVD order episode group, and a search
+/// result for each of them. Fixtures are served with an `ETag` and answered with `304 Not Modified`
+/// if the request carries it. The configuration points the image URLs at the mock server, which answers
+/// every image below `/t/p/` with a placeholder. Responses can be overridden per path, e.g. to simulate
+/// errors. Rate limited (`429`) responses carry a `Retry-After` of `0` seconds. The server is stopped
+/// when it is dropped.
+///
+/// # Examples
+///
+/// The mock server is only available with the `mock` feature (and in the tests of this crate).
+///
+/// ```ignore
+/// let server = MockTmdbServer::start().await;
+/// let movie = server.client().get_movie(27205, "en-US").await?;
+///
+/// server.respond("/movie/27205", 500, "{}");
+/// assert!(server.client().get_movie(27205, "en-US").await.is_err());
+/// ```
+pub struct MockTmdbServer {
+    address: SocketAddr,
+    state: Arc<MockState>,
+    handle: JoinHandle<()>,
+}
+
+impl MockTmdbServer {
+    /// Starts the mock server on a random local port.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no local port can be bound.
+    pub async fn start() -> Self {
+        let state = Arc::new(MockState::default());
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("failed to bind mock server");
+        let address = listener.local_addr().expect("failed to read mock server address");
+
+        let app = Router::new().fallback(serve).with_state(state.clone());
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+
+        Self { address, state, handle }
+    }
+
+    /// Returns the base URL of the mock server, the equivalent of `https://api.themoviedb.org/3`.
+    pub fn base_url(&self) -> String {
+        format!("http://{}/3", self.address)
+    }
+
+    /// Returns a `TmdbClient` pointed at the mock server, authenticated with `MOCK_API_KEY`.
+    pub fn client(&self) -> TmdbClient {
+        TmdbClient::builder(MOCK_API_KEY)
+            .base_url(&self.base_url())
+            .build()
+            .expect("failed to build mock client")
+    }
+
+    /// Overrides the response of a path (e.g. `/movie/27205`) with the given status and body.
+    pub fn respond(&self, path: &str, status: u16, body: &str) {
+        self.state.responses.lock().unwrap().insert(path.to_string(), (status, body.to_string()));
+    }
+
+    /// Answers the next request of a path with the given status and body, e.g. to simulate a transient failure.
+    /// Queued responses are answered in order, before any overridden response or fixture.
+    pub fn respond_once(&self, path: &str, status: u16, body: &str) {
+        self.state
+            .queued
+            .lock()
+            .unwrap()
+            .entry(path.to_string())
+            .or_default()
+            .push_back((status, body.to_string()));
+    }
+
+    /// Returns the paths and queries of the requests the mock server received, in order.
+    pub fn requests(&self) -> Vec<String> {
+        self.state.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockTmdbServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Answers a request with the overridden response or the fixture of its path.
+async fn serve(State(state): State<Arc<MockState>>, headers: HeaderMap, uri: Uri) -> Response {
+    state
+        .requests
+        .lock()
+        .unwrap()
+        .push(uri.path_and_query().map(|path| path.to_string()).unwrap_or_default());
+
+    // images are served without authentication, like by the image CDN of TMDB
+    if let Some(image) = uri.path().strip_prefix("/t/p/") {
+        return (StatusCode::OK, [(header::CONTENT_TYPE, "image/jpeg")], format!("mock image {}", image)).into_response();
+    }
+
+    let authorization = headers.get(header::AUTHORIZATION).and_then(|value| value.to_str().ok());
+    if authorization != Some(&format!("Bearer {}", MOCK_API_KEY)) {
+        return json_response(401, UNAUTHORIZED.to_string());
+    }
+
+    let path = uri.path().strip_prefix("/3").unwrap_or(uri.path());
+
+    if let Some((status, body)) = state.queued.lock().unwrap().get_mut(path).and_then(|queue| queue.pop_front()) {
+        return json_response(status, body);
+    }
+
+    if let Some((status, body)) = state.responses.lock().unwrap().get(path).cloned() {
+        return json_response(status, body);
+    }
+
+    let Some((_, body)) = FIXTURES.iter().find(|(fixture, _)| *fixture == path) else { return json_response(404, NOT_FOUND.to_string()) };
+
+    let etag = format!("\"{:x}\"", body.bytes().fold(0u64, |hash, byte| hash.wrapping_mul(31).wrapping_add(byte as u64)));
+
+    if headers.get(header::IF_NONE_MATCH).and_then(|value| value.to_str().ok()) == Some(etag.as_str()) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+
+    // image URLs point at the mock server instead of the image CDN of TMDB
+    let host = headers.get(header::HOST).and_then(|value| value.to_str().ok()).unwrap_or_default();
+    let body = body.replace("https://image.tmdb.org", &format!("http://{}", host));
+
+    let mut response = json_response(200, body);
+    response.headers_mut().insert(header::ETAG, etag.parse().unwrap());
+    response
+}
+
+fn json_response(status: u16, body: String) -> Response {
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    let mut response = (status, [(header::CONTENT_TYPE, "application/json;charset=utf-8")], body).into_response();
+
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        response.headers_mut().insert(header::RETRY_AFTER, "0".parse().unwrap());
+    }
+
+    response
+}
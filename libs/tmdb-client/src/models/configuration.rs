@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// The configuration of the TMDB API, containing the base URLs and sizes of images.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Configuration {
+    pub images: ImageConfiguration,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ImageConfiguration {
+    pub base_url: String,
+    pub secure_base_url: String,
+    #[serde(default)]
+    pub backdrop_sizes: Vec<String>,
+    #[serde(default)]
+    pub logo_sizes: Vec<String>,
+    #[serde(default)]
+    pub poster_sizes: Vec<String>,
+    #[serde(default)]
+    pub profile_sizes: Vec<String>,
+    #[serde(default)]
+    pub still_sizes: Vec<String>,
+}
+
+impl ImageConfiguration {
+    /// Checks whether images are available in the given size (e.g. `w500` or `original`).
+    pub fn has_size(&self, size: &str) -> bool {
+        [&self.backdrop_sizes, &self.logo_sizes, &self.poster_sizes, &self.profile_sizes, &self.still_sizes]
+            .iter()
+            .any(|sizes| sizes.iter().any(|available| available == size))
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+/// The ordering an episode group arranges the episodes of a TV series in.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(from = "u8", into = "u8")]
+pub enum EpisodeGroupType {
+    OriginalAirDate,
+    Absolute,
+    Dvd,
+    Digital,
+    StoryArc,
+    Production,
+    Tv,
+    Unknown(u8),
+}
+
+impl From<u8> for EpisodeGroupType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::OriginalAirDate,
+            2 => Self::Absolute,
+            3 => Self::Dvd,
+            4 => Self::Digital,
+            5 => Self::StoryArc,
+            6 => Self::Production,
+            7 => Self::Tv,
+            value => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<EpisodeGroupType> for u8 {
+    fn from(value: EpisodeGroupType) -> Self {
+        match value {
+            EpisodeGroupType::OriginalAirDate => 1,
+            EpisodeGroupType::Absolute => 2,
+            EpisodeGroupType::Dvd => 3,
+            EpisodeGroupType::Digital => 4,
+            EpisodeGroupType::StoryArc => 5,
+            EpisodeGroupType::Production => 6,
+            EpisodeGroupType::Tv => 7,
+            EpisodeGroupType::Unknown(value) => value,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EpisodeGroupList {
+    pub results: Vec<EpisodeGroupSummary>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct EpisodeGroupSummary {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub episode_count: u32,
+    pub group_count: u32,
+    #[serde(rename = "type")]
+    pub group_type: EpisodeGroupType,
+}
+
+/// An alternative ordering of the episodes of a TV series, e.g. the order of a DVD release.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct EpisodeGroup {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub episode_count: u32,
+    pub group_count: u32,
+    #[serde(rename = "type")]
+    pub group_type: EpisodeGroupType,
+    pub groups: Vec<EpisodeGroupSeason>,
+}
+
+impl EpisodeGroup {
+    /// Returns the group at the given position of the ordering, which takes the place of a season (e.g. `1` for DVD volume 1).
+    pub fn season(&self, order: u16) -> Option<&EpisodeGroupSeason> {
+        self.groups.iter().find(|group| group.order == order)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct EpisodeGroupSeason {
+    pub id: String,
+    pub name: String,
+    pub order: u16,
+    pub episodes: Vec<GroupedEpisode>,
+}
+
+impl EpisodeGroupSeason {
+    /// Returns the episode at the given position within the group, starting at `1`.
+    pub fn episode(&self, number: u16) -> Option<&GroupedEpisode> {
+        self.episodes.iter().find(|episode| episode.number() == number)
+    }
+}
+
+/// An episode within an episode group, with its aired season and episode number and its position in the group.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GroupedEpisode {
+    pub id: u32,
+    pub name: String,
+    #[serde(default)]
+    pub overview: String,
+    #[serde(default)]
+    pub air_date: Option<String>,
+    pub episode_number: u16,
+    pub season_number: u16,
+    #[serde(default)]
+    pub runtime: Option<u32>,
+    #[serde(default)]
+    pub still_path: Option<String>,
+    /// The position of the episode within the group, starting at `0`.
+    pub order: u16,
+}
+
+impl GroupedEpisode {
+    /// Returns the number of the episode within the group, starting at `1` like episode numbers do.
+    pub fn number(&self) -> u16 {
+        self.order + 1
+    }
+}
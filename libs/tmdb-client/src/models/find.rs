@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use super::{MovieSearchResult, TvSeriesSearchResult};
+
+/// The databases TMDB can look up media by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExternalSource {
+    Imdb,
+    Tvdb,
+}
+
+impl ExternalSource {
+    /// Returns the name of the database in the `external_source` parameter.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Imdb => "imdb_id",
+            Self::Tvdb => "tvdb_id",
+        }
+    }
+}
+
+/// The movies and TV series found by an ID of an external database.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct FindResults {
+    #[serde(default)]
+    pub movie_results: Vec<MovieSearchResult>,
+    #[serde(default)]
+    pub tv_results: Vec<TvSeriesSearchResult>,
+}
+
+/// The external IDs of a movie.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct MovieExternalIds {
+    #[serde(default)]
+    pub imdb_id: Option<String>,
+    #[serde(default)]
+    pub wikidata_id: Option<String>,
+}
+
+/// The IDs of a movie or TV series in TMDB, IMDb and TVDB, of which any might be unknown.
+///
+/// Radarr identifies movies by their TMDB ID and Sonarr TV series by their TVDB ID, so the IDs
+/// provided by users are resolved with `TmdbClient::resolve_movie_ids` and `TmdbClient::resolve_tv_series_ids`.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct MediaIds {
+    #[serde(default)]
+    pub tmdb_id: Option<u32>,
+    #[serde(default)]
+    pub imdb_id: Option<String>,
+    #[serde(default)]
+    pub tvdb_id: Option<u32>,
+}
@@ -1,12 +1,42 @@
 pub mod search;
 pub use search::GenericSearchResponse;
 pub use search::MovieSearchResult;
+pub use search::SearchParams;
 pub use search::TvSeriesSearchResult;
 
+pub mod configuration;
+pub use configuration::Configuration;
+pub use configuration::ImageConfiguration;
+
 pub mod movie;
+pub use movie::AlternativeTitle;
+pub use movie::AlternativeTitles;
+pub use movie::Collection;
+pub use movie::CountryReleaseDates;
+pub use movie::Genre;
 pub use movie::Movie;
+pub use movie::MovieAppend;
+pub use movie::ReleaseDate;
+pub use movie::ReleaseDates;
+pub use movie::ReleaseType;
+
+pub mod episode_group;
+pub use episode_group::EpisodeGroup;
+pub use episode_group::EpisodeGroupList;
+pub use episode_group::EpisodeGroupSeason;
+pub use episode_group::EpisodeGroupSummary;
+pub use episode_group::EpisodeGroupType;
+pub use episode_group::GroupedEpisode;
+
+pub mod find;
+pub use find::ExternalSource;
+pub use find::FindResults;
+pub use find::MediaIds;
+pub use find::MovieExternalIds;
 
 pub mod tv_series;
 pub use tv_series::Episode;
+pub use tv_series::ExternalIds;
+pub use tv_series::SeasonSummary;
 pub use tv_series::TvSeason;
 pub use tv_series::TvSeries;
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::MovieExternalIds;
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Movie {
     pub id: u32,
@@ -14,4 +16,203 @@ pub struct Movie {
     pub poster_path: Option<String>,
     pub vote_average: f32,
     pub vote_count: u32,
+    #[serde(default)]
+    pub imdb_id: Option<String>,
+    #[serde(default)]
+    pub original_title: String,
+    #[serde(default)]
+    pub original_language: String,
+    #[serde(default)]
+    pub genres: Vec<Genre>,
+    #[serde(default)]
+    pub belongs_to_collection: Option<Collection>,
+    /// The release dates per country, only present if appended to the response.
+    #[serde(default)]
+    pub release_dates: Option<ReleaseDates>,
+    /// The titles of the movie in other countries, only present if appended to the response.
+    #[serde(default)]
+    pub alternative_titles: Option<AlternativeTitles>,
+    /// The IDs of the movie in other databases, only present if appended to the response.
+    #[serde(default)]
+    pub external_ids: Option<MovieExternalIds>,
+}
+
+impl Movie {
+    /// Returns the year of the release date, if known.
+    pub fn year(&self) -> Option<u32> {
+        self.release_date.get(..4).and_then(|year| year.parse().ok())
+    }
+
+    /// Returns the certification (e.g. `PG-13` or `12`) of the movie in a country, preferring the theatrical release.
+    ///
+    /// # Arguments
+    ///
+    /// * `country` - The ISO 3166-1 code of the country, e.g. `US` or `DE`.
+    pub fn certification(&self, country: &str) -> Option<&str> {
+        let release_dates = self.country_release_dates(country)?;
+
+        release_dates
+            .iter()
+            .filter(|release| !release.certification.is_empty())
+            .min_by_key(|release| if release.release_type == ReleaseType::Theatrical { 0 } else { 1 })
+            .map(|release| release.certification.as_str())
+    }
+
+    /// Returns the earliest release date (e.g. `2010-07-16T00:00:00.000Z`) of a type of release in a country.
+    ///
+    /// # Arguments
+    ///
+    /// * `country` - The ISO 3166-1 code of the country, e.g. `US` or `DE`.
+    /// * `release_type` - The type of the release, e.g. `ReleaseType::Physical` for the disc release.
+    pub fn release_date_in(&self, country: &str, release_type: ReleaseType) -> Option<&str> {
+        self.country_release_dates(country)?
+            .iter()
+            .filter(|release| release.release_type == release_type)
+            .map(|release| release.release_date.as_str())
+            .min()
+    }
+
+    /// Returns all known titles of the movie, i.e. the title, the original title and the alternative titles without duplicates.
+    ///
+    /// Useful to match disc labels, which are often written in the language of the release instead of the language of the metadata.
+    pub fn titles(&self) -> Vec<&str> {
+        let alternative_titles = self
+            .alternative_titles
+            .iter()
+            .flat_map(|titles| titles.titles.iter().map(|title| title.title.as_str()));
+
+        let mut titles: Vec<&str> = vec![];
+
+        for title in [self.title.as_str(), self.original_title.as_str()].into_iter().chain(alternative_titles) {
+            if !title.is_empty() && !titles.contains(&title) {
+                titles.push(title);
+            }
+        }
+
+        titles
+    }
+
+    fn country_release_dates(&self, country: &str) -> Option<&Vec<ReleaseDate>> {
+        self.release_dates
+            .as_ref()?
+            .results
+            .iter()
+            .find(|result| result.iso_3166_1.eq_ignore_ascii_case(country))
+            .map(|result| &result.release_dates)
+    }
+}
+
+/// The sub-resources that can be appended to the movie details, saving a request per sub-resource.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MovieAppend {
+    ReleaseDates,
+    AlternativeTitles,
+    ExternalIds,
+}
+
+impl MovieAppend {
+    /// Returns the name of the sub-resource in the `append_to_response` parameter.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ReleaseDates => "release_dates",
+            Self::AlternativeTitles => "alternative_titles",
+            Self::ExternalIds => "external_ids",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Genre {
+    pub id: u32,
+    pub name: String,
+}
+
+/// The collection a movie belongs to, e.g. all movies of a franchise.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Collection {
+    pub id: u32,
+    pub name: String,
+    #[serde(default)]
+    pub poster_path: Option<String>,
+    #[serde(default)]
+    pub backdrop_path: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ReleaseDates {
+    pub results: Vec<CountryReleaseDates>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CountryReleaseDates {
+    pub iso_3166_1: String,
+    pub release_dates: Vec<ReleaseDate>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ReleaseDate {
+    #[serde(default)]
+    pub certification: String,
+    #[serde(default)]
+    pub iso_639_1: String,
+    #[serde(default)]
+    pub note: String,
+    pub release_date: String,
+    #[serde(rename = "type")]
+    pub release_type: ReleaseType,
+}
+
+/// The type of a release of a movie.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(from = "u8", into = "u8")]
+pub enum ReleaseType {
+    Premiere,
+    TheatricalLimited,
+    Theatrical,
+    Digital,
+    Physical,
+    Tv,
+    Unknown(u8),
+}
+
+impl From<u8> for ReleaseType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::Premiere,
+            2 => Self::TheatricalLimited,
+            3 => Self::Theatrical,
+            4 => Self::Digital,
+            5 => Self::Physical,
+            6 => Self::Tv,
+            value => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<ReleaseType> for u8 {
+    fn from(value: ReleaseType) -> Self {
+        match value {
+            ReleaseType::Premiere => 1,
+            ReleaseType::TheatricalLimited => 2,
+            ReleaseType::Theatrical => 3,
+            ReleaseType::Digital => 4,
+            ReleaseType::Physical => 5,
+            ReleaseType::Tv => 6,
+            ReleaseType::Unknown(value) => value,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AlternativeTitles {
+    pub titles: Vec<AlternativeTitle>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AlternativeTitle {
+    pub iso_3166_1: String,
+    pub title: String,
+    /// The kind of the title, e.g. `working title` or empty for the regular title in the country.
+    #[serde(default, rename = "type")]
+    pub title_type: String,
 }
@@ -8,6 +8,21 @@ pub struct GenericSearchResponse<T> {
     pub results: Vec<T>,
 }
 
+/// The page and filters of a search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchParams {
+    /// The page of the results, starting at `1`.
+    pub page: u32,
+    /// Only finds movies released in the year, or TV series first aired in the year.
+    pub year: Option<u32>,
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        Self { page: 1, year: None }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct MovieSearchResult {
     pub id: u32,
@@ -18,14 +18,39 @@ pub struct TvSeries {
     pub number_of_seasons: u32,
     pub last_episode_to_air: Episode,
     pub external_ids: ExternalIds,
+    /// The seasons of the series as listed by TMDB, including the specials (season `0`).
+    #[serde(default, rename(deserialize = "seasons"), skip_serializing)]
+    pub season_summaries: Vec<SeasonSummary>,
     #[serde(skip_deserializing)]
     pub seasons: Vec<TvSeason>,
 }
 
+impl TvSeries {
+    /// Returns the season with the given number, e.g. `0` for the specials.
+    pub fn season(&self, season_number: u16) -> Option<&TvSeason> {
+        self.seasons.iter().find(|season| season.season_number == season_number)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SeasonSummary {
+    pub id: u32,
+    pub season_number: u16,
+    pub name: String,
+    #[serde(default)]
+    pub episode_count: u32,
+    #[serde(default)]
+    pub air_date: Option<String>,
+    #[serde(default)]
+    pub poster_path: Option<String>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ExternalIds {
-    pub imdb_id: String,
-    pub tvdb_id: u32,
+    #[serde(default)]
+    pub imdb_id: Option<String>,
+    #[serde(default)]
+    pub tvdb_id: Option<u32>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -34,7 +59,8 @@ pub struct TvSeason {
     pub season_number: u16,
     pub name: String,
     pub overview: String,
-    pub air_date: String,
+    #[serde(default)]
+    pub air_date: Option<String>,
     pub poster_path: Option<String>,
     pub vote_average: f32,
     pub episodes: Vec<Episode>,
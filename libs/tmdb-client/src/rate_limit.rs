@@ -0,0 +1,133 @@
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RateLimitOptions {
+    /// The number of requests per second the token bucket is refilled with.
+    pub requests_per_second: f64,
+    /// The number of requests that can be sent at once before the rate limit applies.
+    pub burst: u32,
+    /// The number of retries of rate limited, failed or timed out requests.
+    pub max_retries: u32,
+    /// The delay before the first retry in milliseconds, doubled for every further retry.
+    pub initial_backoff_ms: u64,
+    /// The maximum delay between two retries in milliseconds, also applied to `Retry-After`.
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RateLimitOptions {
+    fn default() -> Self {
+        Self { requests_per_second: 40.0, burst: 20, max_retries: 3, initial_backoff_ms: 500, max_backoff_ms: 30_000 }
+    }
+}
+
+impl RateLimitOptions {
+    /// Returns the delay before the given retry (starting at `0`), growing exponentially up to the maximum.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let backoff = self.initial_backoff_ms.saturating_mul(2u64.saturating_pow(retry));
+        Duration::from_millis(backoff.min(self.max_backoff_ms))
+    }
+}
+
+/// A token bucket limiting the requests sent to TMDB, shared between all clones of a client.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    options: RateLimitOptions,
+    bucket: Arc<Mutex<(f64, Instant)>>,
+}
+
+impl RateLimiter {
+    /// Creates a new `RateLimiter` with a full bucket.
+    pub fn new(options: RateLimitOptions) -> Self {
+        let tokens = options.burst.max(1) as f64;
+        Self { options, bucket: Arc::new(Mutex::new((tokens, Instant::now()))) }
+    }
+
+    /// Returns the options of the rate limiter.
+    pub fn options(&self) -> &RateLimitOptions {
+        &self.options
+    }
+
+    /// Waits until a request may be sent and takes a token from the bucket.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+                let (tokens, refilled_at) = &mut *bucket;
+
+                let now = Instant::now();
+                *tokens = (*tokens + now.duration_since(*refilled_at).as_secs_f64() * self.options.requests_per_second).min(self.options.burst.max(1) as f64);
+                *refilled_at = now;
+
+                if *tokens >= 1.0 {
+                    *tokens -= 1.0;
+                    return;
+                }
+
+                Duration::from_secs_f64((1.0 - *tokens) / self.options.requests_per_second.max(0.001))
+            };
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock, MockTmdbServer, TmdbClient, TmdbError};
+
+    fn retrying_client(server: &MockTmdbServer) -> TmdbClient {
+        let rate_limit = RateLimitOptions { initial_backoff_ms: 10, ..Default::default() };
+        TmdbClient::builder(mock::MOCK_API_KEY)
+            .base_url(&server.base_url())
+            .rate_limit(rate_limit)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn retries_rate_limited_and_failed_requests() {
+        let server = MockTmdbServer::start().await;
+        server.respond_once("/movie/27205", 429, r#"{"status_code":25,"status_message":"Your request count is over the allowed limit."}"#);
+        server.respond_once("/movie/27205", 503, "{}");
+
+        let movie = retrying_client(&server).get_movie(27205, "en-US").await.unwrap();
+
+        assert_eq!(movie.title, "Inception");
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let server = MockTmdbServer::start().await;
+        server.respond("/movie/27205", 429, "{}");
+
+        let error = retrying_client(&server).get_movie(27205, "en-US").await.unwrap_err();
+
+        assert_eq!(error, TmdbError::RateLimited { retry_after: Some(Duration::ZERO) });
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn serves_overridden_responses() {
+        let server = MockTmdbServer::start().await;
+        server.respond("/movie/27205", 500, "{}");
+
+        assert!(retrying_client(&server).get_movie(27205, "en-US").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn limits_request_rate() {
+        let limiter = RateLimiter::new(RateLimitOptions { requests_per_second: 20.0, burst: 1, ..Default::default() });
+        let started = std::time::Instant::now();
+
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+
+        assert!(started.elapsed() >= Duration::from_millis(90));
+    }
+}
@@ -38,6 +38,8 @@ impl ProgressTracker {
     ///
     /// # Examples
     /// ```
+    /// use utils::ProgressTracker;
+    ///
     /// let tracker = ProgressTracker::new();
     /// ```
     pub fn new() -> Self {
@@ -67,6 +69,8 @@ impl ProgressTracker {
     ///
     /// # Examples
     /// ```
+    /// use utils::ProgressTracker;
+    ///
     /// let mut tracker = ProgressTracker::new();
     /// tracker.update(50.0, 100.0).unwrap();
     /// ```
@@ -123,7 +127,9 @@ impl ProgressTracker {
     ///
     /// # Examples
     /// ```
-    /// let tracker = ProgressTracker::new();
+    /// use utils::ProgressTracker;
+    ///
+    /// let mut tracker = ProgressTracker::new();
     /// tracker.update(50.0, 100.0).unwrap();
     /// let eta = tracker.get_eta();
     /// ```
@@ -131,3 +137,9 @@ impl ProgressTracker {
         self.current_eta
     }
 }
+
+impl Default for ProgressTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
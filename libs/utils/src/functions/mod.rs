@@ -3,3 +3,6 @@ pub use upload_progress::{move_file_with_progress, upload_file_with_sftp, Upload
 
 pub mod parser;
 pub use parser::{parse_csv_line, parse_duration_to_seconds};
+
+pub mod template;
+pub use template::render_template;
@@ -16,6 +16,8 @@
 /// # Examples
 ///
 /// ```
+/// use utils::parse_csv_line;
+///
 /// let line = r#""John, Doe",28,"New York, USA""#;
 /// let parsed = parse_csv_line(line);
 /// assert_eq!(parsed, vec!["John, Doe", "28", "New York, USA"]);
@@ -66,6 +68,8 @@ pub fn parse_csv_line(line: &str) -> Vec<String> {
 /// # Examples
 ///
 /// ```
+/// use utils::parse_duration_to_seconds;
+///
 /// let duration = "01:23:45";
 /// match parse_duration_to_seconds(duration) {
 ///     Ok(seconds) => assert_eq!(seconds, 5025),
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+/// Characters that are not allowed in file names on common file systems.
+const INVALID_FILE_NAME_CHARS: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Renders a file name template by replacing `{variable}` placeholders with their values.
+///
+/// Placeholders without a value (or with an empty value) are removed, as well as brackets and separators
+/// that are left empty because of them, so a template like `{title} ({year})` renders as `Title` if the
+/// year is unknown. Characters that are not allowed in file names are removed from the values.
+///
+/// # Arguments
+///
+/// * `template` - A string slice that holds the template, e.g. `{title} ({year}) [{source}-{resolution}]`.
+/// * `variables` - A map of the variable names to their values.
+///
+/// # Returns
+///
+/// * `String` - The rendered file name.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use utils::render_template;
+///
+/// let variables = HashMap::from([("title", "Dune".to_string()), ("year", "2021".to_string())]);
+/// assert_eq!(render_template("{title} ({year}) [{source}]", &variables), "Dune (2021)");
+/// ```
+pub fn render_template(template: &str, variables: &HashMap<&str, String>) -> String {
+    let mut rendered = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else { break };
+
+        rendered.push_str(&rest[..start]);
+
+        let name = rest[start + 1..start + end].trim();
+        let value = variables.get(name).map(|value| value.as_str()).unwrap_or_default();
+        rendered.extend(value.chars().filter(|c| !INVALID_FILE_NAME_CHARS.contains(c)));
+
+        rest = &rest[start + end + 1..];
+    }
+
+    rendered.push_str(rest);
+
+    clean_file_name(&rendered)
+}
+
+/// Removes brackets and separators left empty by missing values and collapses whitespace.
+fn clean_file_name(name: &str) -> String {
+    let name = [('(', ')'), ('[', ']')]
+        .iter()
+        .fold(name.to_string(), |name, &(open, close)| clean_brackets(&name, open, close));
+
+    let name = name
+        .split(" - ")
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" - ");
+
+    let name = name.split_whitespace().collect::<Vec<&str>>().join(" ").replace(" - [", " [").replace(" - (", " (");
+    name.trim_matches(|c: char| is_separator(c) || c == '.').to_string()
+}
+
+/// Trims separators inside of brackets, e.g. `[Bluray-]`, and removes brackets that are empty afterwards.
+fn clean_brackets(name: &str, open: char, close: char) -> String {
+    let mut cleaned = String::new();
+    let mut rest = name;
+
+    while let Some(start) = rest.find(open) {
+        let Some(end) = rest[start..].find(close).map(|end| start + end) else { break };
+        let content = rest[start + 1..end].trim_matches(is_separator);
+
+        cleaned.push_str(&rest[..start]);
+
+        if !content.is_empty() {
+            cleaned.push(open);
+            cleaned.push_str(content);
+            cleaned.push(close);
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    cleaned.push_str(rest);
+    cleaned
+}
+
+fn is_separator(c: char) -> bool {
+    c == '-' || c == '_' || c.is_whitespace()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(values: &[(&'static str, &str)]) -> HashMap<&'static str, String> {
+        values.iter().map(|&(name, value)| (name, value.to_string())).collect()
+    }
+
+    #[test]
+    fn renders_all_placeholders() {
+        let variables = variables(&[("title", "Dune"), ("year", "2021"), ("source", "Bluray"), ("resolution", "2160p")]);
+        assert_eq!(render_template("{title} ({year}) [{source}-{resolution}]", &variables), "Dune (2021) [Bluray-2160p]");
+    }
+
+    #[test]
+    fn removes_brackets_of_missing_year() {
+        let variables = variables(&[("title", "Dune")]);
+        assert_eq!(render_template("{title} ({year}) [{resolution}]", &variables), "Dune");
+    }
+
+    #[test]
+    fn trims_separators_left_empty_inside_brackets() {
+        let variables = variables(&[("title", "Dune"), ("resolution", "2160p")]);
+        assert_eq!(render_template("{title} [{source}-{resolution}]", &variables), "Dune [2160p]");
+        assert_eq!(render_template("{title} [{source}-]", &variables), "Dune");
+    }
+
+    #[test]
+    fn removes_separators_of_empty_episode_title() {
+        let variables = variables(&[("series", "Fleabag"), ("episode", "S01E01"), ("source", "Bluray")]);
+        assert_eq!(render_template("{series} - {episode} - {episode_title} [{source}]", &variables), "Fleabag - S01E01 [Bluray]");
+        assert_eq!(render_template("{series} - {episode_title} - {episode}", &variables), "Fleabag - S01E01");
+    }
+
+    #[test]
+    fn removes_invalid_file_name_characters_from_values() {
+        let variables = variables(&[("title", "Mission: Impossible / Fallout?"), ("year", "2018")]);
+        assert_eq!(render_template("{title} ({year})", &variables), "Mission Impossible Fallout (2018)");
+    }
+
+    #[test]
+    fn keeps_unterminated_placeholder_as_text() {
+        let variables = variables(&[("title", "Dune"), ("year", "2021")]);
+        assert_eq!(render_template("{title} {year", &variables), "Dune {year");
+    }
+}
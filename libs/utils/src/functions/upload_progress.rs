@@ -39,12 +39,13 @@ const BUFFER_SIZE: usize = 128 * 1024;
 ///
 /// # Example
 ///
-/// ```
+/// ```no_run
 /// use std::sync::mpsc::channel;
 /// use std::thread;
 /// use std::time::Duration;
 /// use std::fs::File;
 /// use std::io::Write;
+/// use utils::move_file_with_progress;
 ///
 /// fn main() {
 ///     let (sender, receiver) = channel();
@@ -112,11 +113,13 @@ pub struct UploadProgressPayload {
 /// # Arguments
 ///
 /// * `local_path` - The path to the local file to be uploaded.
-/// * `remote_user` - The username for SSH authentication on the remote server.
-/// * `remote_host` - The hostname or IP address of the remote server.
 /// * `remote_path` - The path on the remote server where the file should be uploaded.
-/// * `password` - The password for SSH authentication on the remote server.
-/// * `sender` - A channel sender for sending progress updates as a percentage (0.0 to 100.0).
+/// * `file_id` - The step reported with the progress updates, e.g. the index of the file.
+/// * `remote_host` - The hostname or IP address of the remote server.
+/// * `remote_user` - The username for SSH authentication on the remote server.
+/// * `remote_password` - The password for SSH authentication on the remote server.
+/// * `cancel_flag` - A flag that cancels the upload once it is set.
+/// * `sender` - A channel sender for sending progress updates.
 ///
 /// # Errors
 ///
@@ -129,31 +132,32 @@ pub struct UploadProgressPayload {
 ///
 /// # Example
 ///
-/// ```
+/// ```no_run
+/// use std::sync::atomic::AtomicBool;
 /// use std::sync::mpsc::channel;
+/// use std::sync::Arc;
+/// use utils::upload_file_with_sftp;
 ///
 /// fn main() {
-///     let local_path = "path/to/local/file";
-///     let remote_user = "username";
-///     let remote_host = "hostname";
-///     let remote_path = "path/to/remote/file";
-///     let password = "your_password";
-///
+///     let cancel_flag = Arc::new(AtomicBool::new(false));
 ///     let (tx, rx) = channel();
 ///
 ///     // Spawn a thread to handle the file upload
 ///     std::thread::spawn(move || {
-///         if let Err(e) = upload_file_with_sftp(local_path, remote_user, remote_host, remote_path, password, tx) {
+///         if let Err(e) = upload_file_with_sftp("path/to/local/file", "path/to/remote/file", 0, "hostname", "username", "your_password", &cancel_flag, &tx) {
 ///             eprintln!("Error: {}", e);
 ///         }
 ///     });
 ///
 ///     // Handle progress updates
-///     for progress in rx {
-///         println!("Progress: {:.2}%", progress);
+///     for (_, progress) in rx {
+///         if let Some(progress) = progress {
+///             println!("Progress: {:.2}%", progress.progress * 100.0);
+///         }
 ///     }
 /// }
 /// ```
+#[allow(clippy::too_many_arguments)]
 pub fn upload_file_with_sftp(
     local_path: &str, remote_path: &str, file_id: u32, remote_host: &str, remote_user: &str, remote_password: &str, cancel_flag: &Arc<AtomicBool>,
     sender: &Sender<(&str, Option<UploadProgressPayload>)>,
@@ -169,10 +173,10 @@ pub fn upload_file_with_sftp(
     session.userauth_password(remote_user, remote_password)?;
 
     if !session.authenticated() {
-        return Err(io::Error::new(io::ErrorKind::Other, "Authentication failed"));
+        return Err(io::Error::other("Authentication failed"));
     }
 
-    let mut file = File::open(&local_path)?;
+    let mut file = File::open(local_path)?;
     let metadata = file.metadata()?;
     let file_size = metadata.len();
 
@@ -181,7 +185,7 @@ pub fn upload_file_with_sftp(
     let remote_dir = Path::new(remote_path).parent().unwrap();
     create_remote_directory(&sftp, remote_dir)?;
 
-    let mut remote_file = sftp.create(&Path::new(remote_path))?;
+    let mut remote_file = sftp.create(Path::new(remote_path))?;
 
     let mut buffer = vec![0u8; BUFFER_SIZE];
     let mut total_bytes_sent = 0;
@@ -191,7 +195,7 @@ pub fn upload_file_with_sftp(
         if cancel_flag.load(Ordering::Relaxed) {
             info!("uploading operation aborted");
 
-            if let Err(e) = sftp.unlink(&Path::new(remote_path)) {
+            if let Err(e) = sftp.unlink(Path::new(remote_path)) {
                 error!("Failed to delete remote file: {}", e);
             } else {
                 info!("Remote file deleted successfully");
@@ -246,14 +250,13 @@ pub fn upload_file_with_sftp(
 /// This function will return an error if any of the following operations fail:
 /// * Attempting to open a directory using `sftp.opendir`.
 /// * Creating a directory using `sftp.mkdir`.
-/// ```
 fn create_remote_directory(sftp: &Sftp, remote_dir: &Path) -> io::Result<()> {
     let mut path = PathBuf::new();
 
     for component in remote_dir.components() {
         path.push(component);
 
-        if let Err(_) = sftp.opendir(&path) {
+        if sftp.opendir(&path).is_err() {
             sftp.mkdir(&path, 0o777)?;
         }
     }
@@ -1,6 +1,6 @@
 mod functions;
 pub use functions::{move_file_with_progress, upload_file_with_sftp, UploadProgressPayload};
-pub use functions::{parse_csv_line, parse_duration_to_seconds};
+pub use functions::{parse_csv_line, parse_duration_to_seconds, render_template};
 
 mod classes;
 pub use classes::ProgressTracker;
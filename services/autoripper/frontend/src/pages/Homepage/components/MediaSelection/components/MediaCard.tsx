@@ -3,6 +3,7 @@ import { forwardRef } from 'react';
 
 import { ButtonProps } from '$/components/common/ui/button';
 import { cn } from '$/lib/utils';
+import { endpointFactory } from '$/services/endpoints';
 import { Episode, SearchResultItem } from '$/services/metadata';
 
 interface Props {
@@ -43,7 +44,7 @@ export const MediaCard = forwardRef<HTMLButtonElement, ButtonProps & Props>(func
         )}
         {item.posterPath && (
           <img
-            src={`https://image.tmdb.org/t/p/w200/${item.posterPath}`}
+            src={endpointFactory.tmdbImage('w185', item.posterPath)}
             alt={`${item.title} poster`}
             className='size-full'
           />
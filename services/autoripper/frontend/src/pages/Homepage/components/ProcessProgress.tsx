@@ -64,7 +64,15 @@ export const ProcessProgress = () => {
       });
     }
 
-    if (message.type === 'uploading_done') {
+    if (message.type === 'job_held') {
+      console.warn(`Job held after verification: ${message.payload?.label}`);
+    }
+
+    if (message.type === 'job_refused') {
+      console.warn(`Job refused: ${message.payload?.label}`);
+    }
+
+    if (message.type === 'uploading_done' || message.type === 'job_held' || message.type === 'job_refused') {
       useMediaStore.setState({
         selectedTitles: [],
         selectedMovie: null,
@@ -86,7 +94,7 @@ export const ProcessProgress = () => {
         profile: movieSelectionValues!.encodingProfile,
         qualityProfile: movieSelectionValues!.qualityProfile,
         rootFolder: movieSelectionValues!.rootFolder,
-        metadata: { tmdb_id: selectedMovie!.id, title: selectedMovie!.title },
+        metadata: { tmdb_id: selectedMovie!.id, title: selectedMovie!.title, year: selectedMovie!.releaseDate.getFullYear() },
       });
     }
 
@@ -100,7 +108,9 @@ export const ProcessProgress = () => {
         rootFolder: tvShowSelectionValues!.rootFolder,
         metadata: {
           tvdb_id: selectedTvShow!.external_ids.tvdbId,
+          tmdb_id: selectedTvShow!.id,
           title: selectedTvShow!.title,
+          year: selectedTvShow!.releaseDate.getFullYear(),
           series_type: tvShowSelectionValues!.seriesType,
           season: tvShowSelectionValues!.selectedSeason,
           episodes: tvShowSelectionValues!.selectedEpisodes,
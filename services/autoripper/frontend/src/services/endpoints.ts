@@ -15,6 +15,7 @@ export const SEARCH_MOVIE_ENDPOINT = `${BASE_URL}/tmdb/search/movie`;
 export const SEARCH_TV_SHOW_ENDPOINT = `${BASE_URL}/tmdb/search/tv`;
 export const TV_SHOW_DETAILS_ENDPOINT = `${BASE_URL}/tmdb/tv`;
 export const MOVIE_DETAILS_ENDPOINT = `${BASE_URL}/tmdb/movie`;
+export const TMDB_IMAGE_ENDPOINT = `${BASE_URL}/tmdb/image`;
 
 export const endpoints = {
   DEVICE_ENDPOINT,
@@ -51,6 +52,9 @@ export const endpointFactory = {
     payload.titles.forEach((title) => params.append('titles', title));
     return `${RIP_WEB_SOCKET_ENDPOINT}?${params.toString()}`;
   },
+  tmdbImage: (size: string, path: string) => {
+    return `${TMDB_IMAGE_ENDPOINT}/${size}/${path.replace(/^\//, '')}`;
+  },
   searchMovie: (query: string, lang: string) => {
     const params = new URLSearchParams(Object.entries({ query, lang }));
     return `${SEARCH_MOVIE_ENDPOINT}?${params.toString()}`;
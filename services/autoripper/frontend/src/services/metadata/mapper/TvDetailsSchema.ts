@@ -30,7 +30,7 @@ export const TvSeasonSchema = z
     season_number: z.number(),
     name: z.string(),
     overview: z.string(),
-    air_date: z.string(),
+    air_date: z.string().nullable(),
     poster_path: z.string().nullable(),
     vote_average: z.number(),
     episodes: z.array(EpisodeSchema),
@@ -63,8 +63,8 @@ export const TvDetailsSchema = z
     number_of_seasons: z.number(),
     last_episode_to_air: EpisodeSchema,
     external_ids: z.object({
-      imdb_id: z.string(),
-      tvdb_id: z.number(),
+      imdb_id: z.string().nullable(),
+      tvdb_id: z.number().nullable(),
     }),
     seasons: z.array(TvSeasonSchema),
   })
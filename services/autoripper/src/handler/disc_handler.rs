@@ -1,14 +1,20 @@
+use anyhow::Context;
 use axum::extract::State;
 use axum::{http::StatusCode, response::IntoResponse, Json};
 use axum_extra::extract::Query;
 use serde::Deserialize;
 use serde_json::json;
+use std::path::{Path, PathBuf};
 use tracing::error;
 
-use makemkv_core::{detect_devices, filter_movie_main_features, filter_tv_series_main_features, read_disc_properties};
+use handbrake_core::{generate_previews, Previews};
+use makemkv_core::{detect_devices, filter_movie_main_features, filter_tv_series_main_features, read_disc_properties, rip_title_partially, suggest_disc_matches};
 
 use crate::AppState;
 
+/// The file inside the preview directory of a title that holds the path of the complete rip of the title.
+const PREVIEW_SOURCE_FILE_NAME: &str = "source";
+
 #[derive(Deserialize, Debug)]
 pub struct MovieTitlesPayload {
     langs: Vec<String>,
@@ -23,6 +29,26 @@ pub struct TvShowTitlesPayload {
     device: String,
     season: u16,
     episodes: Vec<u32>,
+    /// The episode group (e.g. the DVD order) the season and episodes refer to, the aired order if unset.
+    #[serde(default)]
+    episode_group: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DiscSuggestionsPayload {
+    device: String,
+    lang: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TitlePreviewsPayload {
+    device: String,
+    title: usize,
+    #[serde(default)]
+    clip: bool,
+    /// The fingerprint of the disc returned by a previous request, to serve cached previews without scanning the disc.
+    #[serde(default)]
+    fingerprint: Option<String>,
 }
 
 /// Handles requests to retrieve a list of devices.
@@ -52,6 +78,45 @@ pub async fn get_devices_handler(State(state): State<AppState>) -> impl IntoResp
     }
 }
 
+/// Handles requests to suggest the movie or TV show on a disc, based on its volume label and titles.
+///
+/// This handler reads the disc properties and matches the volume label against TMDB, so the media
+/// doesn't have to be searched by hand. The suggestions are ranked by title similarity, year and runtime.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the necessary dependencies.
+/// * `params` - The query parameters containing the device and the language of the search.
+///
+/// # Returns
+///
+/// A JSON response containing the suggestions ordered by confidence, or an error response if the operation fails.
+pub async fn get_disc_suggestions_handler(State(state): State<AppState>, Query(params): Query<DiscSuggestionsPayload>) -> impl IntoResponse {
+    // scanning the disc blocks until MakeMKV is done, so it runs off the async executor
+    let command = state.makemkv_command.clone();
+    let device = params.device.clone();
+    let makemkv_mutex = state.makemkv_mutex.clone();
+    let disc_handle = tokio::task::spawn_blocking(move || read_disc_properties(&command, &device, &makemkv_mutex));
+
+    match disc_handle.await {
+        Ok(Ok(disc)) => match suggest_disc_matches(&disc, &params.lang, &state.tmdb_client).await {
+            Ok(suggestions) => (StatusCode::OK, Json(suggestions)).into_response(),
+            Err(err) => {
+                error!("failed to suggest disc matches: {}", err);
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "failed to suggest disc matches" }))).into_response()
+            }
+        },
+        Ok(Err(err)) => {
+            error!("failed to read disc properties: {}", err);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "failed to read disc properties" }))).into_response()
+        }
+        Err(err) => {
+            error!("Disc scan task failed: {:?}", err);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "failed to read disc properties" }))).into_response()
+        }
+    }
+}
+
 /// Handles requests to retrieve disc titles and filter them based on the specified parameters.
 ///
 /// This handler reads the disc properties and applies filters based on the provided
@@ -93,7 +158,8 @@ pub async fn get_movie_titles_handler(State(state): State<AppState>, Query(param
 /// Handles requests to retrieve TV show titles and filter them based on the specified parameters.
 ///
 /// This handler reads the disc properties and applies filters based on the provided
-/// `langs`, `season`, and `episodes`. It then returns the filtered TV show titles as a JSON response.
+/// `langs`, `season`, and `episodes`, in the aired order or the order of the given `episode_group`.
+/// It then returns the filtered TV show titles as a JSON response.
 ///
 /// # Arguments
 ///
@@ -114,7 +180,7 @@ pub async fn get_tv_show_titles_handler(State(state): State<AppState>, Query(par
             let langs: Vec<&str> = params.langs.iter().map(|lang| lang.as_str()).collect();
             let episodes: Vec<u16> = params.episodes.iter().map(|&e| e as u16).collect();
 
-            match filter_tv_series_main_features(disc, &langs, params.season, &episodes, params.tmdb_id, &state.tmdb_client).await {
+            match filter_tv_series_main_features(disc, &langs, params.season, &episodes, params.tmdb_id, params.episode_group.as_deref(), &state.tmdb_client).await {
                 Ok(filtered_disc) => (StatusCode::OK, Json(filtered_disc)).into_response(),
                 Err(err) => {
                     error!("failed to filter tv show main features: {}", err);
@@ -128,3 +194,164 @@ pub async fn get_tv_show_titles_handler(State(state): State<AppState>, Query(par
         }
     }
 }
+
+/// Handles requests to generate preview frames (and optionally a short clip) of a disc title.
+///
+/// If the title was ripped completely before (see `record_preview_source`), the previews are taken from
+/// the complete rip, so they are spread across the whole title. Otherwise this handler rips the beginning
+/// of the title with MakeMKV and takes the previews from the partial rip, which is removed afterwards.
+/// The previews are cached per disc fingerprint and title, so they are only generated once per disc. If the request contains the fingerprint returned by a previous
+/// request, cached previews are returned without scanning the disc again. The returned URLs are served
+/// from the `/previews` route.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing the commands, the preview directory and the MakeMKV mutex.
+/// * `params` - The query parameters containing the device, the title ID, whether a clip should be generated
+///   and optionally the disc fingerprint.
+///
+/// # Returns
+///
+/// A JSON response containing the disc fingerprint and the URLs of the frames and the clip, or an error response.
+///
+/// # Errors
+///
+/// Returns a not found response if the disc has no title with the given ID, and an internal server error
+/// if reading the disc properties, ripping the title or generating the previews fails.
+pub async fn get_title_previews_handler(State(state): State<AppState>, Query(params): Query<TitlePreviewsPayload>) -> impl IntoResponse {
+    // scanning and ripping the disc block until MakeMKV is done, so everything runs off the async executor
+    let preview_handle = tokio::task::spawn_blocking(move || -> anyhow::Result<Option<(String, Previews)>> {
+        let title_dir = |fingerprint: &str| preview_title_dir(&state.preview_dir, fingerprint, params.title);
+
+        // the fingerprint becomes part of a path, so anything but the hex digits of `Disc::fingerprint` is ignored
+        if let Some(fingerprint) = params
+            .fingerprint
+            .filter(|fingerprint| fingerprint.len() == 16 && fingerprint.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            if let Some(previews) = Previews::cached(&title_dir(&fingerprint).to_string_lossy(), params.clip, &state.preview_options) {
+                return Ok(Some((fingerprint, previews)));
+            }
+        }
+
+        let disc = read_disc_properties(&state.makemkv_command, &params.device, &state.makemkv_mutex).context("failed to read disc properties")?;
+
+        let Some(title) = disc.titles.iter().find(|title| title.id == params.title) else {
+            return Ok(None);
+        };
+
+        let fingerprint = disc.fingerprint();
+        let title_dir = title_dir(&fingerprint);
+        let output_dir = title_dir.to_string_lossy().to_string();
+
+        if let Some(previews) = Previews::cached(&output_dir, params.clip, &state.preview_options) {
+            return Ok(Some((fingerprint, previews)));
+        }
+
+        let source_file = std::fs::read_to_string(title_dir.join(PREVIEW_SOURCE_FILE_NAME))
+            .ok()
+            .filter(|file| Path::new(file).exists());
+
+        if let Some(file) = source_file {
+            match generate_previews(&state.ffmpeg_command, &state.ffprobe_command, &file, &output_dir, params.clip, None, &state.preview_options) {
+                Ok(previews) => return Ok(Some((fingerprint, previews))),
+                Err(err) => error!("failed to generate previews from ripped title {}, ripping it partially: {:?}", file, err),
+            }
+        }
+
+        let rip_dir = title_dir.join("rip").to_string_lossy().to_string();
+        std::fs::remove_dir_all(&rip_dir).ok();
+
+        let result = rip_title_partially(&state.makemkv_command, &state.makemkv_mutex, &rip_dir, &params.device, params.title, state.preview_rip_size).and_then(|file| {
+            // the header of a partial rip still reports the duration of the whole title, so the covered duration is estimated from its size
+            let size = std::fs::metadata(&file).context("failed to read partial rip size")?.len();
+            let duration = (title.disk_size_bytes > 0).then(|| title.duration as f32 * (size as f32 / title.disk_size_bytes as f32).min(1.0));
+
+            generate_previews(&state.ffmpeg_command, &state.ffprobe_command, &file, &output_dir, params.clip, duration, &state.preview_options)
+        });
+
+        std::fs::remove_dir_all(&rip_dir).ok();
+        result.map(|previews| Some((fingerprint, previews)))
+    });
+
+    let (fingerprint, previews) = match preview_handle.await {
+        Ok(Ok(Some(previews))) => previews,
+        Ok(Ok(None)) => return (StatusCode::NOT_FOUND, Json(json!({ "error": "title not found" }))).into_response(),
+        Ok(Err(err)) => {
+            error!("failed to generate previews: {:?}", err);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "failed to generate previews" }))).into_response();
+        }
+        Err(err) => {
+            error!("Preview task failed: {:?}", err);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "failed to generate previews" }))).into_response();
+        }
+    };
+
+    let url_prefix = format!("/previews/{}/{}", fingerprint, params.title);
+    let to_url = |file: &String| format!("{}/{}", url_prefix, Path::new(file).file_name().unwrap_or_default().to_string_lossy());
+
+    let frames = previews.frames.iter().map(to_url).collect::<Vec<String>>();
+    let clip = previews.clip.as_ref().map(to_url);
+
+    (StatusCode::OK, Json(json!({ "fingerprint": fingerprint, "frames": frames, "clip": clip }))).into_response()
+}
+
+/// Returns the directory the previews of a disc title are cached in.
+fn preview_title_dir(preview_dir: &str, fingerprint: &str, title: usize) -> PathBuf {
+    Path::new(preview_dir).join(fingerprint).join(title.to_string())
+}
+
+/// Records the complete rip of a disc title, so its previews are taken from it instead of a partial rip.
+///
+/// Previews of a partial rip only cover the beginning of the title, so previews that were generated before
+/// the title was ripped are removed and generated again from the complete rip by the next preview request.
+/// Recording a new location of the same rip (e.g. after it was archived) keeps the previews.
+///
+/// # Arguments
+///
+/// * `preview_dir` - The directory the previews are cached in.
+/// * `fingerprint` - The fingerprint of the disc.
+/// * `title` - The ID of the ripped title.
+/// * `file` - The path of the complete rip.
+///
+/// # Errors
+///
+/// Returns an error if the preview directory of the title cannot be created or the record cannot be written.
+pub(crate) fn record_preview_source(preview_dir: &str, fingerprint: &str, title: usize, file: &str) -> std::io::Result<()> {
+    let title_dir = preview_title_dir(preview_dir, fingerprint, title);
+    let record_file = title_dir.join(PREVIEW_SOURCE_FILE_NAME);
+
+    if !record_file.exists() {
+        std::fs::remove_dir_all(&title_dir).ok();
+    }
+
+    std::fs::create_dir_all(&title_dir)?;
+    std::fs::write(record_file, file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_preview_source_replaces_partial_previews() {
+        let preview_dir = std::env::temp_dir().join(format!("ripper_previews_{}", std::process::id()));
+        let preview_dir = preview_dir.to_string_lossy().to_string();
+        let title_dir = preview_title_dir(&preview_dir, "0123456789abcdef", 2);
+
+        std::fs::create_dir_all(&title_dir).unwrap();
+        std::fs::write(title_dir.join("frame_0.jpg"), "partial").unwrap();
+
+        record_preview_source(&preview_dir, "0123456789abcdef", 2, "/output/job/title_t02.mkv").unwrap();
+
+        assert!(!title_dir.join("frame_0.jpg").exists());
+        assert_eq!(std::fs::read_to_string(title_dir.join(PREVIEW_SOURCE_FILE_NAME)).unwrap(), "/output/job/title_t02.mkv");
+
+        std::fs::write(title_dir.join("frame_0.jpg"), "complete").unwrap();
+        record_preview_source(&preview_dir, "0123456789abcdef", 2, "/archive/Movie/title_t02.mkv").unwrap();
+
+        assert!(title_dir.join("frame_0.jpg").exists());
+        assert_eq!(std::fs::read_to_string(title_dir.join(PREVIEW_SOURCE_FILE_NAME)).unwrap(), "/archive/Movie/title_t02.mkv");
+
+        std::fs::remove_dir_all(&preview_dir).ok();
+    }
+}
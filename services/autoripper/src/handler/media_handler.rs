@@ -1,9 +1,10 @@
 use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
 use axum_extra::extract::Query;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use tracing::error;
 
-use handbrake_core::get_encoding_profiles;
+use handbrake_core::{get_encoding_profiles, EncodingEstimate, SourceProperties};
 
 use crate::AppState;
 
@@ -12,6 +13,26 @@ pub struct MediaTypePayload {
     media_type: String,
 }
 
+#[derive(Deserialize)]
+pub struct EncodingEstimatePayload {
+    encoding_profile: String,
+    duration: f32,
+    width: u32,
+    height: u32,
+    bit_rate: u64,
+    media_type: Option<String>,
+    root_folder: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct EncodingEstimateResponse {
+    #[serde(flatten)]
+    estimate: EncodingEstimate,
+    free_space: Option<u64>,
+    insufficient_space: bool,
+    warnings: Vec<String>,
+}
+
 /// Handles requests to retrieve encoding profiles.
 ///
 /// This handler fetches the available encoding profiles using the `get_encoding_profiles`
@@ -81,3 +102,67 @@ pub async fn get_root_folder_handler(State(state): State<AppState>, Query(params
         _ => (StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid media type" }))).into_response(),
     }
 }
+
+/// Handles requests to estimate the output size and encoding time of a title.
+///
+/// The estimate is based on the duration, resolution and bitrate of the title and is calibrated
+/// with the history of completed encodes of the selected encoding profile on this machine. If a
+/// media type and root folder are given, the estimated size is compared to the free space of the
+/// root folder in Radarr (for movies) or Sonarr (for TV shows).
+///
+/// # Arguments
+///
+/// * `State(state)`: The application state containing the encoding history and the Radarr and Sonarr clients.
+/// * `Query(params)`: The query parameters containing the encoding profile and the title properties.
+///
+/// # Returns
+///
+/// An HTTP response with the estimate in JSON format, including warnings if the destination space is insufficient.
+///
+/// # Errors
+///
+/// This function will return an `INTERNAL_SERVER_ERROR` status code if the encoding history cannot be read.
+pub async fn get_encoding_estimate_handler(State(state): State<AppState>, Query(params): Query<EncodingEstimatePayload>) -> impl IntoResponse {
+    let source = SourceProperties { duration: params.duration, width: params.width, height: params.height, bit_rate: params.bit_rate };
+
+    let history = state.encoding_history.clone();
+    let profile = params.encoding_profile.clone();
+
+    let estimate = match tokio::task::spawn_blocking(move || history.estimate(&profile, &source)).await {
+        Ok(Ok(estimate)) => estimate,
+        Ok(Err(err)) => {
+            error!("Failed to estimate encoding: {}", err);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "failed to estimate encoding" }))).into_response();
+        }
+        Err(err) => {
+            error!("Estimation task failed: {:?}", err);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "failed to estimate encoding" }))).into_response();
+        }
+    };
+
+    let mut warnings = vec![];
+
+    if estimate.calibration_samples == 0 {
+        warnings.push("no completed encodes with this profile yet, the estimate is based on defaults".to_string());
+    }
+
+    let root_folders = match (params.media_type.as_deref(), &params.root_folder) {
+        (Some("movie"), Some(_)) => state.radarr_client.client.get_root_folders().await.ok(),
+        (Some("tv_show"), Some(_)) => state.sonarr_client.client.get_root_folders().await.ok(),
+        _ => None,
+    };
+
+    let free_space = root_folders.and_then(|folders| {
+        folders
+            .into_iter()
+            .find(|folder| Some(&folder.path) == params.root_folder.as_ref())
+            .map(|folder| folder.free_space)
+    });
+    let insufficient_space = free_space.is_some_and(|free_space| free_space < estimate.output_size);
+
+    if insufficient_space {
+        warnings.push(format!("the estimated output size of {} bytes exceeds the free space of {} bytes", estimate.output_size, free_space.unwrap()));
+    }
+
+    (StatusCode::OK, Json(EncodingEstimateResponse { estimate, free_space, insufficient_space, warnings })).into_response()
+}
@@ -1,16 +1,27 @@
 use axum::extract::{Path, State};
-use axum::{http::StatusCode, response::IntoResponse, Json};
+use axum::http::header;
+use axum::{http::StatusCode, response::IntoResponse, response::Response, Json};
 use axum_extra::extract::Query;
 use serde::Deserialize;
 use serde_json::json;
-use tracing::error;
-
-use crate::AppState;
+use tmdb_client::{MediaIds, SearchParams, TmdbClient, TmdbError};
+use tracing::{error, warn};
 
 #[derive(Deserialize)]
 pub struct SearchPayload {
     query: String,
     lang: String,
+    #[serde(default)]
+    page: Option<u32>,
+    #[serde(default)]
+    year: Option<u32>,
+}
+
+impl SearchPayload {
+    /// Returns the page and the year filter of the search, starting at the first page.
+    fn search_params(&self) -> SearchParams {
+        SearchParams { page: self.page.unwrap_or(1).max(1), year: self.year }
+    }
 }
 
 #[derive(Deserialize)]
@@ -22,19 +33,16 @@ pub struct DetailsPayload {
 ///
 /// # Arguments
 ///
-/// * `state` - The application state containing the TMDB client.
-/// * `payload` - The search payload containing the query and language.
+/// * `tmdb_client` - The TMDB client.
+/// * `payload` - The search payload containing the query, language and optionally the page and release year.
 ///
 /// # Returns
 ///
-/// A JSON response containing the search results or an error message.
-pub async fn search_movie_handler(State(state): State<AppState>, Query(params): Query<SearchPayload>) -> impl IntoResponse {
-    match state.tmdb_client.search_movies(&params.query, &params.lang).await {
+/// A JSON response containing the page of the search results or an error message.
+pub async fn search_movie_handler(State(tmdb_client): State<TmdbClient>, Query(params): Query<SearchPayload>) -> impl IntoResponse {
+    match tmdb_client.search_movies_with(&params.query, &params.lang, &params.search_params()).await {
         Ok(response) => (StatusCode::OK, Json(response)).into_response(),
-        Err(err) => {
-            error!("Failed to search movies: {}", err);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Internal Server Error" }))).into_response()
-        }
+        Err(err) => tmdb_error_response("Failed to search movies", err),
     }
 }
 
@@ -42,19 +50,16 @@ pub async fn search_movie_handler(State(state): State<AppState>, Query(params):
 ///
 /// # Arguments
 ///
-/// * `state` - The application state containing the TMDB client.
-/// * `payload` - The search payload containing the query and language.
+/// * `tmdb_client` - The TMDB client.
+/// * `payload` - The search payload containing the query, language and optionally the page and first air year.
 ///
 /// # Returns
 ///
-/// A JSON response containing the search results or an error message.
-pub async fn search_tv_show_handler(State(state): State<AppState>, Query(params): Query<SearchPayload>) -> impl IntoResponse {
-    match state.tmdb_client.search_tv_series(&params.query, &params.lang).await {
+/// A JSON response containing the page of the search results or an error message.
+pub async fn search_tv_show_handler(State(tmdb_client): State<TmdbClient>, Query(params): Query<SearchPayload>) -> impl IntoResponse {
+    match tmdb_client.search_tv_series_with(&params.query, &params.lang, &params.search_params()).await {
         Ok(response) => (StatusCode::OK, Json(response)).into_response(),
-        Err(err) => {
-            error!("Failed to search TV series: {}", err);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Internal Server Error" }))).into_response()
-        }
+        Err(err) => tmdb_error_response("Failed to search TV series", err),
     }
 }
 
@@ -62,19 +67,16 @@ pub async fn search_tv_show_handler(State(state): State<AppState>, Query(params)
 ///
 /// # Arguments
 ///
-/// * `state` - The application state containing the TMDB client.
+/// * `tmdb_client` - The TMDB client.
 /// * `payload` - The payload containing the TV series ID.
 ///
 /// # Returns
 ///
 /// A JSON response containing the TV series details or an error message.
-pub async fn get_tv_show_details_handler(State(state): State<AppState>, Path(id): Path<u32>, Query(params): Query<DetailsPayload>) -> impl IntoResponse {
-    match state.tmdb_client.get_tv_series(id, &params.lang).await {
+pub async fn get_tv_show_details_handler(State(tmdb_client): State<TmdbClient>, Path(id): Path<u32>, Query(params): Query<DetailsPayload>) -> impl IntoResponse {
+    match tmdb_client.get_tv_series(id, &params.lang).await {
         Ok(response) => (StatusCode::OK, Json(response)).into_response(),
-        Err(err) => {
-            error!("Failed to get TV series details: {}", err);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Internal Server Error" }))).into_response()
-        }
+        Err(err) => tmdb_error_response("Failed to get TV series details", err),
     }
 }
 
@@ -82,18 +84,259 @@ pub async fn get_tv_show_details_handler(State(state): State<AppState>, Path(id)
 ///
 /// # Arguments
 ///
-/// * `state` - The application state containing the TMDB client.
+/// * `tmdb_client` - The TMDB client.
 /// * `payload` - The payload containing the movie ID.
 ///
 /// # Returns
 ///
 /// A JSON response containing the movie details or an error message.
-pub async fn get_movie_details_handler(State(state): State<AppState>, Path(id): Path<u32>, Query(params): Query<DetailsPayload>) -> impl IntoResponse {
-    match state.tmdb_client.get_movie(id, &params.lang).await {
+pub async fn get_movie_details_handler(State(tmdb_client): State<TmdbClient>, Path(id): Path<u32>, Query(params): Query<DetailsPayload>) -> impl IntoResponse {
+    match tmdb_client.get_movie(id, &params.lang).await {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(err) => tmdb_error_response("Failed to get movie details", err),
+    }
+}
+
+/// Retrieves the episode groups of a TV show, e.g. its DVD or absolute ordering.
+///
+/// # Arguments
+///
+/// * `tmdb_client` - The TMDB client.
+/// * `id` - The TMDB ID of the TV series.
+///
+/// # Returns
+///
+/// A JSON response containing the episode groups or an error message.
+pub async fn get_tv_show_episode_groups_handler(State(tmdb_client): State<TmdbClient>, Path(id): Path<u32>) -> impl IntoResponse {
+    match tmdb_client.get_episode_groups(id).await {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(err) => tmdb_error_response("Failed to get episode groups", err),
+    }
+}
+
+/// Retrieves an episode group with its groups and the episodes they contain in order.
+///
+/// # Arguments
+///
+/// * `tmdb_client` - The TMDB client.
+/// * `id` - The TMDB ID of the episode group.
+/// * `payload` - The payload containing the language.
+///
+/// # Returns
+///
+/// A JSON response containing the episode group or an error message.
+pub async fn get_episode_group_handler(State(tmdb_client): State<TmdbClient>, Path(id): Path<String>, Query(params): Query<DetailsPayload>) -> impl IntoResponse {
+    match tmdb_client.get_episode_group(&id, &params.lang).await {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(err) => tmdb_error_response("Failed to get episode group", err),
+    }
+}
+
+/// Resolves the TMDB, IMDb and TVDB IDs of a movie from any of its TMDB or IMDb ID.
+///
+/// # Arguments
+///
+/// * `tmdb_client` - The TMDB client.
+/// * `params` - The known IDs of the movie.
+///
+/// # Returns
+///
+/// A JSON response containing the resolved IDs or an error message.
+pub async fn resolve_movie_ids_handler(State(tmdb_client): State<TmdbClient>, Query(params): Query<MediaIds>) -> impl IntoResponse {
+    match tmdb_client.resolve_movie_ids(params).await {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(err) => tmdb_error_response("Failed to resolve movie ids", err),
+    }
+}
+
+/// Resolves the TMDB, IMDb and TVDB IDs of a TV show from any of its TMDB, IMDb or TVDB ID.
+///
+/// # Arguments
+///
+/// * `tmdb_client` - The TMDB client.
+/// * `params` - The known IDs of the TV show.
+///
+/// # Returns
+///
+/// A JSON response containing the resolved IDs or an error message.
+pub async fn resolve_tv_show_ids_handler(State(tmdb_client): State<TmdbClient>, Query(params): Query<MediaIds>) -> impl IntoResponse {
+    match tmdb_client.resolve_tv_series_ids(params).await {
         Ok(response) => (StatusCode::OK, Json(response)).into_response(),
-        Err(err) => {
-            error!("Failed to get movie details: {}", err);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Internal Server Error" }))).into_response()
+        Err(err) => tmdb_error_response("Failed to resolve TV series ids", err),
+    }
+}
+
+/// Serves a TMDB image in the given size, so the UI can show artwork without access to the TMDB image CDN.
+///
+/// Images are downloaded once and served from the image directory afterwards. As images on TMDB never
+/// change, they are served with a long-lived cache header.
+///
+/// # Arguments
+///
+/// * `tmdb_client` - The TMDB client.
+/// * `size` - The size of the image, e.g. `w500` or `original`.
+/// * `file` - The file name of the image, i.e. its path without the leading slash.
+///
+/// # Returns
+///
+/// The image or an error message, with `404 Not Found` if TMDB has no image with the path.
+pub async fn get_image_handler(State(tmdb_client): State<TmdbClient>, Path((size, file)): Path<(String, String)>) -> impl IntoResponse {
+    match tmdb_client.get_image(&format!("/{}", file), &size).await {
+        Ok(image) => {
+            let content_type = match file.rsplit('.').next() {
+                Some("png") => "image/png",
+                Some("svg") => "image/svg+xml",
+                _ => "image/jpeg",
+            };
+
+            (StatusCode::OK, [(header::CONTENT_TYPE, content_type), (header::CACHE_CONTROL, "public, max-age=31536000, immutable")], image).into_response()
         }
+        Err(err) => tmdb_error_response("Failed to get image", err),
+    }
+}
+
+/// Removes all cached TMDB responses, so the next lookups are fetched from TMDB again.
+///
+/// # Arguments
+///
+/// * `tmdb_client` - The TMDB client.
+///
+/// # Returns
+///
+/// A JSON response containing the number of removed responses.
+pub async fn flush_cache_handler(State(tmdb_client): State<TmdbClient>) -> impl IntoResponse {
+    let flushed = tmdb_client.flush_cache().await;
+    (StatusCode::OK, Json(json!({ "flushed": flushed }))).into_response()
+}
+
+/// Answers a failed TMDB request with a status telling apart unknown media, invalid requests, rate limits
+/// and TMDB being unavailable, instead of a generic `500 Internal Server Error`.
+///
+/// | Error | Status |
+/// |-------|--------|
+/// | `NotFound` | `404 Not Found` |
+/// | `InvalidInput` | `400 Bad Request` |
+/// | `RateLimited` | `429 Too Many Requests`, with a `Retry-After` header if known |
+/// | `Unauthorized`, `Status`, `Network`, `Decode` | `502 Bad Gateway` |
+/// | `Io` | `500 Internal Server Error` |
+///
+/// # Arguments
+///
+/// * `action` - The description of the failed action, which is logged with the error.
+/// * `err` - The error of the TMDB client.
+///
+/// # Returns
+///
+/// A JSON response containing the error message.
+fn tmdb_error_response(action: &str, err: TmdbError) -> Response {
+    match &err {
+        TmdbError::NotFound { .. } | TmdbError::InvalidInput { .. } => warn!("{}: {}", action, err),
+        _ => error!("{}: {}", action, err),
+    }
+
+    let (status, message) = match &err {
+        TmdbError::NotFound { .. } => (StatusCode::NOT_FOUND, "Not found on TMDB".to_string()),
+        TmdbError::InvalidInput { message } => (StatusCode::BAD_REQUEST, message.clone()),
+        TmdbError::RateLimited { .. } => (StatusCode::TOO_MANY_REQUESTS, "Rate limited by TMDB, try again later".to_string()),
+        TmdbError::Unauthorized { .. } => (StatusCode::BAD_GATEWAY, "TMDB rejected the API key".to_string()),
+        TmdbError::Status { .. } | TmdbError::Network { .. } => (StatusCode::BAD_GATEWAY, "TMDB is unavailable".to_string()),
+        TmdbError::Decode { .. } => (StatusCode::BAD_GATEWAY, "TMDB returned an invalid response".to_string()),
+        TmdbError::Io { .. } => (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error".to_string()),
+    };
+
+    let mut response = (status, Json(json!({ "error": message }))).into_response();
+
+    if let TmdbError::RateLimited { retry_after: Some(retry_after) } = err {
+        response.headers_mut().insert(header::RETRY_AFTER, retry_after.as_secs().into());
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+    use serde_json::Value;
+    use tmdb_client::{mock, MockTmdbServer, RateLimitOptions};
+
+    use super::*;
+
+    async fn json_body(response: Response) -> Value {
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap()
+    }
+
+    fn details() -> Query<DetailsPayload> {
+        Query(DetailsPayload { lang: "en-US".to_string() })
+    }
+
+    #[tokio::test]
+    async fn searches_movies_with_page_and_year() {
+        let server = MockTmdbServer::start().await;
+        let params = SearchPayload { query: "Inception".to_string(), lang: "en-US".to_string(), page: Some(2), year: Some(2010) };
+
+        let response = search_movie_handler(State(server.client()), Query(params)).await.into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(json_body(response).await["results"][0]["id"], 27205);
+        assert_eq!(server.requests(), vec!["/3/search/movie?include_adult=false&query=Inception&language=en-US&page=2&year=2010"]);
+    }
+
+    #[tokio::test]
+    async fn answers_unknown_media_with_not_found() {
+        let server = MockTmdbServer::start().await;
+
+        let response = get_movie_details_handler(State(server.client()), Path(1), details()).await.into_response();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(json_body(response).await["error"], "Not found on TMDB");
+    }
+
+    #[tokio::test]
+    async fn answers_rate_limits_with_retry_after() {
+        let server = MockTmdbServer::start().await;
+        server.respond("/tv/67070", 429, "{}");
+
+        let response = get_tv_show_details_handler(State(server.client()), Path(67070), details()).await.into_response();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "0");
+    }
+
+    #[tokio::test]
+    async fn answers_unavailable_tmdb_with_bad_gateway() {
+        let server = MockTmdbServer::start().await;
+        server.respond("/movie/27205", 503, "{}");
+
+        let rate_limit = RateLimitOptions { initial_backoff_ms: 10, ..Default::default() };
+        let client = TmdbClient::builder(mock::MOCK_API_KEY)
+            .base_url(&server.base_url())
+            .rate_limit(rate_limit)
+            .build()
+            .unwrap();
+        let response = get_movie_details_handler(State(client), Path(27205), details()).await.into_response();
+
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(json_body(response).await["error"], "TMDB is unavailable");
+    }
+
+    #[tokio::test]
+    async fn answers_invalid_api_key_with_bad_gateway() {
+        let server = MockTmdbServer::start().await;
+        let client = TmdbClient::builder("invalid").base_url(&server.base_url()).build().unwrap();
+
+        let response = get_tv_show_episode_groups_handler(State(client), Path(67070)).await.into_response();
+
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(json_body(response).await["error"], "TMDB rejected the API key");
+    }
+
+    #[tokio::test]
+    async fn answers_missing_ids_with_bad_request() {
+        let server = MockTmdbServer::start().await;
+
+        let response = resolve_movie_ids_handler(State(server.client()), Query(MediaIds::default())).await.into_response();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(server.requests().is_empty());
     }
 }
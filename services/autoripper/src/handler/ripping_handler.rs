@@ -7,44 +7,103 @@ use axum_extra::extract::Query;
 use futures::stream::{SplitSink, SplitStream};
 use futures::{sink::SinkExt, stream::StreamExt};
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender};
 use std::sync::{mpsc, Arc};
-use std::{path::Path, thread};
+use std::thread;
+use tmdb_client::{Episode, ImageKind, MediaIds, Movie, TvSeason};
 use tokio::fs;
 use tokio::sync::Mutex;
 use tracing::{error, info};
-use utils::{upload_file_with_sftp, UploadProgressPayload};
+use utils::{move_file_with_progress, render_template, upload_file_with_sftp, UploadProgressPayload};
 
-use handbrake_core::{encode_files, get_encoding_profiles, EncodingProgressPayload, Profile};
+use handbrake_core::{
+    add_downmix_tracks, analyze_source, clean_partial_outputs, flag_forced_subtitles, get_encoding_profiles, ocr_subtitles, probe_file, remux_files,
+    select_downmix_tracks, verify_file, write_metadata, AudioTrack, ContainerMetadata, EncodingJob, EncodingProgressPayload, EncodingQueue, Profile, SourceAnalysis,
+    SourceProperties,
+};
 use makemkv_core::ProgressPayload;
 use makemkv_core::{read_disc_properties, rip_titles, Title};
 
-use crate::AppState;
+use crate::handler::disc_handler::record_preview_source;
+use crate::{AppState, HdrHandling};
+
+/// The version of a title that is remuxed instead of encoded, usable in place of an encoding profile.
+const REMUX_VERSION: &str = "remux";
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobMode {
+    /// Encodes the ripped titles with the selected encoding profile.
+    #[default]
+    Encode,
+    /// Keeps the ripped titles without encoding, optionally removing unwanted tracks.
+    Remux,
+    /// Encodes the ripped titles and additionally archives the raw rips.
+    KeepBoth,
+}
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct RipPayload {
     device: String,
     titles: Vec<usize>,
+    #[serde(default)]
     encoding_profile: String,
+    /// Multiple encoding profiles (or `remux`), each title is processed once per profile.
+    #[serde(default)]
+    encoding_profiles: Vec<String>,
     quality_profile: u32,
     root_folder: String,
     media_type: String,
     metadata: String,
+    #[serde(default)]
+    job_mode: JobMode,
+    #[serde(default)]
+    remux_langs: Vec<String>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct RipMovieMetadata {
-    pub tmdb_id: u32,
+    #[serde(default)]
+    pub tmdb_id: Option<u32>,
+    #[serde(default)]
+    pub imdb_id: Option<String>,
     pub title: String,
+    #[serde(default)]
+    pub year: Option<u32>,
+    #[serde(default)]
+    pub chapter_names: Vec<String>,
+    #[serde(default = "default_metadata_lang")]
+    pub lang: String,
 }
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct RipTvShowMetadata {
-    pub tvdb_id: u32,
+    #[serde(default)]
+    pub tvdb_id: Option<u32>,
+    #[serde(default)]
+    pub imdb_id: Option<String>,
+    #[serde(default)]
+    pub tmdb_id: Option<u32>,
     pub title: String,
     pub series_type: String,
     pub season: u32,
     pub episodes: Vec<u32>,
+    /// The episode group (e.g. the DVD order) the season and episodes refer to, the aired order if unset.
+    #[serde(default)]
+    pub episode_group: Option<String>,
+    #[serde(default)]
+    pub year: Option<u32>,
+    #[serde(default)]
+    pub chapter_names: Vec<String>,
+    #[serde(default = "default_metadata_lang")]
+    pub lang: String,
+}
+
+fn default_metadata_lang() -> String {
+    "de".to_string()
 }
 
 struct RippingHandler {
@@ -53,13 +112,28 @@ struct RippingHandler {
     params: RipPayload,
     titles: Vec<Title>,
     profiles: Vec<Profile>,
+    disc_type: String,
+    versions: Vec<String>,
+    job_dir: String,
+    /// The fingerprint of the disc, which identifies the previews of its titles.
+    fingerprint: String,
+    ids: MediaIds,
+    /// The aired season and episode number of every title of a TV show, in the order of the titles.
+    episodes: Vec<(u16, u16)>,
 }
 
 impl RippingHandler {
     /// Creates a new instance of `RippingHandler`.
     ///
     /// This function initializes the `RippingHandler` by reading encoding profiles,
-    /// disc properties, and selecting the titles to be processed.
+    /// disc properties, and selecting the titles to be processed. Every job gets its own
+    /// directory inside the output directory, so multiple discs can be processed at once.
+    /// The directory is named after the disc, the encoding profiles and the selected titles,
+    /// so a job that is started again after a crash or restart resumes in the same directory.
+    /// Partial outputs of interrupted encodes are removed from it.
+    ///
+    /// Every title is processed once per version of the job, which are the selected encoding
+    /// profiles, or a single remux version for remux jobs.
     ///
     /// # Arguments
     ///
@@ -82,81 +156,323 @@ impl RippingHandler {
             .map(|title| disc.titles.iter().find(|t| t.id == *title).unwrap().to_owned())
             .collect::<Vec<Title>>();
 
-        Self { state, params, titles, profiles, cancel_flag: Arc::new(AtomicBool::new(false)) }
+        let disc_name = if disc.volume_name.is_empty() { &disc.name } else { &disc.volume_name };
+        let title_ids = params.titles.iter().map(|id| id.to_string()).collect::<Vec<String>>().join("-");
+
+        let profile_ids = if params.encoding_profiles.is_empty() { params.encoding_profile.clone() } else { params.encoding_profiles.join("+") };
+
+        let job_id = format!("{}_{}_{}", disc_name, profile_ids, title_ids)
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+            .collect::<String>();
+
+        let job_dir = Path::new(&state.output_dir).join(job_id).to_string_lossy().to_string();
+        std::fs::create_dir_all(&job_dir).context("failed to create job directory").unwrap();
+
+        if let Ok(removed) = clean_partial_outputs(&job_dir) {
+            if removed > 0 {
+                info!("resuming job {}, removed {} partial outputs", job_dir, removed);
+            }
+        }
+
+        let versions = job_versions(&params);
+
+        Self {
+            state,
+            params,
+            titles,
+            profiles,
+            fingerprint: disc.fingerprint(),
+            disc_type: disc.disc_type,
+            versions,
+            job_dir,
+            ids: MediaIds::default(),
+            episodes: vec![],
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+        }
     }
 
     /// Handles cancellation of the ripping process via WebSocket.
     ///
     /// This function listens for "cancel" messages over the WebSocket connection and
-    /// sets the cancellation flag. It also removes any partially processed files upon
-    /// cancellation.
+    /// sets the cancellation flag. It also removes the job directory with any partially
+    /// processed files upon cancellation.
     ///
     /// # Arguments
     ///
     /// * `socket_receiver` - A stream of incoming WebSocket messages.
     pub async fn handle_cancellation(&self, mut socket_receiver: SplitStream<WebSocket>) {
         let cancel_flag = self.cancel_flag.clone();
-        let titles = self.titles.clone();
-        let output_dir = self.state.output_dir.clone();
+        let job_dir = self.job_dir.clone();
 
         tokio::spawn(async move {
             while let Some(msg) = socket_receiver.next().await {
                 if let Ok(Message::Text(text)) = msg {
                     if text.trim() == "cancel" {
                         cancel_flag.store(true, Ordering::Relaxed);
+                        fs::remove_dir_all(&job_dir).await.ok();
+                    }
+                }
+            }
+        });
+    }
 
-                        for title in &titles {
-                            let rip_file = Path::new(&output_dir).join(&title.output_file_name);
+    /// Resolves the TMDB, IMDb and TVDB IDs of the ripped media from the IDs provided in the metadata.
+    ///
+    /// Users may identify a movie by its TMDB or IMDb ID and a TV show by its TMDB, IMDb or TVDB ID.
+    /// The missing IDs are looked up on TMDB, so Radarr always gets the TMDB ID and Sonarr the TVDB ID,
+    /// and the IDs are available to the naming templates. The episodes of a TV show are resolved to
+    /// their aired season and episode numbers as well (see `aired_episodes`). If the IDs or episodes
+    /// cannot be resolved, the job is refused before anything is ripped.
+    ///
+    /// # Arguments
+    ///
+    /// * `socket_sender` - A mutable reference to the WebSocket sender for sending messages.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the IDs were resolved, `false` if the job was refused.
+    pub async fn resolve_ids(&mut self, socket_sender: &mut SplitSink<WebSocket, Message>) -> bool {
+        let result = if self.params.media_type == "tv_show" {
+            let metadata = serde_json::from_str::<RipTvShowMetadata>(&self.params.metadata).unwrap();
+            let ids = MediaIds { tmdb_id: metadata.tmdb_id, imdb_id: metadata.imdb_id.clone(), tvdb_id: metadata.tvdb_id };
+            let ids = self
+                .state
+                .tmdb_client
+                .resolve_tv_series_ids(ids)
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|ids| match ids.tvdb_id {
+                    Some(_) => Ok(ids),
+                    None => Err(anyhow::anyhow!("TV show {} has no TVDB ID", ids.tmdb_id.unwrap_or_default())),
+                });
 
-                            if rip_file.exists() {
-                                fs::remove_file(rip_file).await.unwrap();
-                            }
+            match self.aired_episodes(&metadata).await {
+                Ok(episodes) => {
+                    self.episodes = episodes;
+                    ids
+                }
+                Err(e) => Err(e),
+            }
+        } else {
+            let metadata = serde_json::from_str::<RipMovieMetadata>(&self.params.metadata).unwrap();
+            let ids = MediaIds { tmdb_id: metadata.tmdb_id, imdb_id: metadata.imdb_id, tvdb_id: None };
+            self.state.tmdb_client.resolve_movie_ids(ids).await.map_err(anyhow::Error::from)
+        };
 
-                            let encoded_file = Path::new(&output_dir).join("/encoding/").join(&title.output_file_name);
+        match result {
+            Ok(ids) => {
+                info!("resolved ids of job {}: {:?}", self.job_dir, ids);
+                self.ids = ids;
+                true
+            }
+            Err(e) => {
+                error!("refusing job {}, failed to resolve ids: {:?}", self.job_dir, e);
 
-                            if encoded_file.exists() {
-                                fs::remove_file(encoded_file).await.unwrap();
-                            }
-                        }
-                    }
+                let message = serde_json::json!({ "type": "job_refused", "payload": { "label": format!("could not resolve the IDs of the media: {}", e), "progress": 1, "step": 0, "eta": 0 } });
+
+                if let Err(e) = socket_sender.send(Message::Text(message.to_string())).await {
+                    error!("Failed to send WebSocket message: {:?}", e);
                 }
+
+                false
             }
-        });
+        }
+    }
+
+    /// Checks whether the selected encoding profiles keep the dynamic range of the selected titles.
+    ///
+    /// HDR and Dolby Vision titles can only be encoded with profiles that declare to preserve them.
+    /// Otherwise, depending on the configured HDR handling, the incompatible versions are either
+    /// replaced by a remux or the job is refused before anything is ripped.
+    ///
+    /// # Arguments
+    ///
+    /// * `socket_sender` - A mutable reference to the WebSocket sender for sending messages.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the job can continue, `false` if the job is refused.
+    pub async fn check_dynamic_range(&mut self, socket_sender: &mut SplitSink<WebSocket, Message>) -> bool {
+        let mut versions: Vec<String> = vec![];
+        let mut reasons = vec![];
+
+        for version in self.versions.iter() {
+            let Some(profile) = self.profiles.iter().find(|p| &p.id == version) else {
+                if !versions.contains(version) {
+                    versions.push(version.clone());
+                }
+                continue;
+            };
+
+            let incompatible = self
+                .titles
+                .iter()
+                .filter(|title| !profile.supports_dynamic_range(title.video_stream.is_hdr(), title.video_stream.dolby_vision))
+                .map(|title| title.output_file_name.clone())
+                .collect::<Vec<String>>();
+
+            let version = if incompatible.is_empty() {
+                version.clone()
+            } else if self.state.hdr_handling == HdrHandling::Remux {
+                info!("remuxing job {}, profile {} doesn't preserve the dynamic range of: {:?}", self.job_dir, profile.id, incompatible);
+                REMUX_VERSION.to_string()
+            } else {
+                error!("refusing job {}, profile {} doesn't preserve the dynamic range of: {:?}", self.job_dir, profile.id, incompatible);
+                reasons.push(format!("{} doesn't preserve HDR or Dolby Vision of {}", profile.label, incompatible.join(", ")));
+                continue;
+            };
+
+            if !versions.contains(&version) {
+                versions.push(version);
+            }
+        }
+
+        if reasons.is_empty() {
+            self.versions = versions;
+            return true;
+        }
+
+        let message = serde_json::json!({ "type": "job_refused", "payload": { "label": reasons.join("; "), "progress": 1, "step": 0, "eta": 0 } });
+
+        if let Err(e) = socket_sender.send(Message::Text(message.to_string())).await {
+            error!("Failed to send WebSocket message: {:?}", e);
+        }
+
+        false
+    }
+
+    /// Returns the versions of the job that are encoded, in the order they were selected.
+    fn encode_versions(&self) -> Vec<&str> {
+        self.versions
+            .iter()
+            .map(|version| version.as_str())
+            .filter(|&version| version != REMUX_VERSION)
+            .collect()
+    }
+
+    /// Returns the directory the outputs of a version are written to (inside its `encoding` directory).
+    ///
+    /// Jobs with a single version use the job directory itself, jobs with multiple versions use
+    /// a directory per version, since all versions of a title share the same file name.
+    fn version_dir(&self, version: &str) -> String {
+        if self.versions.len() == 1 {
+            return self.job_dir.clone();
+        }
+
+        Path::new(&self.job_dir).join("versions").join(version).to_string_lossy().to_string()
+    }
+
+    /// Returns the path of the raw rip of a title.
+    fn source_file(&self, title: &Title) -> String {
+        Path::new(&self.job_dir).join(&title.output_file_name).to_string_lossy().to_string()
+    }
+
+    /// Returns the path of the record of the source analysis of a title.
+    fn analysis_file(&self, title: &Title) -> PathBuf {
+        Path::new(&self.job_dir).join(format!("{}.analysis.json", title.output_file_name))
+    }
+
+    /// Returns the path of the processed output of a title in the given version.
+    fn output_file(&self, version: &str, title: &Title) -> String {
+        Path::new(&self.version_dir(version))
+            .join("encoding/")
+            .join(&title.output_file_name)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Returns the processed outputs of all versions, as version, index of the title and path of the output.
+    fn outputs(&self) -> Vec<(&str, usize, String)> {
+        self.versions
+            .iter()
+            .flat_map(|version| {
+                self.titles
+                    .iter()
+                    .enumerate()
+                    .map(move |(i, title)| (version.as_str(), i, self.output_file(version, title)))
+            })
+            .collect()
+    }
+
+    /// Checks whether the processed outputs of a title already exist in all versions of the job.
+    ///
+    /// Outputs are only moved to their final name once they are complete, so an existing
+    /// output belongs to a title that was processed before the job was interrupted.
+    fn is_completed(&self, step: usize) -> bool {
+        self.titles
+            .get(step)
+            .is_some_and(|title| self.versions.iter().all(|version| Path::new(&self.output_file(version, title)).exists()))
     }
 
     /// Rips the selected titles from the disc.
     ///
     /// This function spawns a new thread to handle the ripping process and sends
-    /// progress updates over the WebSocket connection.
+    /// progress updates over the WebSocket connection. As soon as a title is ripped,
+    /// it is added to the encoding queue once per encoded version, so encoding starts
+    /// while the remaining titles are still being ripped. Titles that were already processed in a previous
+    /// run of the job are skipped.
     ///
     /// # Arguments
     ///
     /// * `socket_sender` - A mutable reference to the WebSocket sender for sending messages.
-    pub async fn rip_titles(&self, socket_sender: &mut SplitSink<WebSocket, Message>) {
+    /// * `encoding_sender` - The channel the encoding jobs of the ripped titles report their progress to.
+    ///
+    /// # Returns
+    ///
+    /// The number of encoding jobs that were added to the encoding queue.
+    pub async fn rip_titles(
+        &self, socket_sender: &mut SplitSink<WebSocket, Message>, encoding_sender: &Sender<(&'static str, Option<EncodingProgressPayload>)>,
+    ) -> usize {
         let (rip_sender, rip_receiver) = mpsc::channel::<(&str, Option<ProgressPayload>)>();
 
         let command = self.state.makemkv_command.clone();
         let makemkv_mutex = self.state.makemkv_mutex.clone();
-        let output_dir = self.state.output_dir.clone();
+        let output_dir = self.job_dir.clone();
         let device = self.params.device.clone();
-        let titles = self.params.titles.clone();
         let cancel_flag = self.cancel_flag.clone();
 
+        let pending = (0..self.titles.len()).filter(|&step| !self.is_completed(step)).collect::<Vec<usize>>();
+        let titles = pending.iter().map(|&step| self.titles[step].id).collect::<Vec<usize>>();
+
+        if pending.len() < self.titles.len() {
+            info!("skipping {} titles that are already processed", self.titles.len() - pending.len());
+        }
+
         thread::spawn(move || {
             if let Err(e) = rip_titles(&command, &makemkv_mutex, cancel_flag, rip_sender, &output_dir, &device, &titles) {
                 error!("failed to rip titles: {:?}", e);
             }
         });
 
+        let mut enqueued = 0;
+
         while let Ok((event_type, payload)) = rip_receiver.recv() {
             let message = match event_type {
                 "progress" => {
                     let payload = payload.unwrap();
                     format!(
                         r#"{{ "type": "ripping_progress", "payload": {{ "label": "{}", "progress": {}, "step": {}, "eta": {} }} }}"#,
-                        payload.step_details, payload.progress, payload.step, payload.eta
+                        payload.step_details, payload.progress, pending[payload.step], payload.eta
                     )
                 }
+                "ripped" => {
+                    let step = pending[payload.unwrap().step];
+                    let title = &self.titles[step];
+
+                    if let Err(e) = record_preview_source(&self.state.preview_dir, &self.fingerprint, title.id, &self.source_file(title)) {
+                        error!("failed to record ripped title as preview source: {:?}", e);
+                    }
+
+                    if !self.encode_versions().is_empty() {
+                        match self.enqueue_encoding(step, encoding_sender) {
+                            Ok(jobs) => enqueued += jobs,
+                            Err(e) => error!("failed to enqueue title for encoding: {:?}", e),
+                        }
+                    }
+
+                    continue;
+                }
                 "done" => r#"{"type": "ripping_done"}"#.to_string(),
                 _ => continue,
             };
@@ -166,48 +482,190 @@ impl RippingHandler {
                 break;
             }
         }
+
+        enqueued
     }
 
-    /// Encodes the ripped files using the specified encoding profile.
+    /// Adds a ripped title to the shared encoding queue, once per encoded version.
     ///
-    /// This function spawns a new thread to handle the encoding process and sends
-    /// progress updates over the WebSocket connection.
+    /// If source analysis is enabled, the title is analyzed first and the crop and deinterlace
+    /// decisions are passed to the encoder as overrides on top of the encoding profiles.
+    /// The title is only analyzed once, regardless of the number of versions. The decisions are
+    /// recorded in `<title>.analysis.json` in the job directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - The index of the title within the selected titles.
+    /// * `encoding_sender` - The channel the encoding jobs report their progress to.
+    ///
+    /// # Returns
+    ///
+    /// The number of encoding jobs of the title. The step of a job is the index of the title,
+    /// offset by the number of titles for every preceding version.
+    fn enqueue_encoding(&self, step: usize, encoding_sender: &Sender<(&'static str, Option<EncodingProgressPayload>)>) -> anyhow::Result<usize> {
+        let title = self.titles.get(step).context("ripped title not found")?;
+
+        let mut jobs = vec![];
+
+        for (i, version) in self.encode_versions().into_iter().enumerate() {
+            let profile = self.profiles.iter().find(|p| p.id == version).context("encoding profile not found")?;
+
+            jobs.push(EncodingJob {
+                profile: profile.clone(),
+                input: self.source_file(title),
+                output_dir: self.version_dir(version),
+                step: i * self.titles.len() + step,
+                source: SourceProperties::from_title(title.duration, &title.video_stream.video_size, title.disk_size_bytes),
+                analysis: None,
+                cancel_flag: self.cancel_flag.clone(),
+                sender: encoding_sender.clone(),
+            });
+        }
+
+        let count = jobs.len();
+        let queue = self.state.encoding_queue.clone();
+
+        if !self.state.analysis_options.enabled {
+            enqueue_jobs(&queue, jobs);
+            return Ok(count);
+        }
+
+        // the analysis decodes parts of the title, so it runs in the background to keep the ripping progress flowing
+        let ffmpeg_command = self.state.ffmpeg_command.clone();
+        let ffprobe_command = self.state.ffprobe_command.clone();
+        let options = self.state.analysis_options.clone();
+        let input = self.source_file(title);
+        let record_file = self.analysis_file(title);
+
+        std::thread::spawn(move || {
+            // the guard enqueues the jobs when the thread ends, even if the analysis panics, so `encode_files` never waits for them forever
+            let mut guard = EnqueueGuard { queue, jobs };
+
+            match analyze_source(&ffmpeg_command, &ffprobe_command, &input, &options) {
+                Ok(analysis) => {
+                    if let Err(e) = std::fs::write(&record_file, serde_json::to_string_pretty(&analysis).unwrap()) {
+                        error!("failed to write analysis record of title {}: {:?}", input, e);
+                    }
+
+                    guard.jobs.iter_mut().for_each(|job| job.analysis = Some(analysis.clone()));
+                }
+                Err(e) => error!("failed to analyze title {}, encoding with profile settings: {:?}", input, e),
+            }
+        });
+
+        Ok(count)
+    }
+
+    /// Waits for the encoding jobs of the ripped titles to finish.
+    ///
+    /// The encoding jobs run concurrently in the shared encoding queue. This function
+    /// combines the progress updates of all titles and versions into a single progress,
+    /// which is sent over the WebSocket connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `socket_sender` - A mutable reference to the WebSocket sender for sending messages.
+    /// * `encoding_receiver` - The channel the encoding jobs report their progress to.
+    /// * `jobs` - The number of encoding jobs that were enqueued.
+    pub async fn encode_files(
+        &self, socket_sender: &mut SplitSink<WebSocket, Message>, encoding_receiver: Receiver<(&'static str, Option<EncodingProgressPayload>)>, jobs: usize,
+    ) {
+        let mut progress = self
+            .encode_versions()
+            .into_iter()
+            .flat_map(|version| {
+                self.titles
+                    .iter()
+                    .map(move |title| if Path::new(&self.output_file(version, title)).exists() { 1.0 } else { 0.0 })
+            })
+            .collect::<Vec<f32>>();
+        let mut etas = vec![0.0; progress.len()];
+        let mut finished = 0;
+
+        while finished < jobs {
+            let Ok((event_type, payload)) = encoding_receiver.recv() else { break };
+            let payload = payload.unwrap();
+
+            match event_type {
+                "progress" => {
+                    progress[payload.step] = payload.progress;
+                    etas[payload.step] = payload.eta;
+                }
+                "encoded" | "failed" | "cancelled" => {
+                    progress[payload.step] = 1.0;
+                    etas[payload.step] = 0.0;
+                    finished += 1;
+                }
+                _ => continue,
+            }
+
+            if self.cancel_flag.load(Ordering::Relaxed) {
+                return;
+            }
+
+            let message = format!(
+                r#"{{ "type": "encoding_progress", "payload": {{ "label": "{}", "progress": {}, "step": {}, "eta": {} }} }}"#,
+                "Encoding",
+                progress.iter().sum::<f32>() / progress.len() as f32,
+                finished.min(progress.len().saturating_sub(1)),
+                etas.iter().cloned().fold(0.0, f32::max)
+            );
+
+            if let Err(e) = socket_sender.send(Message::Text(message)).await {
+                error!("Failed to send WebSocket message: {:?}", e);
+                return;
+            }
+        }
+
+        if let Err(e) = socket_sender.send(Message::Text(r#"{"type": "encoding_done"}"#.to_string())).await {
+            error!("Failed to send WebSocket message: {:?}", e);
+        }
+    }
+
+    /// Remuxes the ripped files instead of encoding them.
+    ///
+    /// This function spawns a new thread to handle the remuxing process and sends
+    /// progress updates over the WebSocket connection. The progress is reported as
+    /// the encoding stage, so clients don't have to distinguish between both modes.
+    /// If no languages are selected, the ripped files are kept untouched: they are moved
+    /// into place, unless other versions are encoded from them or they are archived
+    /// afterwards, in which case all tracks are remuxed into a copy. If the job
+    /// encodes other versions as well, the encoding stage is only completed by them.
     ///
     /// # Arguments
     ///
     /// * `socket_sender` - A mutable reference to the WebSocket sender for sending messages.
-    pub async fn encode_files(&self, socket_sender: &mut SplitSink<WebSocket, Message>) {
-        let (encoding_sender, encoding_receiver) = mpsc::channel::<(&str, Option<EncodingProgressPayload>)>();
+    /// * `version` - The version the remuxed files are written to.
+    pub async fn remux_files(&self, socket_sender: &mut SplitSink<WebSocket, Message>, version: &str) {
+        let (remux_sender, remux_receiver) = mpsc::channel::<(&'static str, Option<EncodingProgressPayload>)>();
 
         let cancel_flag = self.cancel_flag.clone();
-        let command = self.state.handbrake_command.clone();
-        let output_dir = self.state.output_dir.clone();
-        let profile = self.profiles.iter().find(|p| p.id == self.params.encoding_profile).unwrap().clone();
+        let command = self.state.mkvmerge_command.clone();
+        let output_dir = self.version_dir(version);
+        let langs = self.params.remux_langs.clone();
+        let keep_files = !self.encode_versions().is_empty() || self.params.job_mode == JobMode::KeepBoth;
 
-        let files: Vec<String> = self
-            .titles
-            .iter()
-            .map(|title| Path::new(&self.state.output_dir).join(&title.output_file_name).to_string_lossy().to_string())
-            .collect();
+        let files: Vec<String> = self.titles.iter().map(|title| self.source_file(title)).collect();
 
         thread::spawn(move || {
             let files = files.iter().map(|f| f.as_str()).collect::<Vec<&str>>();
+            let langs = langs.iter().map(|l| l.as_str()).collect::<Vec<&str>>();
 
-            if let Err(e) = encode_files(&command, &profile, &files, &output_dir, cancel_flag, encoding_sender) {
-                error!("failed to encode titles: {:?}", e);
+            if let Err(e) = remux_files(&command, &files, &output_dir, &langs, keep_files, cancel_flag, remux_sender) {
+                error!("failed to remux titles: {:?}", e);
             }
         });
 
-        while let Ok((event_type, payload)) = encoding_receiver.recv() {
+        while let Ok((event_type, payload)) = remux_receiver.recv() {
             let message = match event_type {
                 "progress" => {
                     let payload = payload.unwrap();
                     format!(
                         r#"{{ "type": "encoding_progress", "payload": {{ "label": "{}", "progress": {}, "step": {}, "eta": {} }} }}"#,
-                        "Encoding", payload.progress, payload.step, payload.eta
+                        "Remuxing", payload.progress, payload.step, payload.eta
                     )
                 }
-                "done" => r#"{"type": "encoding_done"}"#.to_string(),
+                "done" if self.encode_versions().is_empty() => r#"{"type": "encoding_done"}"#.to_string(),
                 _ => continue,
             };
 
@@ -218,10 +676,602 @@ impl RippingHandler {
         }
     }
 
+    /// Adds stereo downmix tracks of the surround audio tracks to the processed files.
+    ///
+    /// The tracks to downmix are selected per language from the audio streams MakeMKV reported
+    /// for the title, based on their channel count and codec. The downmixes are created from the
+    /// raw rips, or from the processed files if the raw rips were moved.
+    pub async fn downmix_audio(&self) {
+        for (_, i, output) in self.outputs() {
+            let title = &self.titles[i];
+            let output = Path::new(&output).to_path_buf();
+            let mut source = Path::new(&self.source_file(title)).to_path_buf();
+
+            if !source.exists() {
+                source = output.clone();
+            }
+
+            let audio_tracks = title
+                .audio_streams
+                .iter()
+                .map(|stream| AudioTrack { lang: stream.lang_code.clone(), channels: stream.audio_channels_count.max(0) as u8, codec: stream.codec_short.clone() })
+                .collect::<Vec<AudioTrack>>();
+
+            let tracks = select_downmix_tracks(&audio_tracks, &self.state.downmix_options);
+            if tracks.is_empty() {
+                continue;
+            }
+
+            let source = source.to_string_lossy().to_string();
+            let output = output.to_string_lossy().to_string();
+            let ffmpeg_command = self.state.ffmpeg_command.clone();
+            let ffprobe_command = self.state.ffprobe_command.clone();
+            let mkvmerge_command = self.state.mkvmerge_command.clone();
+            let options = self.state.downmix_options.clone();
+
+            let downmix_handle =
+                tokio::task::spawn_blocking(move || add_downmix_tracks(&ffmpeg_command, &ffprobe_command, &mkvmerge_command, &source, &output, &tracks, &options));
+
+            match downmix_handle.await {
+                Ok(Ok(added)) => info!("added {} stereo downmix tracks to {}", added, title.output_file_name),
+                Ok(Err(e)) => error!("failed to add stereo downmix tracks: {:?}", e),
+                Err(e) => error!("Downmix task failed: {:?}", e),
+            }
+        }
+    }
+
+    /// Converts the image based subtitles of the ripped titles to SRT subtitles.
+    ///
+    /// The subtitles are read from the raw rips (or the remuxed files, if the raw rips were
+    /// moved) and either placed beside the processed files or muxed into them, depending on
+    /// the configured output. Progress updates are sent over the WebSocket connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `socket_sender` - A mutable reference to the WebSocket sender for sending messages.
+    pub async fn ocr_subtitles(&self, socket_sender: &mut SplitSink<WebSocket, Message>) {
+        let outputs = self.outputs();
+
+        for (i, (_, title_index, output)) in outputs.iter().enumerate() {
+            let title = &self.titles[*title_index];
+            let output = Path::new(output).to_path_buf();
+            let mut source = Path::new(&self.source_file(title)).to_path_buf();
+
+            if !source.exists() {
+                source = output.clone();
+            }
+
+            let source = source.to_string_lossy().to_string();
+            let output = output.to_string_lossy().to_string();
+            let ffprobe_command = self.state.ffprobe_command.clone();
+            let mkvmerge_command = self.state.mkvmerge_command.clone();
+            let options = self.state.subtitle_ocr_options.clone();
+
+            let ocr_handle = tokio::task::spawn_blocking(move || ocr_subtitles(&ffprobe_command, &mkvmerge_command, &source, &output, &options));
+
+            match ocr_handle.await {
+                Ok(Ok(files)) => info!("converted subtitles of {}: {:?}", title.output_file_name, files),
+                Ok(Err(e)) => error!("failed to convert subtitles: {:?}", e),
+                Err(e) => error!("Subtitle task failed: {:?}", e),
+            }
+
+            let message = format!(
+                r#"{{ "type": "subtitle_progress", "payload": {{ "label": "{}", "progress": {}, "step": {}, "eta": {} }} }}"#,
+                "Converting subtitles",
+                (i + 1) as f32 / outputs.len() as f32,
+                i,
+                0
+            );
+
+            if let Err(e) = socket_sender.send(Message::Text(message)).await {
+                error!("Failed to send WebSocket message: {:?}", e);
+            }
+        }
+    }
+
+    /// Flags the forced subtitle tracks of the processed files.
+    ///
+    /// Forced tracks are detected in the raw rips, using the forced streams reported by
+    /// MakeMKV as hints, and get the forced and default flags in the processed files, so
+    /// players show them automatically. If the raw rip was moved, the processed file is
+    /// used as source.
+    pub async fn flag_subtitles(&self) {
+        for (_, i, output) in self.outputs() {
+            let title = &self.titles[i];
+            let output = Path::new(&output).to_path_buf();
+            let mut source = Path::new(&self.source_file(title)).to_path_buf();
+
+            if !source.exists() {
+                source = output.clone();
+            }
+
+            let source = source.to_string_lossy().to_string();
+            let output = output.to_string_lossy().to_string();
+            let command = self.state.mkvpropedit_command.clone();
+            let ffprobe_command = self.state.ffprobe_command.clone();
+
+            let hints = title
+                .subtitle_streams
+                .iter()
+                .enumerate()
+                .filter(|(_, stream)| stream.forced)
+                .map(|(position, _)| position)
+                .collect::<Vec<usize>>();
+
+            let flag_handle = tokio::task::spawn_blocking(move || flag_forced_subtitles(&command, &ffprobe_command, &source, &output, &hints));
+
+            match flag_handle.await {
+                Ok(Ok(flagged)) => info!("flagged {} forced subtitle tracks of {}", flagged, title.output_file_name),
+                Ok(Err(e)) => error!("failed to flag forced subtitles: {:?}", e),
+                Err(e) => error!("Flagging task failed: {:?}", e),
+            }
+        }
+    }
+
+    /// Returns the aired season and episode number of every ripped episode.
+    ///
+    /// Without an episode group, the season and episodes of the metadata are the aired ones. Otherwise they are
+    /// positions within the episode group (e.g. the DVD order), which are translated to the aired numbers TMDB
+    /// lists for the grouped episodes, so naming, tagging and Sonarr use the numbering they know. The episodes of
+    /// a group may belong to different aired seasons, e.g. if a DVD contains a special.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the episode group cannot be fetched or doesn't contain the season or an episode.
+    async fn aired_episodes(&self, metadata: &RipTvShowMetadata) -> anyhow::Result<Vec<(u16, u16)>> {
+        let Some(group_id) = &metadata.episode_group else {
+            return Ok(metadata.episodes.iter().map(|&episode| (metadata.season as u16, episode as u16)).collect());
+        };
+
+        let group = self.state.tmdb_client.get_episode_group(group_id, &metadata.lang).await?;
+        let season = group
+            .season(metadata.season as u16)
+            .with_context(|| format!("episode group {} has no season {}", group_id, metadata.season))?;
+
+        metadata
+            .episodes
+            .iter()
+            .map(|&number| {
+                season
+                    .episode(number as u16)
+                    .map(|episode| (episode.season_number, episode.episode_number))
+                    .with_context(|| format!("season {} of episode group {} has no episode {}", metadata.season, group_id, number))
+            })
+            .collect()
+    }
+
+    /// Fetches the TMDB episode of every ripped title, if the TMDB ID of the TV show is known.
+    ///
+    /// The episodes are looked up by their aired season and episode number, every aired season is fetched once.
+    async fn fetch_episodes(&self, metadata: &RipTvShowMetadata) -> Vec<Option<Episode>> {
+        let mut seasons: HashMap<u16, TvSeason> = HashMap::new();
+
+        if let Some(tmdb_id) = self.ids.tmdb_id {
+            for &(season_number, _) in &self.episodes {
+                if seasons.contains_key(&season_number) {
+                    continue;
+                }
+
+                match self.state.tmdb_client.get_season_details(tmdb_id, season_number, &metadata.lang).await {
+                    Ok(season) => {
+                        seasons.insert(season_number, season);
+                    }
+                    Err(e) => error!("failed to fetch season {} of tv show {}: {:?}", season_number, tmdb_id, e),
+                }
+            }
+        }
+
+        self.episodes
+            .iter()
+            .map(|(season_number, episode_number)| {
+                seasons
+                    .get(season_number)
+                    .and_then(|season| season.episodes.iter().find(|episode| episode.episode_number == *episode_number).cloned())
+            })
+            .collect()
+    }
+
+    /// Fetches the details of the ripped movie from TMDB, including its release dates and alternative titles.
+    async fn fetch_movie(&self, metadata: &RipMovieMetadata) -> Option<Movie> {
+        let tmdb_id = self.ids.tmdb_id?;
+
+        match self.state.tmdb_client.get_movie(tmdb_id, &metadata.lang).await {
+            Ok(movie) => Some(movie),
+            Err(e) => {
+                error!("failed to fetch movie {}: {:?}", tmdb_id, e);
+                None
+            }
+        }
+    }
+
+    /// Renders the names the processed files are uploaded with, using the naming template of the media type.
+    ///
+    /// The names don't contain the file extension. The resolution and video codec are read from the
+    /// processed files, the source type from the disc type and the episode titles, IMDb IDs, original
+    /// titles, collections and certifications are fetched from TMDB. The certification is the one of the
+    /// country of the metadata language (e.g. `de-DE` or `de` for Germany), or of the US if there is none.
+    /// Jobs with multiple versions use the version templates, which name the files like Jellyfin
+    /// expects multiple versions of a movie or episode (e.g. `Movie (2020) - 1080p`). The `version`
+    /// is the resolution of the file, or the label of its profile if several versions share a resolution.
+    ///
+    /// # Arguments
+    ///
+    /// * `outputs` - The processed files, as version, index of the title and path of the file.
+    ///
+    /// # Returns
+    ///
+    /// The rendered names, in the order of the files.
+    async fn output_names(&self, outputs: &[(&str, usize, String)]) -> Vec<String> {
+        let multiple_versions = self.versions.len() > 1;
+
+        let mut variables: HashMap<&str, String> = HashMap::new();
+        variables.insert("source", source_type(&self.disc_type).to_string());
+
+        let (template, episodes) = if self.params.media_type == "tv_show" {
+            let metadata = serde_json::from_str::<RipTvShowMetadata>(&self.params.metadata).unwrap();
+            let tmdb_episodes = self.fetch_episodes(&metadata).await;
+
+            variables.insert("title", metadata.title.clone());
+            variables.insert("year", metadata.year.map(|year| year.to_string()).unwrap_or_default());
+            variables.insert("tvdb_id", self.ids.tvdb_id.map(|id| id.to_string()).unwrap_or_default());
+            variables.insert("tmdb_id", self.ids.tmdb_id.map(|id| id.to_string()).unwrap_or_default());
+            variables.insert("imdb_id", self.ids.imdb_id.clone().unwrap_or_default());
+
+            let episodes = self
+                .episodes
+                .iter()
+                .zip(tmdb_episodes)
+                .map(|(&(season_number, episode_number), episode)| {
+                    let episode_title = episode.map(|episode| episode.name).unwrap_or_default();
+                    (format!("{:0>2}", season_number), format!("{:0>2}", episode_number), episode_title)
+                })
+                .collect::<Vec<(String, String, String)>>();
+
+            let template = if multiple_versions { &self.state.naming_templates.tv_show_version } else { &self.state.naming_templates.tv_show };
+
+            (template, episodes)
+        } else {
+            let metadata = serde_json::from_str::<RipMovieMetadata>(&self.params.metadata).unwrap();
+
+            let movie = self.fetch_movie(&metadata).await;
+            let country = metadata.lang.rsplit('-').next().unwrap_or_default().to_uppercase();
+
+            variables.insert("imdb_id", self.ids.imdb_id.clone().unwrap_or_default());
+            variables.insert("original_title", movie.as_ref().map(|movie| movie.original_title.clone()).unwrap_or_default());
+            variables.insert(
+                "collection",
+                movie
+                    .as_ref()
+                    .and_then(|movie| movie.belongs_to_collection.as_ref())
+                    .map(|collection| collection.name.clone())
+                    .unwrap_or_default(),
+            );
+            variables.insert(
+                "certification",
+                movie
+                    .as_ref()
+                    .and_then(|movie| movie.certification(&country).or_else(|| movie.certification("US")))
+                    .unwrap_or_default()
+                    .to_string(),
+            );
+
+            variables.insert("title", metadata.title);
+            variables.insert("year", metadata.year.map(|year| year.to_string()).unwrap_or_default());
+            variables.insert("tmdb_id", self.ids.tmdb_id.map(|id| id.to_string()).unwrap_or_default());
+
+            let template = if multiple_versions { &self.state.naming_templates.movie_version } else { &self.state.naming_templates.movie };
+
+            (template, vec![])
+        };
+
+        let mut videos = vec![];
+
+        for (_, _, file) in outputs {
+            let ffprobe_command = self.state.ffprobe_command.clone();
+            let probe_file_path = file.clone();
+
+            let video = match tokio::task::spawn_blocking(move || probe_file(&ffprobe_command, &probe_file_path)).await {
+                Ok(Ok(info)) => info
+                    .streams
+                    .iter()
+                    .find(|stream| stream.codec_type == "video")
+                    .map(|video| (resolution_label(video.width.unwrap_or(0), video.height.unwrap_or(0)), codec_label(&video.codec_name))),
+                _ => None,
+            };
+
+            videos.push(video);
+        }
+
+        let mut names = vec![];
+
+        for (k, (version, i, file)) in outputs.iter().enumerate() {
+            let mut variables = variables.clone();
+            variables.insert("file", Path::new(file).file_stem().unwrap_or_default().to_string_lossy().to_string());
+            variables.insert("profile", self.version_label(version));
+
+            if let Some((season, episode, episode_title)) = episodes.get(*i) {
+                variables.insert("season", season.clone());
+                variables.insert("episode", episode.clone());
+                variables.insert("episode_title", episode_title.clone());
+            }
+
+            if let Some((resolution, codec)) = &videos[k] {
+                variables.insert("resolution", resolution.to_string());
+                variables.insert("codec", codec.clone());
+            }
+
+            // versions of the same title are told apart by their resolution, as long as it is unique
+            let shared_resolution = outputs
+                .iter()
+                .zip(videos.iter())
+                .filter(|((_, other, _), video)| other == i && video.as_ref().map(|(resolution, _)| resolution) == videos[k].as_ref().map(|(resolution, _)| resolution))
+                .count()
+                > 1;
+
+            match &videos[k] {
+                Some((resolution, _)) if !shared_resolution => variables.insert("version", resolution.to_string()),
+                _ => variables.insert("version", self.version_label(version)),
+            };
+
+            names.push(render_template(template, &variables));
+        }
+
+        names
+    }
+
+    /// Returns the label of a version, which is the label of its encoding profile or `Remux`.
+    fn version_label(&self, version: &str) -> String {
+        if version == REMUX_VERSION {
+            return "Remux".to_string();
+        }
+
+        self.profiles
+            .iter()
+            .find(|p| p.id == version)
+            .map(|profile| profile.label.clone())
+            .unwrap_or_else(|| version.to_string())
+    }
+
+    /// Writes the title, year, episode names and chapter names into the processed files.
+    ///
+    /// For TV shows, the episodes of the season are fetched from TMDB, so every file is
+    /// labelled with the name of its episode. Files that cannot be tagged are kept untouched,
+    /// the metadata is only a convenience for players outside of Jellyfin.
+    pub async fn tag_files(&self) {
+        let metadata = if self.params.media_type == "tv_show" {
+            let metadata = serde_json::from_str::<RipTvShowMetadata>(&self.params.metadata).unwrap();
+
+            self.fetch_episodes(&metadata)
+                .await
+                .into_iter()
+                .map(|episode| ContainerMetadata { title: metadata.title.clone(), year: metadata.year, episode, chapter_names: metadata.chapter_names.clone() })
+                .collect::<Vec<ContainerMetadata>>()
+        } else {
+            let metadata = serde_json::from_str::<RipMovieMetadata>(&self.params.metadata).unwrap();
+            let metadata = ContainerMetadata { title: metadata.title, year: metadata.year, episode: None, chapter_names: metadata.chapter_names };
+
+            vec![metadata; self.titles.len()]
+        };
+
+        for (_, i, file) in self.outputs() {
+            let Some(metadata) = metadata.get(i).cloned() else { continue };
+            let command = self.state.mkvpropedit_command.clone();
+            let ffprobe_command = self.state.ffprobe_command.clone();
+
+            let tag_handle = tokio::task::spawn_blocking(move || write_metadata(&command, &ffprobe_command, &file, &metadata));
+
+            match tag_handle.await {
+                Ok(Err(e)) => error!("failed to write metadata: {:?}", e),
+                Err(e) => error!("Tagging task failed: {:?}", e),
+                _ => {}
+            }
+        }
+    }
+
+    /// Verifies the encoded files of all encoded versions against their ripped sources.
+    ///
+    /// Each file is checked with the configured verification options and progress updates
+    /// are sent over the WebSocket connection. If any file fails the verification, the job
+    /// is held: the files are kept in the job directory together with a `verification.json`
+    /// report, and a `job_held` message is sent instead of uploading the files.
+    ///
+    /// # Arguments
+    ///
+    /// * `socket_sender` - A mutable reference to the WebSocket sender for sending messages.
+    ///
+    /// # Returns
+    ///
+    /// `true` if all files passed the verification, `false` if the job is held.
+    pub async fn verify_files(&self, socket_sender: &mut SplitSink<WebSocket, Message>) -> bool {
+        let mut reports = vec![];
+        let mut reasons = vec![];
+
+        let outputs = self.outputs().into_iter().filter(|(version, _, _)| *version != REMUX_VERSION).collect::<Vec<_>>();
+        let total = outputs.len();
+
+        for (i, (version, title_index, output)) in outputs.into_iter().enumerate() {
+            let title = &self.titles[title_index];
+            let source = self.source_file(title);
+            let label = if self.versions.len() > 1 { format!("{} ({})", title.output_file_name, version) } else { title.output_file_name.clone() };
+
+            let ffprobe_command = self.state.ffprobe_command.clone();
+            let ffmpeg_command = self.state.ffmpeg_command.clone();
+            let options = self.state.verification_options.clone();
+
+            // the encodes are cropped and deinterlaced as decided by the analysis, so the source has to be compared the same way
+            let analysis = match fs::read(self.analysis_file(title)).await {
+                Ok(record) => serde_json::from_slice::<SourceAnalysis>(&record).ok(),
+                Err(_) => None,
+            };
+
+            let verify_handle = tokio::task::spawn_blocking(move || verify_file(&ffprobe_command, &ffmpeg_command, &source, &output, analysis.as_ref(), &options));
+
+            match verify_handle.await {
+                Ok(Ok(report)) => {
+                    reasons.extend(report.reasons.iter().map(|reason| format!("{}: {}", label, reason)));
+                    reports.push(report);
+                }
+                Ok(Err(e)) => {
+                    error!("failed to verify file: {:?}", e);
+                    reasons.push(format!("{}: verification failed", label));
+                }
+                Err(e) => {
+                    error!("Verification task failed: {:?}", e);
+                    reasons.push(format!("{}: verification failed", label));
+                }
+            }
+
+            let message = format!(
+                r#"{{ "type": "verification_progress", "payload": {{ "label": "{}", "progress": {}, "step": {}, "eta": {} }} }}"#,
+                "Verifying",
+                (i + 1) as f32 / total as f32,
+                i,
+                0
+            );
+
+            if let Err(e) = socket_sender.send(Message::Text(message)).await {
+                error!("Failed to send WebSocket message: {:?}", e);
+            }
+        }
+
+        if reasons.is_empty() {
+            return true;
+        }
+
+        error!("holding job {} because verification failed: {:?}", self.job_dir, reasons);
+
+        let report_file = Path::new(&self.job_dir).join("verification.json");
+        if let Err(e) = fs::write(report_file, serde_json::to_string_pretty(&reports).unwrap()).await {
+            error!("failed to write verification report: {:?}", e);
+        }
+
+        let message = serde_json::json!({ "type": "job_held", "payload": { "label": reasons.join("; "), "progress": 1, "step": 0, "eta": 0 } });
+
+        if let Err(e) = socket_sender.send(Message::Text(message.to_string())).await {
+            error!("Failed to send WebSocket message: {:?}", e);
+        }
+
+        false
+    }
+
+    /// Moves the raw rips of the job into the archive directory.
+    ///
+    /// Movies are archived as `<archive_dir>/<title>/<file>`, TV shows as
+    /// `<archive_dir>/<title>/Season XX/SXXEXX_<file>`.
+    pub async fn archive_files(&self) {
+        let Some(archive_dir) = self.state.archive_dir.clone() else {
+            error!("no archive directory configured, raw rips are not archived");
+            return;
+        };
+
+        for (i, title) in self.titles.iter().enumerate() {
+            let source = Path::new(&self.job_dir).join(&title.output_file_name);
+
+            if !source.exists() {
+                continue;
+            }
+
+            let destination = if self.params.media_type == "tv_show" {
+                let metadata = serde_json::from_str::<RipTvShowMetadata>(&self.params.metadata).unwrap();
+                let (season, episode) = self.episodes[i];
+
+                Path::new(&archive_dir)
+                    .join(&metadata.title)
+                    .join(format!("Season {:0>2}", season))
+                    .join(format!("S{:0>2}E{:0>2}_{}", season, episode, title.output_file_name))
+            } else {
+                let metadata = serde_json::from_str::<RipMovieMetadata>(&self.params.metadata).unwrap();
+                Path::new(&archive_dir).join(&metadata.title).join(&title.output_file_name)
+            };
+
+            info!("Archiving raw rip {:?} to {:?}", source, destination);
+
+            let archived_file = destination.to_string_lossy().to_string();
+
+            let archive_handle = tokio::task::spawn_blocking(move || {
+                let (progress_sender, _progress_receiver) = mpsc::channel::<f64>();
+                std::fs::create_dir_all(destination.parent().unwrap())?;
+                move_file_with_progress(source, destination, progress_sender)
+            });
+
+            match archive_handle.await {
+                Ok(Err(e)) => error!("failed to archive raw rip: {:?}", e),
+                Err(e) => error!("Archive task failed: {:?}", e),
+                Ok(Ok(_)) => {
+                    // the previews of the title keep being taken from the raw rip in its new place
+                    if let Err(e) = record_preview_source(&self.state.preview_dir, &self.fingerprint, title.id, &archived_file) {
+                        error!("failed to record archived title as preview source: {:?}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Downloads the artwork of the ripped media from TMDB into the job directory.
+    ///
+    /// Movies and TV shows get their poster as `poster.jpg` and their backdrop as `fanart.jpg`,
+    /// TV shows additionally the posters of the aired seasons of the ripped episodes as
+    /// `Season XX/poster.jpg`, which is how Jellyfin and other media servers pick up local
+    /// artwork. Missing artwork is skipped.
+    ///
+    /// # Returns
+    ///
+    /// The downloaded artwork, as local file and path relative to the folder of the movie or TV show.
+    async fn download_artwork(&self) -> Vec<(String, String)> {
+        let Some(tmdb_id) = self.ids.tmdb_id else { return vec![] };
+        let mut images: Vec<(Option<String>, ImageKind, String)> = vec![];
+
+        if self.params.media_type == "tv_show" {
+            let metadata = serde_json::from_str::<RipTvShowMetadata>(&self.params.metadata).unwrap();
+
+            match self.state.tmdb_client.get_tv_series(tmdb_id, &metadata.lang).await {
+                Ok(tv_series) => {
+                    let mut seasons = self.episodes.iter().map(|(season_number, _)| *season_number).collect::<Vec<u16>>();
+                    seasons.sort();
+                    seasons.dedup();
+
+                    for season_number in seasons {
+                        let season_poster = tv_series.season(season_number).and_then(|season| season.poster_path.clone());
+                        images.push((season_poster, ImageKind::Poster, format!("Season {:0>2}/poster.jpg", season_number)));
+                    }
+
+                    images.push((tv_series.poster_path, ImageKind::Poster, "poster.jpg".to_string()));
+                    images.push((tv_series.backdrop_path, ImageKind::Backdrop, "fanart.jpg".to_string()));
+                }
+                Err(e) => error!("failed to fetch artwork of tv show {}: {:?}", tmdb_id, e),
+            }
+        } else {
+            let metadata = serde_json::from_str::<RipMovieMetadata>(&self.params.metadata).unwrap();
+
+            if let Some(movie) = self.fetch_movie(&metadata).await {
+                images.push((movie.poster_path, ImageKind::Poster, "poster.jpg".to_string()));
+                images.push((movie.backdrop_path, ImageKind::Backdrop, "fanart.jpg".to_string()));
+            }
+        }
+
+        let artwork_dir = Path::new(&self.job_dir).join("artwork");
+        let mut artwork = vec![];
+
+        for (image, kind, name) in images {
+            let Some(image) = image else { continue };
+            let file = artwork_dir.join(&name);
+
+            match self.state.tmdb_client.save_artwork(&image, kind, &file).await {
+                Ok(()) => artwork.push((file.to_string_lossy().to_string(), name)),
+                Err(e) => error!("failed to download artwork {}: {:?}", name, e),
+            }
+        }
+
+        artwork
+    }
+
     /// Uploads the encoded files to the specified remote server.
     ///
     /// This function spawns a new task to handle the file upload process and sends
-    /// progress updates over the WebSocket connection.
+    /// progress updates over the WebSocket connection. All versions of a title are
+    /// uploaded next to each other. Since Radarr and Sonarr only know a single file per
+    /// movie or episode, jobs with multiple versions are rescanned without renaming.
+    /// If enabled, the artwork is uploaded into the folder of the movie or TV show as well.
     ///
     /// # Arguments
     ///
@@ -229,87 +1279,154 @@ impl RippingHandler {
     pub async fn upload_files(&self, socket_sender: Arc<Mutex<SplitSink<WebSocket, Message>>>) {
         let (upload_sender, upload_receiver) = mpsc::channel::<(&str, Option<UploadProgressPayload>)>();
 
-        let files: Vec<String> = self
-            .titles
+        let mut outputs = self.outputs();
+
+        // movies are a single title, only the first title of every version is uploaded
+        if self.params.media_type == "movie" {
+            outputs.retain(|(_, i, _)| *i == 0);
+        }
+
+        let names = self.output_names(&outputs).await;
+        let seasons: Vec<u16> = outputs
             .iter()
-            .map(|title| {
-                Path::new(&self.state.output_dir)
-                    .join("encoding/")
-                    .join(&title.output_file_name)
-                    .to_string_lossy()
-                    .to_string()
-            })
+            .map(|(_, i, _)| self.episodes.get(*i).map(|(season_number, _)| *season_number).unwrap_or_default())
             .collect();
+        let files: Vec<String> = outputs.into_iter().map(|(_, _, file)| file).collect();
 
         info!("Uploading Files: {:?}", files);
 
+        let artwork = if self.state.write_artwork { self.download_artwork().await } else { vec![] };
+        let artwork_id = files.len().saturating_sub(1) as u32;
+
+        let multiple_versions = self.versions.len() > 1;
+
         let cancel_flag = self.cancel_flag.clone();
         let metadata = self.params.metadata.clone();
         let media_type = self.params.media_type.clone();
         let radarr_client = self.state.radarr_client.clone();
         let sonarr_client = self.state.sonarr_client.clone();
         let jellyfin_client = self.state.jellyfin_client.clone();
-        let quality_profile_id = self.params.quality_profile.clone();
+        let quality_profile_id = self.params.quality_profile;
         let root_folder = self.params.root_folder.clone();
         let remote_host = self.state.remote_host.clone();
         let remote_user = self.state.remote_user.clone();
         let remote_password = self.state.remote_password.clone();
-        let remove_output_dir = self.state.output_dir.clone();
+        let job_dir = self.job_dir.clone();
+        let ids = self.ids.clone();
 
         let upload_handle = tokio::spawn(async move {
             if media_type == "movie" {
                 let metadata = serde_json::from_str::<RipMovieMetadata>(&metadata).unwrap();
 
-                let file = files.first().unwrap();
-
-                info!("Uploading movie: {}", file);
-
                 let movie = radarr_client
-                    .create_movie(metadata.tmdb_id, &metadata.title, quality_profile_id, &root_folder)
+                    .create_movie(ids.tmdb_id.unwrap_or_default(), &metadata.title, quality_profile_id, &root_folder)
                     .await
                     .unwrap();
 
-                let local_file_name = Path::new(&file).file_name().unwrap().to_string_lossy().to_string();
-                let remote_path = Path::new(&movie.path).join(format!("[Bluray-1080p]_{}", local_file_name));
+                for (i, file) in files.iter().enumerate() {
+                    info!("Uploading movie: {}", file);
+
+                    let remote_path = Path::new(&movie.path).join(remote_file_name(file, file, &names[i]));
+
+                    if let Err(e) =
+                        upload_file_with_sftp(file, remote_path.to_str().unwrap(), i as u32, &remote_host, &remote_user, &remote_password, &cancel_flag, &upload_sender)
+                    {
+                        error!("failed to upload file: {:?}", e);
+                    }
+
+                    for sidecar in sidecar_files(file) {
+                        let remote_path = Path::new(&movie.path).join(remote_file_name(file, &sidecar, &names[i]));
+
+                        if let Err(e) = upload_file_with_sftp(
+                            &sidecar,
+                            remote_path.to_str().unwrap(),
+                            i as u32,
+                            &remote_host,
+                            &remote_user,
+                            &remote_password,
+                            &cancel_flag,
+                            &upload_sender,
+                        ) {
+                            error!("failed to upload subtitle file: {:?}", e);
+                        }
+                    }
+                }
+
+                for (file, name) in artwork.iter() {
+                    let remote_path = Path::new(&movie.path).join(name);
 
-                if let Err(e) = upload_file_with_sftp(&file, remote_path.to_str().unwrap(), 0, &remote_host, &remote_user, &remote_password, &cancel_flag, &upload_sender)
-                {
-                    error!("failed to upload file: {:?}", e);
+                    if let Err(e) =
+                        upload_file_with_sftp(file, remote_path.to_str().unwrap(), artwork_id, &remote_host, &remote_user, &remote_password, &cancel_flag, &upload_sender)
+                    {
+                        error!("failed to upload artwork: {:?}", e);
+                    }
                 }
 
-                radarr_client.scan_rename_movie(movie.id).await.ok();
+                if multiple_versions {
+                    radarr_client.rescan_movie(movie.id).await.ok();
+                } else {
+                    radarr_client.scan_rename_movie(movie.id).await.ok();
+                }
             }
 
             if media_type == "tv_show" {
                 let metadata = serde_json::from_str::<RipTvShowMetadata>(&metadata).unwrap();
 
                 let tv_show = sonarr_client
-                    .create_tv_show(metadata.tvdb_id, &metadata.title, &metadata.series_type, quality_profile_id, &root_folder)
+                    .create_tv_show(ids.tvdb_id.unwrap_or_default(), &metadata.title, &metadata.series_type, quality_profile_id, &root_folder)
                     .await
                     .unwrap();
 
                 for (i, file) in files.iter().enumerate() {
                     info!("Uploading TV show: {}", file);
 
-                    let season_path = Path::new(&tv_show.path).join(format!("Season {:0>2}", metadata.season));
-                    let file_name = Path::new(&file).file_name().unwrap().to_string_lossy().to_string();
-                    let prefixed_file_name = format!("[Bluray-1080p]_S{:0>2}E{:0>2}_{}", metadata.season, metadata.episodes[i], file_name);
-                    let remote_path = season_path.join(prefixed_file_name);
+                    let season_path = Path::new(&tv_show.path).join(format!("Season {:0>2}", seasons[i]));
+                    let remote_path = season_path.join(remote_file_name(file, file, &names[i]));
 
                     if let Err(e) =
-                        upload_file_with_sftp(&file, remote_path.to_str().unwrap(), i as u32, &remote_host, &remote_user, &remote_password, &cancel_flag, &upload_sender)
+                        upload_file_with_sftp(file, remote_path.to_str().unwrap(), i as u32, &remote_host, &remote_user, &remote_password, &cancel_flag, &upload_sender)
                     {
                         error!("failed to upload file: {:?}", e);
                     }
+
+                    for sidecar in sidecar_files(file) {
+                        let remote_path = season_path.join(remote_file_name(file, &sidecar, &names[i]));
+
+                        if let Err(e) = upload_file_with_sftp(
+                            &sidecar,
+                            remote_path.to_str().unwrap(),
+                            i as u32,
+                            &remote_host,
+                            &remote_user,
+                            &remote_password,
+                            &cancel_flag,
+                            &upload_sender,
+                        ) {
+                            error!("failed to upload subtitle file: {:?}", e);
+                        }
+                    }
+                }
+
+                for (file, name) in artwork.iter() {
+                    let remote_path = Path::new(&tv_show.path).join(name);
+
+                    if let Err(e) =
+                        upload_file_with_sftp(file, remote_path.to_str().unwrap(), artwork_id, &remote_host, &remote_user, &remote_password, &cancel_flag, &upload_sender)
+                    {
+                        error!("failed to upload artwork: {:?}", e);
+                    }
                 }
 
-                sonarr_client.scan_rename_tv_show(tv_show.id).await.ok();
+                if multiple_versions {
+                    sonarr_client.rescan_tv_show(tv_show.id).await.ok();
+                } else {
+                    sonarr_client.scan_rename_tv_show(tv_show.id).await.ok();
+                }
             }
 
             jellyfin_client.library_scan().await.ok();
 
-            fs::remove_dir_all(Path::new(&remove_output_dir)).await.ok();
-            fs::create_dir_all(Path::new(&remove_output_dir)).await.ok();
+            fs::remove_dir_all(Path::new(&job_dir)).await.ok();
 
             upload_sender.send(("done", None)).unwrap();
         });
@@ -356,32 +1473,195 @@ impl RippingHandler {
 
     /// Handles the entire ripping process from ripping to uploading files.
     ///
-    /// This function coordinates the cancellation, ripping, encoding, and uploading
-    /// processes, and sends updates over the WebSocket connection.
+    /// The stages run in order and report their progress over the WebSocket connection. The job
+    /// stops early if it is refused, cancelled or held. The MakeMKV lock is released as soon as
+    /// ripping is done, so the next disc can be ripped while the titles of this disc are encoding.
+    ///
+    /// 1. Listening for cancellation requests.
+    /// 2. Resolving the media IDs and the aired episode numbers, refusing the job on failure.
+    /// 3. Checking that the encoding profiles preserve the dynamic range of the titles.
+    /// 4. Ripping the titles, which enqueues their encoding jobs as soon as they are ripped.
+    /// 5. Remuxing and encoding all versions.
+    /// 6. Downmixing audio and converting subtitles with OCR, if enabled.
+    /// 7. Flagging forced subtitles and tagging the files.
+    /// 8. Verifying the encoded files, if enabled, holding the job on failure.
+    /// 9. Archiving the raw rips, if the job mode keeps them.
+    /// 10. Uploading the files and the artwork.
     ///
     /// # Arguments
     ///
     /// * `socket` - The WebSocket connection for sending and receiving messages.
-    pub async fn handle(self, socket: WebSocket) {
+    pub async fn handle(mut self, socket: WebSocket) {
         let (mut socket_sender, socket_receiver) = socket.split();
 
         self.handle_cancellation(socket_receiver).await;
 
-        self.rip_titles(&mut socket_sender).await;
+        if !self.resolve_ids(&mut socket_sender).await {
+            return;
+        }
+
+        if !self.check_dynamic_range(&mut socket_sender).await {
+            return;
+        }
+
+        let (encoding_sender, encoding_receiver) = mpsc::channel::<(&'static str, Option<EncodingProgressPayload>)>();
+
+        let jobs = self.rip_titles(&mut socket_sender, &encoding_sender).await;
         if self.cancel_flag.load(Ordering::Relaxed) {
             return;
         }
 
-        self.encode_files(&mut socket_sender).await;
+        if self.versions.iter().any(|version| version == REMUX_VERSION) {
+            self.remux_files(&mut socket_sender, REMUX_VERSION).await;
+        }
+
+        if !self.encode_versions().is_empty() {
+            self.encode_files(&mut socket_sender, encoding_receiver, jobs).await;
+        }
+
         if self.cancel_flag.load(Ordering::Relaxed) {
             return;
         }
 
+        if self.state.downmix_options.enabled {
+            self.downmix_audio().await;
+        }
+
+        if self.state.subtitle_ocr_options.enabled {
+            self.ocr_subtitles(&mut socket_sender).await;
+        }
+
+        self.flag_subtitles().await;
+        self.tag_files().await;
+
+        if !self.encode_versions().is_empty() && self.state.verification_options.enabled && !self.verify_files(&mut socket_sender).await {
+            return;
+        }
+
+        if self.params.job_mode == JobMode::KeepBoth {
+            self.archive_files().await;
+        }
+
         let mutex_socket_sender: Arc<Mutex<SplitSink<WebSocket, Message>>> = Arc::new(Mutex::new(socket_sender));
         self.upload_files(mutex_socket_sender).await;
     }
 }
 
+/// Returns the versions a job produces of every title, without duplicates.
+///
+/// Remux jobs produce a single remux version. Otherwise the versions are the selected encoding
+/// profiles, where `remux` selects a remux version next to the encoded ones.
+fn job_versions(params: &RipPayload) -> Vec<String> {
+    if params.job_mode == JobMode::Remux {
+        return vec![REMUX_VERSION.to_string()];
+    }
+
+    let profiles = if params.encoding_profiles.is_empty() { std::slice::from_ref(&params.encoding_profile) } else { &params.encoding_profiles[..] };
+
+    let mut versions: Vec<String> = vec![];
+    for profile in profiles {
+        if !versions.contains(profile) {
+            versions.push(profile.clone());
+        }
+    }
+
+    versions
+}
+
+/// Adds encoding jobs to the encoding queue, reporting jobs that cannot be enqueued as failed.
+fn enqueue_jobs(queue: &EncodingQueue, jobs: Vec<EncodingJob>) {
+    for job in jobs {
+        let sender = job.sender.clone();
+        let payload = Some(EncodingProgressPayload { progress: 1.0, eta: 0.0, step: job.step });
+
+        if let Err(e) = queue.enqueue(job) {
+            error!("failed to enqueue title for encoding: {:?}", e);
+            sender.send(("failed", payload)).ok();
+        }
+    }
+}
+
+/// Enqueues encoding jobs into the shared encoding queue when dropped.
+///
+/// Jobs that are prepared in a background thread (e.g. analyzed first) are held by the guard, so they
+/// are enqueued when the thread ends, including when it panics.
+struct EnqueueGuard {
+    queue: EncodingQueue,
+    jobs: Vec<EncodingJob>,
+}
+
+impl Drop for EnqueueGuard {
+    fn drop(&mut self) {
+        enqueue_jobs(&self.queue, std::mem::take(&mut self.jobs));
+    }
+}
+
+/// Returns the name a file belonging to a processed file is uploaded with.
+///
+/// The suffix of `file` after the stem of the processed file (e.g. `.mkv` or `.de.forced.srt`) is appended
+/// to the rendered `name`, so sidecar files keep their language and flags.
+fn remote_file_name(processed_file: &str, file: &str, name: &str) -> String {
+    let stem = Path::new(processed_file).file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let file_name = Path::new(file).file_name().unwrap_or_default().to_string_lossy().to_string();
+
+    format!("{}{}", name, file_name.strip_prefix(&stem).unwrap_or(&file_name))
+}
+
+/// Maps the MakeMKV disc type (e.g. `Blu-ray disc`) to the source name used by Radarr and Sonarr.
+fn source_type(disc_type: &str) -> &'static str {
+    let disc_type = disc_type.to_lowercase();
+
+    if disc_type.contains("hd-dvd") {
+        "HDDVD"
+    } else if disc_type.contains("dvd") {
+        "DVD"
+    } else {
+        "Bluray"
+    }
+}
+
+/// Returns the resolution label of a video (e.g. `1080p`), using the width as well since the video might be cropped.
+fn resolution_label(width: u32, height: u32) -> &'static str {
+    match (width, height) {
+        (w, h) if w >= 3200 || h >= 1800 => "2160p",
+        (w, h) if w >= 1700 || h >= 1000 => "1080p",
+        (w, h) if w >= 1200 || h >= 700 => "720p",
+        (_, h) if h >= 540 => "576p",
+        _ => "480p",
+    }
+}
+
+/// Maps an ffprobe codec name to the codec name commonly used in release names.
+fn codec_label(codec_name: &str) -> String {
+    match codec_name {
+        "h264" => "x264".to_string(),
+        "hevc" => "x265".to_string(),
+        "av1" => "AV1".to_string(),
+        "mpeg2video" => "MPEG2".to_string(),
+        "vc1" => "VC1".to_string(),
+        codec_name => codec_name.to_string(),
+    }
+}
+
+/// Returns the subtitle files placed beside a processed file, e.g. `title_t00.de.forced.srt` for `title_t00.mkv`.
+fn sidecar_files(file: &str) -> Vec<String> {
+    let path = Path::new(file);
+    let (Some(dir), Some(stem)) = (path.parent(), path.file_stem()) else { return vec![] };
+    let prefix = format!("{}.", stem.to_string_lossy());
+
+    let Ok(entries) = std::fs::read_dir(dir) else { return vec![] };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.starts_with(&prefix) && name.ends_with(".srt")
+        })
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
+
 /// Handles WebSocket connections to rip Blu-ray discs using MakeMKV and stream progress updates.
 ///
 /// This handler accepts a WebSocket connection, receives ripping parameters, and initiates
@@ -1,12 +1,19 @@
+use axum::extract::FromRef;
 use axum::http::{header, HeaderValue, Method};
-use axum::{routing::get, Router};
+use axum::routing::{get, post};
+use axum::Router;
+use handbrake_core::{
+    AnalysisOptions, DownmixOptions, Encoder, EncodingHistory, EncodingQueue, FfmpegEncoder, HandBrakeEncoder, PreviewOptions, ProcessPriority, SubtitleOcrOptions,
+    VerificationOptions,
+};
 use serde::Deserialize;
 use servarr_clients::{JellyfinClient, RadarrClient, SonarrClient};
 use std::fs::File;
 use std::io::Read;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::{fmt::Debug, net::SocketAddr};
-use tmdb_client::TmdbClient;
+use tmdb_client::{CacheOptions, ImageOptions, RateLimitOptions, TmdbClient};
 use tower_http::trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer};
 use tower_http::{cors::CorsLayer, services::ServeDir};
 use tracing::{info, Level};
@@ -20,6 +27,17 @@ struct Config {
     output_dir: String,
     encoding_profiles_path: String,
     tmdb_key: String,
+    #[serde(default)]
+    tmdb_base_url: Option<String>,
+    #[serde(default)]
+    tmdb_cache: CacheOptions,
+    #[serde(default)]
+    tmdb_rate_limit: RateLimitOptions,
+    #[serde(default)]
+    tmdb_images: ImageOptions,
+    /// Whether the poster and backdrop are uploaded next to the media as `poster.jpg` and `fanart.jpg`.
+    #[serde(default = "default_write_artwork")]
+    write_artwork: bool,
     radarr_endpoint: String,
     radarr_api_key: String,
     sonarr_endpoint: String,
@@ -30,16 +48,142 @@ struct Config {
     remote_host: String,
     remote_user: String,
     remote_password: String,
+    #[serde(default)]
+    encoder: EncoderBackend,
+    #[serde(default = "default_encoding_workers")]
+    encoding_workers: usize,
+    #[serde(default)]
+    encoding_priority: ProcessPriority,
+    #[serde(default)]
+    encoding_history_path: Option<String>,
+    #[serde(default = "default_mkvmerge_command")]
+    mkvmerge_command: String,
+    #[serde(default)]
+    archive_dir: Option<String>,
+    #[serde(default = "default_ffprobe_command")]
+    ffprobe_command: String,
+    #[serde(default = "default_ffmpeg_command")]
+    ffmpeg_command: String,
+    #[serde(default = "default_mkvpropedit_command")]
+    mkvpropedit_command: String,
+    #[serde(default)]
+    verification: VerificationOptions,
+    #[serde(default)]
+    subtitle_ocr: SubtitleOcrOptions,
+    #[serde(default)]
+    source_analysis: AnalysisOptions,
+    #[serde(default)]
+    hdr_handling: HdrHandling,
+    #[serde(default)]
+    downmix: DownmixOptions,
+    #[serde(default)]
+    preview_dir: Option<String>,
+    #[serde(default = "default_preview_rip_size_mb")]
+    preview_rip_size_mb: u64,
+    #[serde(default)]
+    previews: PreviewOptions,
+    #[serde(default)]
+    naming: NamingTemplates,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+enum EncoderBackend {
+    #[default]
+    Handbrake,
+    Ffmpeg,
+}
+
+/// The templates the uploaded files are named with, per media type and without file extension.
+///
+/// Available variables are `title`, `year`, `tmdb_id`, `tvdb_id`, `imdb_id`, `season`, `episode`,
+/// `episode_title`, `source`, `resolution`, `codec`, `profile`, `version` and `file` (the name of the
+/// ripped file). Movies additionally provide `original_title`, `collection` and `certification`. The
+/// version templates are used for jobs with multiple versions and have to follow Jellyfin's naming of
+/// multiple versions, i.e. the name followed by ` - ` and the version.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+struct NamingTemplates {
+    movie: String,
+    tv_show: String,
+    movie_version: String,
+    tv_show_version: String,
+}
+
+impl Default for NamingTemplates {
+    fn default() -> Self {
+        Self {
+            movie: "{title} ({year}) [{source}-{resolution}]".to_string(),
+            tv_show: "{title} - S{season}E{episode} - {episode_title} [{source}-{resolution}]".to_string(),
+            movie_version: "{title} ({year}) - {version}".to_string(),
+            tv_show_version: "{title} - S{season}E{episode} - {version}".to_string(),
+        }
+    }
+}
+
+/// How jobs are handled whose HDR or Dolby Vision titles would be encoded with a profile that doesn't preserve them.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum HdrHandling {
+    /// The job is refused before ripping.
+    #[default]
+    Refuse,
+    /// The job is switched to remuxing, so the titles keep their dynamic range.
+    Remux,
+}
+
+fn default_encoding_workers() -> usize {
+    1
+}
+
+fn default_mkvmerge_command() -> String {
+    "mkvmerge".to_string()
+}
+
+fn default_ffprobe_command() -> String {
+    "ffprobe".to_string()
+}
+
+fn default_ffmpeg_command() -> String {
+    "ffmpeg".to_string()
+}
+
+fn default_mkvpropedit_command() -> String {
+    "mkvpropedit".to_string()
+}
+
+fn default_preview_rip_size_mb() -> u64 {
+    256
+}
+
+fn default_write_artwork() -> bool {
+    true
 }
 
 #[derive(Debug, Clone)]
 struct AppState {
     encoding_profiles_path: String,
     makemkv_command: String,
-    handbrake_command: String,
+    mkvmerge_command: String,
+    ffprobe_command: String,
+    ffmpeg_command: String,
+    mkvpropedit_command: String,
     output_dir: String,
+    archive_dir: Option<String>,
     tmdb_client: TmdbClient,
     makemkv_mutex: Arc<Mutex<()>>,
+    encoding_queue: EncodingQueue,
+    encoding_history: EncodingHistory,
+    verification_options: VerificationOptions,
+    subtitle_ocr_options: SubtitleOcrOptions,
+    analysis_options: AnalysisOptions,
+    hdr_handling: HdrHandling,
+    downmix_options: DownmixOptions,
+    preview_dir: String,
+    preview_rip_size: u64,
+    preview_options: PreviewOptions,
+    naming_templates: NamingTemplates,
+    write_artwork: bool,
     radarr_client: RadarrClient,
     sonarr_client: SonarrClient,
     jellyfin_client: JellyfinClient,
@@ -48,22 +192,81 @@ struct AppState {
     remote_password: String,
 }
 
+/// Lets handlers that only talk to TMDB extract the client instead of the whole application state.
+impl FromRef<AppState> for TmdbClient {
+    fn from_ref(state: &AppState) -> Self {
+        state.tmdb_client.clone()
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let mut contents = String::new();
     File::open("config.json").unwrap().read_to_string(&mut contents).unwrap();
     let config: Config = serde_json::from_str(&contents).unwrap();
 
+    let encoding_history_path = config
+        .encoding_history_path
+        .unwrap_or_else(|| Path::new(&config.output_dir).join("encoding_history.json").to_string_lossy().to_string());
+    let encoding_history = EncodingHistory::new(&encoding_history_path);
+
+    let preview_dir = config
+        .preview_dir
+        .unwrap_or_else(|| Path::new(&config.output_dir).join("previews").to_string_lossy().to_string());
+
+    let mut tmdb_cache = config.tmdb_cache;
+    if tmdb_cache.dir.is_none() {
+        tmdb_cache.dir = Some(Path::new(&config.output_dir).join("tmdb_cache").to_string_lossy().to_string());
+    }
+
+    let mut tmdb_images = config.tmdb_images;
+    if tmdb_images.dir.is_none() {
+        tmdb_images.dir = Some(Path::new(&config.output_dir).join("tmdb_images").to_string_lossy().to_string());
+    }
+
+    let mut tmdb_client = TmdbClient::builder(&config.tmdb_key)
+        .cache(tmdb_cache)
+        .rate_limit(config.tmdb_rate_limit)
+        .images(tmdb_images);
+    if let Some(tmdb_base_url) = &config.tmdb_base_url {
+        tmdb_client = tmdb_client.base_url(tmdb_base_url);
+    }
+
+    let encoder: Arc<dyn Encoder> = match config.encoder {
+        EncoderBackend::Handbrake => {
+            Arc::new(HandBrakeEncoder { command: config.handbrake_command, ffprobe_command: config.ffprobe_command.clone(), priority: config.encoding_priority })
+        }
+        EncoderBackend::Ffmpeg => {
+            Arc::new(FfmpegEncoder { command: config.ffmpeg_command.clone(), ffprobe_command: config.ffprobe_command.clone(), priority: config.encoding_priority })
+        }
+    };
+
     let state = AppState {
         makemkv_command: config.makemkv_command,
-        handbrake_command: config.handbrake_command,
+        mkvmerge_command: config.mkvmerge_command,
+        ffprobe_command: config.ffprobe_command,
+        ffmpeg_command: config.ffmpeg_command,
+        mkvpropedit_command: config.mkvpropedit_command,
 
         output_dir: config.output_dir,
+        archive_dir: config.archive_dir,
         encoding_profiles_path: config.encoding_profiles_path,
 
         makemkv_mutex: Arc::new(Mutex::new(())),
+        encoding_queue: EncodingQueue::new(encoder, config.encoding_workers, Some(encoding_history.clone())),
+        encoding_history,
+        verification_options: config.verification,
+        subtitle_ocr_options: config.subtitle_ocr,
+        analysis_options: config.source_analysis,
+        hdr_handling: config.hdr_handling,
+        downmix_options: config.downmix,
+        preview_dir: preview_dir.clone(),
+        preview_rip_size: config.preview_rip_size_mb * 1024 * 1024,
+        preview_options: config.previews,
+        naming_templates: config.naming,
+        write_artwork: config.write_artwork,
 
-        tmdb_client: TmdbClient::new(&config.tmdb_key),
+        tmdb_client: tmdb_client.build().unwrap(),
         radarr_client: RadarrClient::new(&config.radarr_endpoint, &config.radarr_api_key),
         sonarr_client: SonarrClient::new(&config.sonarr_endpoint, &config.sonarr_api_key),
         jellyfin_client: JellyfinClient::new(&config.jellyfin_endpoint, &config.jellyfin_api_key),
@@ -88,14 +291,24 @@ async fn main() {
         .route("/search/movie", get(handler::search_movie_handler))
         .route("/search/tv", get(handler::search_tv_show_handler))
         .route("/movie/:id", get(handler::get_movie_details_handler))
-        .route("/tv/:id", get(handler::get_tv_show_details_handler));
+        .route("/tv/:id", get(handler::get_tv_show_details_handler))
+        .route("/tv/:id/episode-groups", get(handler::get_tv_show_episode_groups_handler))
+        .route("/episode-group/:id", get(handler::get_episode_group_handler))
+        .route("/resolve/movie", get(handler::resolve_movie_ids_handler))
+        .route("/resolve/tv", get(handler::resolve_tv_show_ids_handler))
+        .route("/image/:size/:file", get(handler::get_image_handler))
+        .route("/cache/flush", post(handler::flush_cache_handler));
 
-    let handbrake_routes = Router::new().route("/encoding-presets", get(handler::get_encoding_profiles_handler));
+    let handbrake_routes = Router::new()
+        .route("/encoding-presets", get(handler::get_encoding_profiles_handler))
+        .route("/estimate", get(handler::get_encoding_estimate_handler));
 
     let makemkv_routes = Router::new()
         .route("/devices", get(handler::get_devices_handler))
         .route("/titles/movie", get(handler::get_movie_titles_handler))
         .route("/titles/tv", get(handler::get_tv_show_titles_handler))
+        .route("/previews", get(handler::get_title_previews_handler))
+        .route("/suggestions", get(handler::get_disc_suggestions_handler))
         .route("/rip", get(handler::rip_websocket_handler));
 
     let media_routes = Router::new()
@@ -108,6 +321,7 @@ async fn main() {
         .nest("/api/handbrake", handbrake_routes)
         .nest("/api/makemkv", makemkv_routes)
         .nest("/api/management", media_routes)
+        .nest_service("/previews", ServeDir::new(&preview_dir))
         .layer(cors)
         .layer(trace_layer)
         .with_state(state);
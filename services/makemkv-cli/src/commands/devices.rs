@@ -16,7 +16,7 @@ pub fn device_execution(args: &Devices) {
     let lock = Arc::new(Mutex::new(()));
     let devices = detect_devices(&args.location, &lock).unwrap();
 
-    if devices.len() == 0 {
+    if devices.is_empty() {
         return eprintln!("No devices found.");
     }
 